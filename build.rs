@@ -1,7 +1,41 @@
 extern crate cc;
 
+use std::fs;
+
 fn main() {
-    cc::Build::new()
+    println!("cargo:rerun-if-changed=c_code");
+
+    let mut build = cc::Build::new();
+    build
         .file("c_code/mikktspace.c")
-        .compile("mikktspace");
+        .file("c_code/mikktspace_f64.c");
+
+    // Pin floating point semantics in every profile. Results must not depend
+    // on the optimization level, on target defaults or on CFLAGS, and
+    // contracting multiplies and adds into FMA instructions changes them.
+    if build.get_compiler().is_like_msvc() {
+        build.flag("/fp:precise");
+    } else {
+        build
+            .flag_if_supported("-fno-fast-math")
+            .flag_if_supported("-ffp-contract=off")
+            .flag_if_supported("-fexcess-precision=standard");
+    }
+
+    build.compile("mikktspace");
+
+    // Identifies the C sources, so that cached results of a different
+    // version of the algorithm are never used.
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for path in &[
+        "c_code/mikktspace.h",
+        "c_code/mikktspace.c",
+        "c_code/mikktspace_f64.c",
+    ] {
+        for byte in fs::read(path).unwrap() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    println!("cargo:rustc-env=MIKKTSPACE_C_HASH={:016x}", hash);
 }
@@ -30,6 +30,36 @@
 
 #include "mikktspace.h"
 
+// Results are only reproducible with strict IEEE semantics. The build script
+// passes the matching compiler flags, but flags from the environment (CFLAGS)
+// may come after them, so refuse to build rather than silently diverge.
+#if defined(__FAST_MATH__)
+#error "mikktspace.c must not be compiled with -ffast-math"
+#endif
+#if defined(__clang__)
+#pragma STDC FP_CONTRACT OFF
+#endif
+
+// This file is compiled twice. Once as is and once by mikktspace_f64.c with
+// MIKK_F64 defined, which switches all internal computations and the interface
+// to double precision and renames the entry points to their F64 variants.
+#ifdef MIKK_F64
+typedef double treal;
+#define MIKK_SQRT				sqrt
+#define MIKK_FABS				fabs
+#define MIKK_REAL_MIN			DBL_MIN
+#define SMikkTSpaceInterface	SMikkTSpaceInterfaceF64
+#define SMikkTSpaceContext		SMikkTSpaceContextF64
+#define genTangSpaceDefault		genTangSpaceDefaultF64
+#define genTangSpace			genTangSpaceF64
+#define genTangSpaceEx			genTangSpaceExF64
+#else
+typedef float treal;
+#define MIKK_SQRT				sqrtf
+#define MIKK_FABS				fabsf
+#define MIKK_REAL_MIN			FLT_MIN
+#endif
+
 #define TFALSE		0
 #define TTRUE		1
 
@@ -41,7 +71,7 @@
 
 // internal structure
 typedef struct {
-	float x, y, z;
+	treal x, y, z;
 } SVec3;
 
 static tbool			veq( const SVec3 v1, const SVec3 v2 )
@@ -72,7 +102,7 @@ static SVec3		vsub( const SVec3 v1, const SVec3 v2 )
 	return vRes;
 }
 
-static SVec3		vscale(const float fS, const SVec3 v)
+static SVec3		vscale(const treal fS, const SVec3 v)
 {
 	SVec3 vRes;
 
@@ -83,14 +113,14 @@ static SVec3		vscale(const float fS, const SVec3 v)
 	return vRes;
 }
 
-static float			LengthSquared( const SVec3 v )
+static treal			LengthSquared( const SVec3 v )
 {
 	return v.x*v.x + v.y*v.y + v.z*v.z;
 }
 
-static float			Length( const SVec3 v )
+static treal			Length( const SVec3 v )
 {
-	return sqrtf(LengthSquared(v));
+	return MIKK_SQRT(LengthSquared(v));
 }
 
 static SVec3		Normalize( const SVec3 v )
@@ -98,16 +128,16 @@ static SVec3		Normalize( const SVec3 v )
 	return vscale(1 / Length(v), v);
 }
 
-static float		vdot( const SVec3 v1, const SVec3 v2)
+static treal		vdot( const SVec3 v1, const SVec3 v2)
 {
 	return v1.x*v2.x + v1.y*v2.y + v1.z*v2.z;
 }
 
 
-static tbool NotZero(const float fX)
+static tbool NotZero(const treal fX)
 {
 	// could possibly use FLT_EPSILON instead
-	return fabsf(fX) > FLT_MIN;
+	return MIKK_FABS(fX) > MIKK_REAL_MIN;
 }
 
 static tbool VNotZero(const SVec3 v)
@@ -144,7 +174,7 @@ typedef struct {
 	
 	// normalized first order face derivatives
 	SVec3 vOs, vOt;
-	float fMagS, fMagT;	// original magnitudes
+	treal fMagS, fMagT;	// original magnitudes
 
 	// determines if the current and the next triangle are a quad.
 	int iOrgFaceNumber;
@@ -154,20 +184,21 @@ typedef struct {
 
 typedef struct {
 	SVec3 vOs;
-	float fMagS;
+	treal fMagS;
 	SVec3 vOt;
-	float fMagT;
+	treal fMagT;
 	int iCounter;	// this is to average back into quads.
 	tbool bOrient;
 } STSpace;
 
 static int GenerateInitialVerticesIndexList(STriInfo pTriInfos[], int piTriList_out[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn);
-static void GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn);
-static void InitTriInfo(STriInfo pTriInfos[], const int piTriListIn[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn);
-static int Build4RuleGroups(STriInfo pTriInfos[], SGroup pGroups[], int piGroupTrianglesBuffer[], const int piTriListIn[], const int iNrTrianglesIn);
+static tbool GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn, const tbool bQuadraticFallback);
+static tbool InitTriInfo(STriInfo pTriInfos[], const int piTriListIn[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn, const tbool bQuadraticFallback);
+static void SplitHardEdges(STriInfo pTriInfos[], const int piTriListIn[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn);
+static int Build4RuleGroups(STriInfo pTriInfos[], SGroup pGroups[], int piGroupTrianglesBuffer[], const int piTriListIn[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn);
 static tbool GenerateTSpaces(STSpace psTspace[], const STriInfo pTriInfos[], const SGroup pGroups[],
-                             const int iNrActiveGroups, const int piTriListIn[], const float fThresCos,
-                             const SMikkTSpaceContext * pContext);
+                             const int iNrActiveGroups, const int piTriListIn[], const treal fThresCos,
+                             const SMikkTSpaceContext * pContext, int * piNrSubGroups);
 
 static int MakeIndex(const int iFace, const int iVert)
 {
@@ -214,6 +245,43 @@ static STSpace AvgTSpace(const STSpace * pTS0, const STSpace * pTS1)
 static SVec3 GetPosition(const SMikkTSpaceContext * pContext, const int index);
 static SVec3 GetNormal(const SMikkTSpaceContext * pContext, const int index);
 static SVec3 GetTexCoord(const SMikkTSpaceContext * pContext, const int index);
+static STSpace NormalBasisTSpace(const SVec3 vN, const tbool bOrient);
+
+static void * MikkAlloc(const SMikkTSpaceContext * pContext, const size_t iSize)
+{
+	if (pContext->m_pInterface->m_alloc!=NULL)
+		return pContext->m_pInterface->m_alloc(pContext, iSize);
+	return malloc(iSize);
+}
+
+static void MikkFree(const SMikkTSpaceContext * pContext, void * pPtr)
+{
+	if (pContext->m_pInterface->m_free!=NULL)
+		pContext->m_pInterface->m_free(pContext, pPtr);
+	else
+		free(pPtr);
+}
+
+static void BeginPhase(const SMikkTSpaceContext * pContext, const int iPhase)
+{
+	if (pContext->m_pInterface->m_beginPhase!=NULL)
+		pContext->m_pInterface->m_beginPhase(pContext, iPhase);
+}
+
+static void EndPhase(const SMikkTSpaceContext * pContext, const int iPhase, const int iCount)
+{
+	if (pContext->m_pInterface->m_endPhase!=NULL)
+		pContext->m_pInterface->m_endPhase(pContext, iPhase, iCount);
+}
+
+// Approximate number of m_reportProgress() calls per phase.
+#define MIKK_PROGRESS_STEPS			256
+
+static void ReportProgress(const SMikkTSpaceContext * pContext, const int iPhase, const int iDone, const int iTotal)
+{
+	if (pContext->m_pInterface->m_reportProgress!=NULL && (iDone%(iTotal/MIKK_PROGRESS_STEPS+1)==0 || iDone==iTotal))
+		pContext->m_pInterface->m_reportProgress(pContext, iPhase, iDone, iTotal);
+}
 
 
 // degen triangles
@@ -227,6 +295,15 @@ tbool genTangSpaceDefault(const SMikkTSpaceContext * pContext)
 }
 
 tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThreshold)
+{
+	SMikkTSpaceOptions sOptions;
+	memset(&sOptions, 0, sizeof(sOptions));
+	sOptions.fAngularThreshold = fAngularThreshold;
+	sOptions.bQuadraticFallback = TTRUE;
+	return genTangSpaceEx(pContext, &sOptions);
+}
+
+tbool genTangSpaceEx(const SMikkTSpaceContext * pContext, const SMikkTSpaceOptions * pOptions)
 {
 	// count nr_triangles
 	int * piTriListIn = NULL, * piGroupTrianglesBuffer = NULL;
@@ -235,10 +312,11 @@ tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThre
 	STSpace * psTspace = NULL;
 	int iNrTrianglesIn = 0, f=0, t=0, i=0;
 	int iNrTSPaces = 0, iTotTris = 0, iDegenTriangles = 0, iNrMaxGroups = 0;
-	int iNrActiveGroups = 0, index = 0;
+	int iNrActiveGroups = 0, iNrSubGroups = 0, index = 0;
 	const int iNrFaces = pContext->m_pInterface->m_getNumFaces(pContext);
 	tbool bRes = TFALSE;
-	const float fThresCos = (float) cos((fAngularThreshold*(float)M_PI)/180.0f);
+	const float fAngularThreshold = pOptions->fAngularThreshold;
+	const treal fThresCos = (treal) cos((fAngularThreshold*(treal)M_PI)/180.0f);
 
 	// verify all call-backs have been set
 	if ( pContext->m_pInterface->m_getNumFaces==NULL ||
@@ -258,12 +336,12 @@ tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThre
 	if (iNrTrianglesIn<=0) return TFALSE;
 
 	// allocate memory for an index list
-	piTriListIn = (int *) malloc(sizeof(int)*3*iNrTrianglesIn);
-	pTriInfos = (STriInfo *) malloc(sizeof(STriInfo)*iNrTrianglesIn);
+	piTriListIn = (int *) MikkAlloc(pContext, sizeof(int)*3*iNrTrianglesIn);
+	pTriInfos = (STriInfo *) MikkAlloc(pContext, sizeof(STriInfo)*iNrTrianglesIn);
 	if (piTriListIn==NULL || pTriInfos==NULL)
 	{
-		if (piTriListIn!=NULL) free(piTriListIn);
-		if (pTriInfos!=NULL) free(pTriInfos);
+		if (piTriListIn!=NULL) MikkFree(pContext, piTriListIn);
+		if (pTriInfos!=NULL) MikkFree(pContext, pTriInfos);
 		return TFALSE;
 	}
 
@@ -272,10 +350,18 @@ tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThre
 
 	// make a welded index list of identical positions and attributes (pos, norm, texc)
 	//printf("gen welded index list begin\n");
-	GenerateSharedVerticesIndexList(piTriListIn, pContext, iNrTrianglesIn);
+	BeginPhase(pContext, MIKK_PHASE_WELD);
+	if (!GenerateSharedVerticesIndexList(piTriListIn, pContext, iNrTrianglesIn, pOptions->bQuadraticFallback))
+	{
+		MikkFree(pContext, piTriListIn);
+		MikkFree(pContext, pTriInfos);
+		return TFALSE;
+	}
+	EndPhase(pContext, MIKK_PHASE_WELD, iNrTrianglesIn);
 	//printf("gen welded index list end\n");
 
 	// Mark all degenerate triangles
+	BeginPhase(pContext, MIKK_PHASE_DEGENERATES);
 	iTotTris = iNrTrianglesIn;
 	iDegenTriangles = 0;
 	for (t=0; t<iTotTris; t++)
@@ -286,6 +372,7 @@ tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThre
 		const SVec3 p0 = GetPosition(pContext, i0);
 		const SVec3 p1 = GetPosition(pContext, i1);
 		const SVec3 p2 = GetPosition(pContext, i2);
+		ReportProgress(pContext, MIKK_PHASE_DEGENERATES, t, iTotTris);
 		if (veq(p0,p1) || veq(p0,p2) || veq(p1,p2))	// degenerate
 		{
 			pTriInfos[t].iFlag |= MARK_DEGENERATE;
@@ -293,6 +380,8 @@ tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThre
 		}
 	}
 	iNrTrianglesIn = iTotTris - iDegenTriangles;
+	ReportProgress(pContext, MIKK_PHASE_DEGENERATES, iTotTris, iTotTris);
+	EndPhase(pContext, MIKK_PHASE_DEGENERATES, iDegenTriangles);
 
 	// mark all triangle pairs that belong to a quad with only one
 	// good triangle. These need special treatment in DegenEpilogue().
@@ -304,36 +393,51 @@ tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThre
 	
 	// evaluate triangle level attributes and neighbor list
 	//printf("gen neighbors list begin\n");
-	InitTriInfo(pTriInfos, piTriListIn, pContext, iNrTrianglesIn);
+	BeginPhase(pContext, MIKK_PHASE_TRI_INFO);
+	if (!InitTriInfo(pTriInfos, piTriListIn, pContext, iNrTrianglesIn, pOptions->bQuadraticFallback))
+	{
+		MikkFree(pContext, piTriListIn);
+		MikkFree(pContext, pTriInfos);
+		return TFALSE;
+	}
 	//printf("gen neighbors list end\n");
 
+	// disconnect neighbors across user specified hard edges
+	if (pContext->m_pInterface->m_getSmoothingGroup!=NULL || pContext->m_pInterface->m_isEdgeSharp!=NULL)
+		SplitHardEdges(pTriInfos, piTriListIn, pContext, iNrTrianglesIn);
+	ReportProgress(pContext, MIKK_PHASE_TRI_INFO, iNrTrianglesIn, iNrTrianglesIn);
+	EndPhase(pContext, MIKK_PHASE_TRI_INFO, iNrTrianglesIn);
+
 	
 	// based on the 4 rules, identify groups based on connectivity
 	iNrMaxGroups = iNrTrianglesIn*3;
-	pGroups = (SGroup *) malloc(sizeof(SGroup)*iNrMaxGroups);
-	piGroupTrianglesBuffer = (int *) malloc(sizeof(int)*iNrTrianglesIn*3);
+	pGroups = (SGroup *) MikkAlloc(pContext, sizeof(SGroup)*iNrMaxGroups);
+	piGroupTrianglesBuffer = (int *) MikkAlloc(pContext, sizeof(int)*iNrTrianglesIn*3);
 	if (pGroups==NULL || piGroupTrianglesBuffer==NULL)
 	{
-		if (pGroups!=NULL) free(pGroups);
-		if (piGroupTrianglesBuffer!=NULL) free(piGroupTrianglesBuffer);
-		free(piTriListIn);
-		free(pTriInfos);
+		if (pGroups!=NULL) MikkFree(pContext, pGroups);
+		if (piGroupTrianglesBuffer!=NULL) MikkFree(pContext, piGroupTrianglesBuffer);
+		MikkFree(pContext, piTriListIn);
+		MikkFree(pContext, pTriInfos);
 		return TFALSE;
 	}
 	//printf("gen 4rule groups begin\n");
+	BeginPhase(pContext, MIKK_PHASE_GROUPS);
 	iNrActiveGroups =
-		Build4RuleGroups(pTriInfos, pGroups, piGroupTrianglesBuffer, piTriListIn, iNrTrianglesIn);
+		Build4RuleGroups(pTriInfos, pGroups, piGroupTrianglesBuffer, piTriListIn, pContext, iNrTrianglesIn);
+	ReportProgress(pContext, MIKK_PHASE_GROUPS, iNrTrianglesIn, iNrTrianglesIn);
+	EndPhase(pContext, MIKK_PHASE_GROUPS, iNrActiveGroups);
 	//printf("gen 4rule groups end\n");
 
 	//
 
-	psTspace = (STSpace *) malloc(sizeof(STSpace)*iNrTSPaces);
+	psTspace = (STSpace *) MikkAlloc(pContext, sizeof(STSpace)*iNrTSPaces);
 	if (psTspace==NULL)
 	{
-		free(piTriListIn);
-		free(pTriInfos);
-		free(pGroups);
-		free(piGroupTrianglesBuffer);
+		MikkFree(pContext, piTriListIn);
+		MikkFree(pContext, pTriInfos);
+		MikkFree(pContext, pGroups);
+		MikkFree(pContext, piGroupTrianglesBuffer);
 		return TFALSE;
 	}
 	memset(psTspace, 0, sizeof(STSpace)*iNrTSPaces);
@@ -347,17 +451,20 @@ tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThre
 	// based on fAngularThreshold. Finally a tangent space is made for
 	// every resulting subgroup
 	//printf("gen tspaces begin\n");
-	bRes = GenerateTSpaces(psTspace, pTriInfos, pGroups, iNrActiveGroups, piTriListIn, fThresCos, pContext);
+	BeginPhase(pContext, MIKK_PHASE_TSPACES);
+	bRes = GenerateTSpaces(psTspace, pTriInfos, pGroups, iNrActiveGroups, piTriListIn, fThresCos, pContext, &iNrSubGroups);
+	if (bRes) ReportProgress(pContext, MIKK_PHASE_TSPACES, iNrActiveGroups, iNrActiveGroups);
+	EndPhase(pContext, MIKK_PHASE_TSPACES, iNrSubGroups);
 	//printf("gen tspaces end\n");
 	
 	// clean up
-	free(pGroups);
-	free(piGroupTrianglesBuffer);
+	MikkFree(pContext, pGroups);
+	MikkFree(pContext, piGroupTrianglesBuffer);
 
 	if (!bRes)	// if an allocation in GenerateTSpaces() failed
 	{
 		// clean up and return false
-		free(pTriInfos); free(piTriListIn); free(psTspace);
+		MikkFree(pContext, pTriInfos); MikkFree(pContext, piTriListIn); MikkFree(pContext, psTspace);
 		return TFALSE;
 	}
 
@@ -366,9 +473,12 @@ tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThre
 	// the good triangle to the coinciding vertex.
 	// all other degenerate triangles will just copy a space from any good triangle
 	// with the same welded index in piTriListIn[].
+	BeginPhase(pContext, MIKK_PHASE_DEGEN_EPILOGUE);
 	DegenEpilogue(psTspace, pTriInfos, piTriListIn, pContext, iNrTrianglesIn, iTotTris);
+	ReportProgress(pContext, MIKK_PHASE_DEGEN_EPILOGUE, iDegenTriangles, iDegenTriangles);
+	EndPhase(pContext, MIKK_PHASE_DEGEN_EPILOGUE, iDegenTriangles);
 
-	free(pTriInfos); free(piTriListIn);
+	MikkFree(pContext, pTriInfos); MikkFree(pContext, piTriListIn);
 
 	index = 0;
 	for (f=0; f<iNrFaces; f++)
@@ -381,39 +491,55 @@ tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThre
 		// vary between left/right hand coordinate systems at the vertices.
 		// All healthy triangles on the other hand are built to always be either or.
 
-		/*// force the coordinate system orientation to be uniform for every face.
+		// force the coordinate system orientation to be uniform for every face.
 		// (this is already the case for good triangles but not for
 		// degenerate ones and those with bGroupWithAnything==true)
-		bool bOrient = psTspace[index].bOrient;
-		if (psTspace[index].iCounter == 0)	// tspace was not derived from a group
+		// Only done when requested through SMikkTSpaceOptions::bUniformOrientation.
+		tbool bOrient = psTspace[index].bOrient;
+		if (pOptions->bUniformOrientation && psTspace[index].iCounter == 0)	// tspace was not derived from a group
 		{
 			// look for a space created in GenerateTSpaces() by iCounter>0
-			bool bNotFound = true;
-			int i=1;
+			tbool bNotFound = TTRUE;
+			i=1;
 			while (i<verts && bNotFound)
 			{
-				if (psTspace[index+i].iCounter > 0) bNotFound=false;
+				if (psTspace[index+i].iCounter > 0) bNotFound=TFALSE;
 				else ++i;
 			}
 			if (!bNotFound) bOrient = psTspace[index+i].bOrient;
-		}*/
+			else if (pOptions->iFallback!=MIKK_FALLBACK_NONE) bOrient = TTRUE;	// every corner gets a fallback
+		}
 
 		// set data
 		for (i=0; i<verts; i++)
 		{
 			const STSpace * pTSpace = &psTspace[index];
-			float tang[] = {pTSpace->vOs.x, pTSpace->vOs.y, pTSpace->vOs.z};
-			float bitang[] = {pTSpace->vOt.x, pTSpace->vOt.y, pTSpace->vOt.z};
+			tbool bOrientOut = pOptions->bUniformOrientation ? bOrient : pTSpace->bOrient;
+			STSpace sFallback;
+			treal tang[3], bitang[3];
+
+			// the tspace still holds its initialization values
+			if (pOptions->iFallback==MIKK_FALLBACK_NORMAL_BASIS && pTSpace->iCounter==0)
+			{
+				if (!pOptions->bUniformOrientation) bOrientOut = TTRUE;
+				sFallback = NormalBasisTSpace(GetNormal(pContext, MakeIndex(f, i)), bOrientOut);
+				pTSpace = &sFallback;
+				if (pContext->m_pInterface->m_reportFallback!=NULL)
+					pContext->m_pInterface->m_reportFallback(pContext, f, i);
+			}
+
+			tang[0] = pTSpace->vOs.x; tang[1] = pTSpace->vOs.y; tang[2] = pTSpace->vOs.z;
+			bitang[0] = pTSpace->vOt.x; bitang[1] = pTSpace->vOt.y; bitang[2] = pTSpace->vOt.z;
 			if (pContext->m_pInterface->m_setTSpace!=NULL)
-				pContext->m_pInterface->m_setTSpace(pContext, tang, bitang, pTSpace->fMagS, pTSpace->fMagT, pTSpace->bOrient, f, i);
+				pContext->m_pInterface->m_setTSpace(pContext, tang, bitang, pTSpace->fMagS, pTSpace->fMagT, bOrientOut, f, i);
 			if (pContext->m_pInterface->m_setTSpaceBasic!=NULL)
-				pContext->m_pInterface->m_setTSpaceBasic(pContext, tang, pTSpace->bOrient==TTRUE ? 1.0f : (-1.0f), f, i);
+				pContext->m_pInterface->m_setTSpaceBasic(pContext, tang, bOrientOut==TTRUE ? 1.0f : (-1.0f), f, i);
 
 			++index;
 		}
 	}
 
-	free(psTspace);
+	MikkFree(pContext, psTspace);
 
 	
 	return TTRUE;
@@ -422,7 +548,7 @@ tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThre
 ///////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
 typedef struct {
-	float vert[3];
+	treal vert[3];
 	int index;
 } STmpVert;
 
@@ -437,9 +563,9 @@ static const int g_iCells = 2048;
 // it is IMPORTANT that this function is called to evaluate the hash since
 // inlining could potentially reorder instructions and generate different
 // results for the same effective input value fVal.
-static NOINLINE int FindGridCell(const float fMin, const float fMax, const float fVal)
+static NOINLINE int FindGridCell(const treal fMin, const treal fMax, const treal fVal)
 {
-	const float fIndex = g_iCells * ((fVal-fMin)/(fMax-fMin));
+	const treal fIndex = g_iCells * ((fVal-fMin)/(fMax-fMin));
 	const int iIndex = (int)fIndex;
 	return iIndex < g_iCells ? (iIndex >= 0 ? iIndex : 0) : (g_iCells - 1);
 }
@@ -448,7 +574,7 @@ static void MergeVertsFast(int piTriList_in_and_out[], STmpVert pTmpVert[], cons
 static void MergeVertsSlow(int piTriList_in_and_out[], const SMikkTSpaceContext * pContext, const int pTable[], const int iEntries);
 static void GenerateSharedVerticesIndexListSlow(int piTriList_in_and_out[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn);
 
-static void GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn)
+static tbool GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn, const tbool bQuadraticFallback)
 {
 
 	// Generate bounding box
@@ -457,7 +583,7 @@ static void GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SM
 	int i=0, iChannel=0, k=0, e=0;
 	int iMaxCount=0;
 	SVec3 vMin = GetPosition(pContext, 0), vMax = vMin, vDim;
-	float fMin, fMax;
+	treal fMin, fMax;
 	for (i=1; i<(iNrTrianglesIn*3); i++)
 	{
 		const int index = piTriList_in_and_out[i];
@@ -488,19 +614,20 @@ static void GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SM
 	}
 
 	// make allocations
-	piHashTable = (int *) malloc(sizeof(int)*iNrTrianglesIn*3);
-	piHashCount = (int *) malloc(sizeof(int)*g_iCells);
-	piHashOffsets = (int *) malloc(sizeof(int)*g_iCells);
-	piHashCount2 = (int *) malloc(sizeof(int)*g_iCells);
+	piHashTable = (int *) MikkAlloc(pContext, sizeof(int)*iNrTrianglesIn*3);
+	piHashCount = (int *) MikkAlloc(pContext, sizeof(int)*g_iCells);
+	piHashOffsets = (int *) MikkAlloc(pContext, sizeof(int)*g_iCells);
+	piHashCount2 = (int *) MikkAlloc(pContext, sizeof(int)*g_iCells);
 
 	if (piHashTable==NULL || piHashCount==NULL || piHashOffsets==NULL || piHashCount2==NULL)
 	{
-		if (piHashTable!=NULL) free(piHashTable);
-		if (piHashCount!=NULL) free(piHashCount);
-		if (piHashOffsets!=NULL) free(piHashOffsets);
-		if (piHashCount2!=NULL) free(piHashCount2);
+		if (piHashTable!=NULL) MikkFree(pContext, piHashTable);
+		if (piHashCount!=NULL) MikkFree(pContext, piHashCount);
+		if (piHashOffsets!=NULL) MikkFree(pContext, piHashOffsets);
+		if (piHashCount2!=NULL) MikkFree(pContext, piHashCount2);
+		if (!bQuadraticFallback) return TFALSE;
 		GenerateSharedVerticesIndexListSlow(piTriList_in_and_out, pContext, iNrTrianglesIn);
-		return;
+		return TTRUE;
 	}
 	memset(piHashCount, 0, sizeof(int)*g_iCells);
 	memset(piHashCount2, 0, sizeof(int)*g_iCells);
@@ -510,7 +637,7 @@ static void GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SM
 	{
 		const int index = piTriList_in_and_out[i];
 		const SVec3 vP = GetPosition(pContext, index);
-		const float fVal = iChannel==0 ? vP.x : (iChannel==1 ? vP.y : vP.z);
+		const treal fVal = iChannel==0 ? vP.x : (iChannel==1 ? vP.y : vP.z);
 		const int iCell = FindGridCell(fMin, fMax, fVal);
 		++piHashCount[iCell];
 	}
@@ -525,7 +652,7 @@ static void GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SM
 	{
 		const int index = piTriList_in_and_out[i];
 		const SVec3 vP = GetPosition(pContext, index);
-		const float fVal = iChannel==0 ? vP.x : (iChannel==1 ? vP.y : vP.z);
+		const treal fVal = iChannel==0 ? vP.x : (iChannel==1 ? vP.y : vP.z);
 		const int iCell = FindGridCell(fMin, fMax, fVal);
 		int * pTable = NULL;
 
@@ -536,15 +663,21 @@ static void GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SM
 	}
 	for (k=0; k<g_iCells; k++)
 		assert(piHashCount2[k] == piHashCount[k]);	// verify the count
-	free(piHashCount2);
+	MikkFree(pContext, piHashCount2);
 
 	// find maximum amount of entries in any hash entry
 	iMaxCount = piHashCount[0];
 	for (k=1; k<g_iCells; k++)
 		if (iMaxCount<piHashCount[k])
 			iMaxCount=piHashCount[k];
-	pTmpVert = (STmpVert *) malloc(sizeof(STmpVert)*iMaxCount);
-	
+	pTmpVert = (STmpVert *) MikkAlloc(pContext, sizeof(STmpVert)*iMaxCount);
+	if (pTmpVert==NULL && !bQuadraticFallback)
+	{
+		MikkFree(pContext, piHashTable);
+		MikkFree(pContext, piHashCount);
+		MikkFree(pContext, piHashOffsets);
+		return TFALSE;
+	}
 
 	// complete the merge
 	for (k=0; k<g_iCells; k++)
@@ -552,6 +685,7 @@ static void GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SM
 		// extract table of cell k and amount of entries in it
 		int * pTable = &piHashTable[piHashOffsets[k]];
 		const int iEntries = piHashCount[k];
+		ReportProgress(pContext, MIKK_PHASE_WELD, k, g_iCells);
 		if (iEntries < 2) continue;
 
 		if (pTmpVert!=NULL)
@@ -569,18 +703,21 @@ static void GenerateSharedVerticesIndexList(int piTriList_in_and_out[], const SM
 			MergeVertsSlow(piTriList_in_and_out, pContext, pTable, iEntries);
 	}
 
-	if (pTmpVert!=NULL) { free(pTmpVert); }
-	free(piHashTable);
-	free(piHashCount);
-	free(piHashOffsets);
+	ReportProgress(pContext, MIKK_PHASE_WELD, g_iCells, g_iCells);
+
+	if (pTmpVert!=NULL) { MikkFree(pContext, pTmpVert); }
+	MikkFree(pContext, piHashTable);
+	MikkFree(pContext, piHashCount);
+	MikkFree(pContext, piHashOffsets);
+	return TTRUE;
 }
 
 static void MergeVertsFast(int piTriList_in_and_out[], STmpVert pTmpVert[], const SMikkTSpaceContext * pContext, const int iL_in, const int iR_in)
 {
 	// make bbox
 	int c=0, l=0, channel=0;
-	float fvMin[3], fvMax[3];
-	float dx=0, dy=0, dz=0, fSep=0;
+	treal fvMin[3], fvMax[3];
+	treal dx=0, dy=0, dz=0, fSep=0;
 	for (c=0; c<3; c++)
 	{	fvMin[c]=pTmpVert[iL_in].vert[c]; fvMax[c]=fvMin[c];	}
 	for (l=(iL_in+1); l<=iR_in; l++) {
@@ -731,6 +868,7 @@ static void GenerateSharedVerticesIndexListSlow(int piTriList_in_and_out[], cons
 	int iNumUniqueVerts = 0, t=0, i=0;
 	for (t=0; t<iNrTrianglesIn; t++)
 	{
+		ReportProgress(pContext, MIKK_PHASE_WELD, t, iNrTrianglesIn);
 		for (i=0; i<3; i++)
 		{
 			const int offs = t*3 + i;
@@ -753,7 +891,10 @@ static void GenerateSharedVerticesIndexListSlow(int piTriList_in_and_out[], cons
 					const SVec3 vT2 = GetTexCoord(pContext, index2);
 					
 					if (veq(vP,vP2) && veq(vN,vN2) && veq(vT,vT2))
+					{
 						bFound = TTRUE;
+						index2rec = index2;
+					}
 					else
 						++j;
 				}
@@ -767,6 +908,7 @@ static void GenerateSharedVerticesIndexListSlow(int piTriList_in_and_out[], cons
 			piTriList_in_and_out[offs] = index2rec;
 		}
 	}
+	ReportProgress(pContext, MIKK_PHASE_WELD, iNrTrianglesIn, iNrTrianglesIn);
 }
 
 static int GenerateInitialVerticesIndexList(STriInfo pTriInfos[], int piTriList_out[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn)
@@ -809,8 +951,8 @@ static int GenerateInitialVerticesIndexList(STriInfo pTriInfos[], int piTriList_
 				const SVec3 T1 = GetTexCoord(pContext, i1);
 				const SVec3 T2 = GetTexCoord(pContext, i2);
 				const SVec3 T3 = GetTexCoord(pContext, i3);
-				const float distSQ_02 = LengthSquared(vsub(T2,T0));
-				const float distSQ_13 = LengthSquared(vsub(T3,T1));
+				const treal distSQ_02 = LengthSquared(vsub(T2,T0));
+				const treal distSQ_13 = LengthSquared(vsub(T3,T1));
 				tbool bQuadDiagIs_02;
 				if (distSQ_02<distSQ_13)
 					bQuadDiagIs_02 = TTRUE;
@@ -822,8 +964,8 @@ static int GenerateInitialVerticesIndexList(STriInfo pTriInfos[], int piTriList_
 					const SVec3 P1 = GetPosition(pContext, i1);
 					const SVec3 P2 = GetPosition(pContext, i2);
 					const SVec3 P3 = GetPosition(pContext, i3);
-					const float distSQ_02 = LengthSquared(vsub(P2,P0));
-					const float distSQ_13 = LengthSquared(vsub(P3,P1));
+					const treal distSQ_02 = LengthSquared(vsub(P2,P0));
+					const treal distSQ_13 = LengthSquared(vsub(P3,P1));
 
 					bQuadDiagIs_02 = distSQ_13<distSQ_02 ? TFALSE : TTRUE;
 				}
@@ -883,7 +1025,7 @@ static int GenerateInitialVerticesIndexList(STriInfo pTriInfos[], int piTriList_
 static SVec3 GetPosition(const SMikkTSpaceContext * pContext, const int index)
 {
 	int iF, iI;
-	SVec3 res; float pos[3];
+	SVec3 res; treal pos[3];
 	IndexToData(&iF, &iI, index);
 	pContext->m_pInterface->m_getPosition(pContext, pos, iF, iI);
 	res.x=pos[0]; res.y=pos[1]; res.z=pos[2];
@@ -893,7 +1035,7 @@ static SVec3 GetPosition(const SMikkTSpaceContext * pContext, const int index)
 static SVec3 GetNormal(const SMikkTSpaceContext * pContext, const int index)
 {
 	int iF, iI;
-	SVec3 res; float norm[3];
+	SVec3 res; treal norm[3];
 	IndexToData(&iF, &iI, index);
 	pContext->m_pInterface->m_getNormal(pContext, norm, iF, iI);
 	res.x=norm[0]; res.y=norm[1]; res.z=norm[2];
@@ -903,13 +1045,35 @@ static SVec3 GetNormal(const SMikkTSpaceContext * pContext, const int index)
 static SVec3 GetTexCoord(const SMikkTSpaceContext * pContext, const int index)
 {
 	int iF, iI;
-	SVec3 res; float texc[2];
+	SVec3 res; treal texc[2];
 	IndexToData(&iF, &iI, index);
 	pContext->m_pInterface->m_getTexCoord(pContext, texc, iF, iI);
 	res.x=texc[0]; res.y=texc[1]; res.z=1.0f;
 	return res;
 }
 
+// orthonormal basis around the normal, see "Building an Orthonormal Basis, Revisited" by Duff et al.
+// bOrient selects between a right handed (tangent, bitangent, normal) frame and a left handed one.
+static STSpace NormalBasisTSpace(const SVec3 vN, const tbool bOrient)
+{
+	STSpace res;
+	SVec3 n;
+	treal fSign, a, b;
+	if (VNotZero(vN)) n = Normalize(vN);
+	else { n.x=0.0f; n.y=0.0f; n.z=1.0f; }
+
+	fSign = n.z<0 ? (-1.0f) : 1.0f;
+	a = -1.0f / (fSign + n.z);
+	b = n.x * n.y * a;
+	res.vOs.x = 1.0f + fSign * n.x * n.x * a; res.vOs.y = fSign * b; res.vOs.z = -fSign * n.x;
+	res.vOt.x = b; res.vOt.y = fSign + n.y * n.y * a; res.vOt.z = -n.y;
+	if (!bOrient) res.vOt = vscale(-1.0f, res.vOt);
+	res.fMagS = 1.0f; res.fMagT = 1.0f;
+	res.iCounter = 0;
+	res.bOrient = bOrient;
+	return res;
+}
+
 /////////////////////////////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////////////////////////////
 
@@ -925,23 +1089,23 @@ static void BuildNeighborsFast(STriInfo pTriInfos[], SEdge * pEdges, const int p
 static void BuildNeighborsSlow(STriInfo pTriInfos[], const int piTriListIn[], const int iNrTrianglesIn);
 
 // returns the texture area times 2
-static float CalcTexArea(const SMikkTSpaceContext * pContext, const int indices[])
+static treal CalcTexArea(const SMikkTSpaceContext * pContext, const int indices[])
 {
 	const SVec3 t1 = GetTexCoord(pContext, indices[0]);
 	const SVec3 t2 = GetTexCoord(pContext, indices[1]);
 	const SVec3 t3 = GetTexCoord(pContext, indices[2]);
 
-	const float t21x = t2.x-t1.x;
-	const float t21y = t2.y-t1.y;
-	const float t31x = t3.x-t1.x;
-	const float t31y = t3.y-t1.y;
+	const treal t21x = t2.x-t1.x;
+	const treal t21y = t2.y-t1.y;
+	const treal t31x = t3.x-t1.x;
+	const treal t31y = t3.y-t1.y;
 
-	const float fSignedAreaSTx2 = t21x*t31y - t21y*t31x;
+	const treal fSignedAreaSTx2 = t21x*t31y - t21y*t31x;
 
 	return fSignedAreaSTx2<0 ? (-fSignedAreaSTx2) : fSignedAreaSTx2;
 }
 
-static void InitTriInfo(STriInfo pTriInfos[], const int piTriListIn[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn)
+static tbool InitTriInfo(STriInfo pTriInfos[], const int piTriListIn[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn, const tbool bQuadraticFallback)
 {
 	int f=0, i=0, t=0;
 	// pTriInfos[f].iFlag is cleared in GenerateInitialVerticesIndexList() which is called before this function.
@@ -973,26 +1137,27 @@ static void InitTriInfo(STriInfo pTriInfos[], const int piTriListIn[], const SMi
 		const SVec3 t2 = GetTexCoord(pContext, piTriListIn[f*3+1]);
 		const SVec3 t3 = GetTexCoord(pContext, piTriListIn[f*3+2]);
 
-		const float t21x = t2.x-t1.x;
-		const float t21y = t2.y-t1.y;
-		const float t31x = t3.x-t1.x;
-		const float t31y = t3.y-t1.y;
+		const treal t21x = t2.x-t1.x;
+		const treal t21y = t2.y-t1.y;
+		const treal t31x = t3.x-t1.x;
+		const treal t31y = t3.y-t1.y;
 		const SVec3 d1 = vsub(v2,v1);
 		const SVec3 d2 = vsub(v3,v1);
 
-		const float fSignedAreaSTx2 = t21x*t31y - t21y*t31x;
+		const treal fSignedAreaSTx2 = t21x*t31y - t21y*t31x;
 		//assert(fSignedAreaSTx2!=0);
 		SVec3 vOs = vsub(vscale(t31y,d1), vscale(t21y,d2));	// eq 18
 		SVec3 vOt = vadd(vscale(-t31x,d1), vscale(t21x,d2)); // eq 19
 
+		ReportProgress(pContext, MIKK_PHASE_TRI_INFO, f, iNrTrianglesIn);
 		pTriInfos[f].iFlag |= (fSignedAreaSTx2>0 ? ORIENT_PRESERVING : 0);
 
 		if ( NotZero(fSignedAreaSTx2) )
 		{
-			const float fAbsArea = fabsf(fSignedAreaSTx2);
-			const float fLenOs = Length(vOs);
-			const float fLenOt = Length(vOt);
-			const float fS = (pTriInfos[f].iFlag&ORIENT_PRESERVING)==0 ? (-1.0f) : 1.0f;
+			const treal fAbsArea = MIKK_FABS(fSignedAreaSTx2);
+			const treal fLenOs = Length(vOs);
+			const treal fLenOt = Length(vOt);
+			const treal fS = (pTriInfos[f].iFlag&ORIENT_PRESERVING)==0 ? (-1.0f) : 1.0f;
 			if ( NotZero(fLenOs) ) pTriInfos[f].vOs = vscale(fS/fLenOs, vOs);
 			if ( NotZero(fLenOt) ) pTriInfos[f].vOt = vscale(fS/fLenOt, vOt);
 
@@ -1048,25 +1213,93 @@ static void InitTriInfo(STriInfo pTriInfos[], const int piTriListIn[], const SMi
 	
 	// match up edge pairs
 	{
-		SEdge * pEdges = (SEdge *) malloc(sizeof(SEdge)*iNrTrianglesIn*3);
+		SEdge * pEdges = (SEdge *) MikkAlloc(pContext, sizeof(SEdge)*iNrTrianglesIn*3);
 		if (pEdges==NULL)
+		{
+			if (!bQuadraticFallback) return TFALSE;
 			BuildNeighborsSlow(pTriInfos, piTriListIn, iNrTrianglesIn);
+		}
 		else
 		{
 			BuildNeighborsFast(pTriInfos, pEdges, piTriListIn, iNrTrianglesIn);
 	
-			free(pEdges);
+			MikkFree(pContext, pEdges);
 		}
 	}
+	return TTRUE;
 }
 
 /////////////////////////////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////////////////////////////
 
+// tests the edge from vertex i to vertex i+1 of a triangle against m_isEdgeSharp()
+static tbool IsTriEdgeSharp(const STriInfo * pTriInfo, const int i, const SMikkTSpaceContext * pContext)
+{
+	const int iFace = pTriInfo->iOrgFaceNumber;
+	const int iVertA = pTriInfo->vert_num[i];
+	const int iVertB = pTriInfo->vert_num[i<2?(i+1):0];
+	int iVerts = 0;
+	if (pContext->m_pInterface->m_isEdgeSharp==NULL) return TFALSE;
+
+	iVerts = pContext->m_pInterface->m_getNumVerticesOfFace(pContext, iFace);
+	if (((iVertA+1)%iVerts)==iVertB)
+		return pContext->m_pInterface->m_isEdgeSharp(pContext, iFace, iVertA)!=0 ? TTRUE : TFALSE;
+	else if (((iVertB+1)%iVerts)==iVertA)
+		return pContext->m_pInterface->m_isEdgeSharp(pContext, iFace, iVertB)!=0 ? TTRUE : TFALSE;
+
+	// the diagonal of a quad is never sharp
+	return TFALSE;
+}
+
+static void SplitHardEdges(STriInfo pTriInfos[], const int piTriListIn[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn)
+{
+	int f=0, i=0;
+	for (f=0; f<iNrTrianglesIn; f++)
+	{
+		for (i=0; i<3; i++)
+		{
+			const int t = pTriInfos[f].FaceNeighbors[i];
+			if (t>=0)
+			{
+				const int i0 = piTriListIn[f*3+i];
+				const int i1 = piTriListIn[f*3+(i<2?(i+1):0)];
+				const int iFaceA = pTriInfos[f].iOrgFaceNumber;
+				const int iFaceB = pTriInfos[t].iOrgFaceNumber;
+				tbool bHard = TFALSE;
+
+				// locate the shared edge on the neighbor, it is stored in reverse order
+				int j=0;
+				while (j<3 && !(pTriInfos[t].FaceNeighbors[j]==f && piTriListIn[t*3+j]==i1 && piTriListIn[t*3+(j<2?(j+1):0)]==i0))
+					++j;
+				assert(j<3);
+
+				// triangles of the same quad always stay connected
+				if (iFaceA!=iFaceB)
+				{
+					if (pContext->m_pInterface->m_getSmoothingGroup!=NULL)
+					{
+						const unsigned int uGroupA = pContext->m_pInterface->m_getSmoothingGroup(pContext, iFaceA);
+						const unsigned int uGroupB = pContext->m_pInterface->m_getSmoothingGroup(pContext, iFaceB);
+						if ((uGroupA&uGroupB)==0) bHard = TTRUE;
+					}
+					if (!bHard && IsTriEdgeSharp(&pTriInfos[f], i, pContext)) bHard = TTRUE;
+					if (!bHard && j<3 && IsTriEdgeSharp(&pTriInfos[t], j, pContext)) bHard = TTRUE;
+				}
+
+				if (bHard)
+				{
+					pTriInfos[f].FaceNeighbors[i] = -1;
+					if (j<3) pTriInfos[t].FaceNeighbors[j] = -1;
+				}
+			}
+		}
+	}
+}
+
 static tbool AssignRecur(const int piTriListIn[], STriInfo psTriInfos[], const int iMyTriIndex, SGroup * pGroup);
 static void AddTriToGroup(SGroup * pGroup, const int iTriIndex);
 
-static int Build4RuleGroups(STriInfo pTriInfos[], SGroup pGroups[], int piGroupTrianglesBuffer[], const int piTriListIn[], const int iNrTrianglesIn)
+static int Build4RuleGroups(STriInfo pTriInfos[], SGroup pGroups[], int piGroupTrianglesBuffer[], const int piTriListIn[], const SMikkTSpaceContext * pContext, const int iNrTrianglesIn)
 {
 	const int iNrMaxGroups = iNrTrianglesIn*3;
 	int iNrActiveGroups = 0;
@@ -1074,6 +1307,7 @@ static int Build4RuleGroups(STriInfo pTriInfos[], SGroup pGroups[], int piGroupT
 	(void)iNrMaxGroups;  /* quiet warnings in non debug mode */
 	for (f=0; f<iNrTrianglesIn; f++)
 	{
+		ReportProgress(pContext, MIKK_PHASE_GROUPS, f, iNrTrianglesIn);
 		for (i=0; i<3; i++)
 		{
 			// if not assigned to a group
@@ -1196,13 +1430,14 @@ static void QuickSort(int* pSortBuffer, int iLeft, int iRight, unsigned int uSee
 static STSpace EvalTspace(int face_indices[], const int iFaces, const int piTriListIn[], const STriInfo pTriInfos[], const SMikkTSpaceContext * pContext, const int iVertexRepresentitive);
 
 static tbool GenerateTSpaces(STSpace psTspace[], const STriInfo pTriInfos[], const SGroup pGroups[],
-                             const int iNrActiveGroups, const int piTriListIn[], const float fThresCos,
-                             const SMikkTSpaceContext * pContext)
+                             const int iNrActiveGroups, const int piTriListIn[], const treal fThresCos,
+                             const SMikkTSpaceContext * pContext, int * piNrSubGroups)
 {
 	STSpace * pSubGroupTspace = NULL;
 	SSubGroup * pUniSubGroups = NULL;
 	int * pTmpMembers = NULL;
 	int iMaxNrFaces=0, iUniqueTspaces=0, g=0, i=0;
+	*piNrSubGroups = 0;
 	for (g=0; g<iNrActiveGroups; g++)
 		if (iMaxNrFaces < pGroups[g].iNrFaces)
 			iMaxNrFaces = pGroups[g].iNrFaces;
@@ -1210,14 +1445,14 @@ static tbool GenerateTSpaces(STSpace psTspace[], const STriInfo pTriInfos[], con
 	if (iMaxNrFaces == 0) return TTRUE;
 
 	// make initial allocations
-	pSubGroupTspace = (STSpace *) malloc(sizeof(STSpace)*iMaxNrFaces);
-	pUniSubGroups = (SSubGroup *) malloc(sizeof(SSubGroup)*iMaxNrFaces);
-	pTmpMembers = (int *) malloc(sizeof(int)*iMaxNrFaces);
+	pSubGroupTspace = (STSpace *) MikkAlloc(pContext, sizeof(STSpace)*iMaxNrFaces);
+	pUniSubGroups = (SSubGroup *) MikkAlloc(pContext, sizeof(SSubGroup)*iMaxNrFaces);
+	pTmpMembers = (int *) MikkAlloc(pContext, sizeof(int)*iMaxNrFaces);
 	if (pSubGroupTspace==NULL || pUniSubGroups==NULL || pTmpMembers==NULL)
 	{
-		if (pSubGroupTspace!=NULL) free(pSubGroupTspace);
-		if (pUniSubGroups!=NULL) free(pUniSubGroups);
-		if (pTmpMembers!=NULL) free(pTmpMembers);
+		if (pSubGroupTspace!=NULL) MikkFree(pContext, pSubGroupTspace);
+		if (pUniSubGroups!=NULL) MikkFree(pContext, pUniSubGroups);
+		if (pTmpMembers!=NULL) MikkFree(pContext, pTmpMembers);
 		return TFALSE;
 	}
 
@@ -1227,6 +1462,7 @@ static tbool GenerateTSpaces(STSpace psTspace[], const STriInfo pTriInfos[], con
 	{
 		const SGroup * pGroup = &pGroups[g];
 		int iUniqueSubGroups = 0, s=0;
+		ReportProgress(pContext, MIKK_PHASE_TSPACES, g, iNrActiveGroups);
 
 		for (i=0; i<pGroup->iNrFaces; i++)	// triangles
 		{
@@ -1272,8 +1508,8 @@ static tbool GenerateTSpaces(STSpace psTspace[], const STriInfo pTriInfos[], con
 					// make sure triangles which belong to the same quad are joined.
 					const tbool bSameOrgFace = iOF_1==iOF_2 ? TTRUE : TFALSE;
 
-					const float fCosS = vdot(vOs,vOs2);
-					const float fCosT = vdot(vOt,vOt2);
+					const treal fCosS = vdot(vOs,vOs2);
+					const treal fCosT = vdot(vOt,vOt2);
 
 					assert(f!=t || bSameOrgFace);	// sanity check
 					if (bAny || bSameOrgFace || (fCosS>fThresCos && fCosT>fThresCos))
@@ -1307,16 +1543,16 @@ static tbool GenerateTSpaces(STSpace psTspace[], const STriInfo pTriInfos[], con
 			if (!bFound)
 			{
 				// insert new subgroup
-				int * pIndices = (int *) malloc(sizeof(int)*iMembers);
+				int * pIndices = (int *) MikkAlloc(pContext, sizeof(int)*iMembers);
 				if (pIndices==NULL)
 				{
 					// clean up and return false
 					int s=0;
 					for (s=0; s<iUniqueSubGroups; s++)
-						free(pUniSubGroups[s].pTriMembers);
-					free(pUniSubGroups);
-					free(pTmpMembers);
-					free(pSubGroupTspace);
+						MikkFree(pContext, pUniSubGroups[s].pTriMembers);
+					MikkFree(pContext, pUniSubGroups);
+					MikkFree(pContext, pTmpMembers);
+					MikkFree(pContext, pSubGroupTspace);
 					return TFALSE;
 				}
 				pUniSubGroups[iUniqueSubGroups].iNrFaces = iMembers;
@@ -1348,19 +1584,32 @@ static tbool GenerateTSpaces(STSpace psTspace[], const STriInfo pTriInfos[], con
 					pTS_out->bOrient = pGroup->bOrientPreservering;
 				}
 			}
+
+			// report the assignment with the members EvalTspace() summed,
+			// pTmpMembers is refilled for the next triangle
+			if (pContext->m_pInterface->m_reportCorner!=NULL)
+			{
+				int iContributing = 0;
+				for (j=0; j<iMembers; j++)
+					if ((pTriInfos[pTmpMembers[j]].iFlag&GROUP_WITH_ANY)==0)
+						pTmpMembers[iContributing++] = pTriInfos[pTmpMembers[j]].iOrgFaceNumber;
+				pContext->m_pInterface->m_reportCorner(pContext, iOF_1, pTriInfos[f].vert_num[index], g,
+					iUniqueTspaces+l, pGroup->bOrientPreservering, pTmpMembers, iContributing);
+			}
 		}
 
 		// clean up and offset iUniqueTspaces
 		for (s=0; s<iUniqueSubGroups; s++)
-			free(pUniSubGroups[s].pTriMembers);
+			MikkFree(pContext, pUniSubGroups[s].pTriMembers);
 		iUniqueTspaces += iUniqueSubGroups;
 	}
 
 	// clean up
-	free(pUniSubGroups);
-	free(pTmpMembers);
-	free(pSubGroupTspace);
+	MikkFree(pContext, pUniSubGroups);
+	MikkFree(pContext, pTmpMembers);
+	MikkFree(pContext, pSubGroupTspace);
 
+	*piNrSubGroups = iUniqueTspaces;
 	return TTRUE;
 }
 
@@ -1368,7 +1617,7 @@ static STSpace EvalTspace(int face_indices[], const int iFaces, const int piTriL
                           const SMikkTSpaceContext * pContext, const int iVertexRepresentitive)
 {
 	STSpace res;
-	float fAngleSum = 0;
+	treal fAngleSum = 0;
 	int face=0;
 	res.vOs.x=0.0f; res.vOs.y=0.0f; res.vOs.z=0.0f;
 	res.vOt.x=0.0f; res.vOt.y=0.0f; res.vOt.z=0.0f;
@@ -1382,7 +1631,7 @@ static STSpace EvalTspace(int face_indices[], const int iFaces, const int piTriL
 		if ( (pTriInfos[f].iFlag&GROUP_WITH_ANY)==0 )
 		{
 			SVec3 n, vOs, vOt, p0, p1, p2, v1, v2;
-			float fCos, fAngle, fMagS, fMagT;
+			treal fCos, fAngle, fMagS, fMagT;
 			int i=-1, index=-1, i0=-1, i1=-1, i2=-1;
 			if (piTriListIn[3*f+0]==iVertexRepresentitive) i=0;
 			else if (piTriListIn[3*f+1]==iVertexRepresentitive) i=1;
@@ -1414,7 +1663,7 @@ static STSpace EvalTspace(int face_indices[], const int iFaces, const int piTriL
 			// weight contribution by the angle
 			// between the two edge vectors
 			fCos = vdot(v1,v2); fCos=fCos>1?1:(fCos<(-1) ? (-1) : fCos);
-			fAngle = (float) acos(fCos);
+			fAngle = (treal) acos(fCos);
 			fMagS = pTriInfos[f].fMagS;
 			fMagT = pTriInfos[f].fMagT;
 
@@ -24,6 +24,7 @@
 #ifndef __MIKKTSPACE_H__
 #define __MIKKTSPACE_H__
 
+#include <stddef.h>
 
 #ifdef __cplusplus
 extern "C" {
@@ -58,6 +59,20 @@ extern "C" {
  * and also quad triangulator plugin.
  */
 
+/* This is an altered version of the original files, modified for mikktspace-sys.
+ * All extensions are opt-in. When they are not used the generated results are
+ * identical to the original implementation.
+ *  - optional smoothing group and sharp edge call-backs
+ *  - genTangSpaceEx() taking SMikkTSpaceOptions
+ *  - uniform orientation per face and fallback tangent spaces as options
+ *  - a double precision variant of the interface and of the entry points
+ *  - mikktspace.c refuses to build with fast math enabled
+ *  - optional allocator call-backs replacing malloc() and free()
+ *  - optional call-backs marking the phases of genTangSpace()
+ *  - an optional call-back reporting the groups and subgroups of every corner
+ *  - an optional call-back reporting the progress of every phase
+ */
+
 
 typedef int tbool;
 typedef struct SMikkTSpaceContext SMikkTSpaceContext;
@@ -101,6 +116,47 @@ typedef struct {
 	// DO NOT! use an already existing index list.
 	void (*m_setTSpace)(const SMikkTSpaceContext * pContext, const float fvTangent[], const float fvBiTangent[], const float fMagS, const float fMagT,
 						const tbool bIsOrientationPreserving, const int iFace, const int iVert);
+
+	// The following call-backs are extensions added for mikktspace-sys and may be left NULL.
+
+	// Returns the smoothing group bit mask of face number iFace. Tangent spaces are never
+	// shared across an edge between two faces whose masks have no bit in common.
+	unsigned int (*m_getSmoothingGroup)(const SMikkTSpaceContext * pContext, const int iFace);
+
+	// Returns true if the edge from vertex iVert to vertex (iVert+1)%getNumVerticesOfFace(iFace)
+	// of face iFace is sharp. Tangent spaces are never shared across a sharp edge. An edge
+	// is sharp if either of the two faces using it reports it as sharp.
+	tbool (*m_isEdgeSharp)(const SMikkTSpaceContext * pContext, const int iFace, const int iVert);
+
+	// Called for every corner which received a fallback tangent space, see SMikkTSpaceOptions::iFallback.
+	// It is called right before the setTSpace call-backs of the same corner.
+	void (*m_reportFallback)(const SMikkTSpaceContext * pContext, const int iFace, const int iVert);
+
+	// Replace malloc() and free() for all internal buffers. Either both or neither must be set.
+	// The returned memory must be suitably aligned for any type. Returning NULL makes
+	// genTangSpace() fail cleanly, except for the welding and edge buffers when
+	// SMikkTSpaceOptions::bQuadraticFallback is set, which take the slower allocation free paths.
+	void * (*m_alloc)(const SMikkTSpaceContext * pContext, const size_t iSize);
+	void (*m_free)(const SMikkTSpaceContext * pContext, void * pPtr);
+
+	// Called when genTangSpace() enters and leaves one of its phases, see MIKK_PHASE_*.
+	// iCount is the number of items the phase produced, as documented for each phase.
+	void (*m_beginPhase)(const SMikkTSpaceContext * pContext, const int iPhase);
+	void (*m_endPhase)(const SMikkTSpaceContext * pContext, const int iPhase, const int iCount);
+
+	// Called for every corner of every triangle which got assigned to a group. Corners shared by
+	// the two triangles of a quad are reported twice. iGroup numbers the groups of triangles
+	// found by Build4RuleGroups(), iSubGroup the distinct tangent spaces they are split into by the
+	// angular threshold. piFaces lists the faces of the triangles averaged into the subgroup
+	// tangent space, faces split into two triangles appear twice. Triangles without usable
+	// texture derivatives (GROUP_WITH_ANY) join subgroups but contribute nothing and aren't listed.
+	void (*m_reportCorner)(const SMikkTSpaceContext * pContext, const int iFace, const int iVert, const int iGroup,
+						   const int iSubGroup, const tbool bOrientPreserving, const int piFaces[], const int iNrFaces);
+
+	// Called periodically while a phase runs, see MIKK_PHASE_*. iDone of iTotal items of the phase
+	// are processed, what an item is depends on the phase. Every phase that runs ends with a call
+	// where iDone equals iTotal.
+	void (*m_reportProgress)(const SMikkTSpaceContext * pContext, const int iPhase, const int iDone, const int iTotal);
 } SMikkTSpaceInterface;
 
 struct SMikkTSpaceContext
@@ -109,9 +165,81 @@ struct SMikkTSpaceContext
 	void * m_pUserData;						// pointer to client side mesh data etc. (passed as the first parameter with every interface call)
 };
 
-// these are both thread safe!
+typedef struct
+{
+	float fAngularThreshold;		// same as the parameter of genTangSpace()
+
+	// Force every corner of a face to share one coordinate system orientation.
+	// Degenerate triangles and triangles without usable texture coordinates otherwise
+	// may vary between left and right handed at their vertices.
+	tbool bUniformOrientation;
+
+	// What to return for corners no tangent space could be derived for, for
+	// instance because all texture coordinates around the vertex are degenerate.
+	int iFallback;
+
+	// Fall back to the allocation free but quadratic welding and neighbor search
+	// when their buffers can't be allocated, instead of failing. genTangSpace()
+	// and genTangSpaceDefault() set this, like the original implementation.
+	tbool bQuadraticFallback;
+} SMikkTSpaceOptions;
+
+// Keep the initialization values tangent=(1,0,0) and bitangent=(0,1,0) (original behavior).
+#define MIKK_FALLBACK_NONE			0
+// Build an orthonormal basis around the vertex normal.
+#define MIKK_FALLBACK_NORMAL_BASIS	1
+
+// Phases reported through m_beginPhase(), m_endPhase() and m_reportProgress(), in order.
+#define MIKK_PHASE_WELD				0	// GenerateSharedVerticesIndexList(), count: triangles
+#define MIKK_PHASE_DEGENERATES		1	// degenerate marking, count: degenerate triangles
+#define MIKK_PHASE_TRI_INFO			2	// InitTriInfo() and hard edges, count: remaining triangles
+#define MIKK_PHASE_GROUPS			3	// Build4RuleGroups(), count: active groups
+#define MIKK_PHASE_TSPACES			4	// GenerateTSpaces(), count: subgroups
+#define MIKK_PHASE_DEGEN_EPILOGUE	5	// DegenEpilogue(), count: degenerate triangles
+#define MIKK_PHASE_COUNT			6
+
+// these are all thread safe!
 tbool genTangSpaceDefault(const SMikkTSpaceContext * pContext);	// Default (recommended) fAngularThreshold is 180 degrees (which means threshold disabled)
 tbool genTangSpace(const SMikkTSpaceContext * pContext, const float fAngularThreshold);
+tbool genTangSpaceEx(const SMikkTSpaceContext * pContext, const SMikkTSpaceOptions * pOptions);
+
+
+// Double precision variant. The interface is identical to SMikkTSpaceInterface except that
+// all attributes and results are doubles. All internal computations are done in double
+// precision as well, which keeps welding and derivatives stable for meshes placed far
+// away from the origin. Results differ from the single precision path in the last bits.
+typedef struct SMikkTSpaceContextF64 SMikkTSpaceContextF64;
+
+typedef struct {
+	int (*m_getNumFaces)(const SMikkTSpaceContextF64 * pContext);
+	int (*m_getNumVerticesOfFace)(const SMikkTSpaceContextF64 * pContext, const int iFace);
+	void (*m_getPosition)(const SMikkTSpaceContextF64 * pContext, double fvPosOut[], const int iFace, const int iVert);
+	void (*m_getNormal)(const SMikkTSpaceContextF64 * pContext, double fvNormOut[], const int iFace, const int iVert);
+	void (*m_getTexCoord)(const SMikkTSpaceContextF64 * pContext, double fvTexcOut[], const int iFace, const int iVert);
+	void (*m_setTSpaceBasic)(const SMikkTSpaceContextF64 * pContext, const double fvTangent[], const double fSign, const int iFace, const int iVert);
+	void (*m_setTSpace)(const SMikkTSpaceContextF64 * pContext, const double fvTangent[], const double fvBiTangent[], const double fMagS, const double fMagT,
+						const tbool bIsOrientationPreserving, const int iFace, const int iVert);
+	unsigned int (*m_getSmoothingGroup)(const SMikkTSpaceContextF64 * pContext, const int iFace);
+	tbool (*m_isEdgeSharp)(const SMikkTSpaceContextF64 * pContext, const int iFace, const int iVert);
+	void (*m_reportFallback)(const SMikkTSpaceContextF64 * pContext, const int iFace, const int iVert);
+	void * (*m_alloc)(const SMikkTSpaceContextF64 * pContext, const size_t iSize);
+	void (*m_free)(const SMikkTSpaceContextF64 * pContext, void * pPtr);
+	void (*m_beginPhase)(const SMikkTSpaceContextF64 * pContext, const int iPhase);
+	void (*m_endPhase)(const SMikkTSpaceContextF64 * pContext, const int iPhase, const int iCount);
+	void (*m_reportCorner)(const SMikkTSpaceContextF64 * pContext, const int iFace, const int iVert, const int iGroup,
+						   const int iSubGroup, const tbool bOrientPreserving, const int piFaces[], const int iNrFaces);
+	void (*m_reportProgress)(const SMikkTSpaceContextF64 * pContext, const int iPhase, const int iDone, const int iTotal);
+} SMikkTSpaceInterfaceF64;
+
+struct SMikkTSpaceContextF64
+{
+	SMikkTSpaceInterfaceF64 * m_pInterface;
+	void * m_pUserData;
+};
+
+tbool genTangSpaceDefaultF64(const SMikkTSpaceContextF64 * pContext);
+tbool genTangSpaceF64(const SMikkTSpaceContextF64 * pContext, const float fAngularThreshold);
+tbool genTangSpaceExF64(const SMikkTSpaceContextF64 * pContext, const SMikkTSpaceOptions * pOptions);
 
 
 // To avoid visual errors (distortions/unwanted hard edges in lighting), when using sampled normal maps, the
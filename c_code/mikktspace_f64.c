@@ -0,0 +1,7 @@
+/** \file mikktspace/mikktspace_f64.c
+ *  \ingroup mikktspace
+ */
+
+// Double precision build of mikktspace.c, see genTangSpaceExF64() in mikktspace.h.
+#define MIKK_F64
+#include "mikktspace.c"
@@ -0,0 +1,78 @@
+//! Writes the generated tangent frames as a line mesh for inspection in any
+//! mesh viewer, see `tangent_frames` for the colors.
+//!
+//! `cargo run --example export_frames -- [input.ply] frames.ply` (or
+//! `frames.obj`). Without an input a wavy grid with mirrored texture
+//! coordinates is used.
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use mikktspace_sys::{
+    gen_tang_space_collect, read_ply, tangent_frames, write_frames_obj, write_frames_ply,
+    FrameOptions, GenOptions, MikkTSpaceInterface, PlyMesh,
+};
+
+/// Writes the frames of `mesh` to `path`, as OBJ if its extension is `obj`
+/// and as PLY otherwise.
+fn export_frames<I>(mesh: &mut I, path: &Path) -> Result<usize, Box<dyn std::error::Error>>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let tspaces = gen_tang_space_collect(mesh, &GenOptions::default())?;
+    let segments = tangent_frames(mesh, &tspaces, &FrameOptions::default());
+    let mut out = BufWriter::new(File::create(path)?);
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("obj") => write_frames_obj(&mut out, &segments)?,
+        _ => write_frames_ply(&mut out, &segments)?,
+    }
+    out.flush()?;
+    Ok(segments.len() / 3)
+}
+
+/// Wavy `n` x `n` grid whose texture coordinates are mirrored in the middle.
+fn make_grid(n: u32) -> PlyMesh {
+    let mut mesh = PlyMesh::default();
+    for y in 0..=n {
+        for x in 0..=n {
+            let (fx, fy) = (x as f32 / n as f32, y as f32 / n as f32);
+            let z = 0.1 * f32::sin(6.0 * fx);
+            let dx = 0.6 * f32::cos(6.0 * fx);
+            let len = f32::sqrt(dx * dx + 1.0);
+            mesh.positions.push([fx, fy, z]);
+            mesh.normals.push([-dx / len, 0.0, 1.0 / len]);
+            mesh.tex_coords
+                .push([if fx > 0.5 { 1.0 - fx } else { fx }, fy]);
+        }
+    }
+    let row = n + 1;
+    for y in 0..n {
+        for x in 0..n {
+            let i = y * row + x;
+            mesh.faces.push(vec![i, i + 1, i + row + 1, i + row]);
+        }
+    }
+    mesh
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (mut mesh, output) = match args.as_slice() {
+        [] => (make_grid(6), "tangent_frames.ply"),
+        [output] => (make_grid(6), output.as_str()),
+        [input, output] => (
+            read_ply(BufReader::new(File::open(input)?))?,
+            output.as_str(),
+        ),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "usage: export_frames [input.ply] frames.ply",
+            )
+            .into())
+        }
+    };
+    let frames = export_frames(&mut mesh, Path::new(output))?;
+    println!("wrote {} frames to {}", frames, output);
+    Ok(())
+}
@@ -1,3 +1,6 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+
 /// Example taken & slightly modified from mikktspace crate
 pub type Face = [u32; 3];
 
@@ -253,8 +256,36 @@ fn make_cube() -> Mesh {
     Mesh { faces, vertices }
 }
 
-fn main() {
+/// Reads `input`, generates tangents and writes the re-indexed mesh with
+/// `tx ty tz tw` vertex properties to `output`.
+fn convert_ply(input: &str, output: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut mesh = mikktspace_sys::read_ply(BufReader::new(File::open(input)?))?;
+    let num_vertices = mesh.positions.len();
+    mesh.generate_tangents(&mikktspace_sys::GenOptions::default())?;
+    let mut out = BufWriter::new(File::create(output)?);
+    mikktspace_sys::write_ply(
+        &mut out,
+        &mesh,
+        mikktspace_sys::PlyFormat::BinaryLittleEndian,
+    )?;
+    out.flush()?;
+    println!(
+        "wrote {} vertices ({} split) to {}",
+        mesh.positions.len(),
+        mesh.positions.len() - num_vertices,
+        output
+    );
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(input) = args.first() {
+        let output = args.get(1).map_or("tangents.ply", String::as_str);
+        return convert_ply(input, output);
+    }
     let mut cube = make_cube();
     let ret = mikktspace_sys::gen_tang_space_default(&mut cube);
     assert!(ret);
+    Ok(())
 }
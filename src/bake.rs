@@ -0,0 +1,409 @@
+//! Baking of tangent space normal maps from a high poly mesh.
+//!
+//! Every texel covered by the low poly mesh casts a ray along the negated
+//! interpolated normal, starting `front_distance` outside of the surface, and
+//! takes the normal of the first high poly triangle hit within
+//! `front_distance + rear_distance`. With a cage the ray starts at the same
+//! point of the cage instead and goes through the low poly surface, which
+//! avoids the gaps and overlaps of diverging normals at hard edges. The
+//! object space normal hit is transformed
+//! into the tangent frame interpolated at the texel with the exact inverse of
+//! the pixel shader transform, see `normal_map`, so a renderer using the
+//! tangent spaces generated for the low poly mesh reproduces it.
+
+use std::error::Error;
+use std::fmt;
+
+use super::normal_map::{
+    cross, dot, for_each_texel, normalize, sub, CornerFrames, Frame, SurfacePoint,
+};
+use super::triangulate::face_triangles;
+use super::{GenError, GenOptions, MikkTSpaceInterface, NormalMap};
+
+/// Size of the baked map and extent of the rays.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BakeOptions {
+    pub width: usize,
+    pub height: usize,
+    /// How far outside of the low poly surface the rays start, in object
+    /// space units. Unused with a cage.
+    pub front_distance: f32,
+    /// How far inside of the low poly surface the rays end.
+    pub rear_distance: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BakeError {
+    /// The cage has `found` faces instead of the `expected` of the low poly
+    /// mesh.
+    CageFaceCount {
+        expected: usize,
+        found: usize,
+    },
+    /// Face `face` of the cage has `found` vertices instead of `expected`.
+    CageVertexCount {
+        face: usize,
+        expected: usize,
+        found: usize,
+    },
+    Gen(GenError),
+}
+
+impl fmt::Display for BakeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BakeError::CageFaceCount { expected, found } => write!(
+                f,
+                "the cage has {} faces, the low poly mesh {}",
+                found, expected
+            ),
+            BakeError::CageVertexCount {
+                face,
+                expected,
+                found,
+            } => write!(
+                f,
+                "face {} of the cage has {} vertices, the low poly face {}",
+                face, found, expected
+            ),
+            BakeError::Gen(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for BakeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BakeError::Gen(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<GenError> for BakeError {
+    fn from(error: GenError) -> Self {
+        BakeError::Gen(error)
+    }
+}
+
+/// Bakes the normals of `high` into a tangent space normal map for `low`,
+/// matching the tangent spaces `gen_tang_space_ex` generates for `low` with
+/// `options`.
+///
+/// Only the positions and normals of `high` are used, its faces are split
+/// into triangles like the ones of `low`. Texels not covered by `low` and
+/// texels whose ray misses `high` are set to `[0.0, 0.0, 1.0]`, the
+/// unperturbed low poly normal.
+///
+/// `cage` is an optional copy of `low` with its vertices pushed outwards,
+/// typically along averaged normals so that it stays closed at hard edges.
+/// Only its positions are used. Rays then start at the cage and go through
+/// the low poly surface, ending `rear_distance` behind it. A cage without
+/// the faces of `low`, with the same number of vertices each, is rejected.
+pub fn bake_normal_map<L, H>(
+    low: &mut L,
+    high: &H,
+    cage: Option<&dyn MikkTSpaceInterface>,
+    options: &GenOptions,
+    bake: &BakeOptions,
+) -> Result<NormalMap, BakeError>
+where
+    L: MikkTSpaceInterface + ?Sized,
+    H: MikkTSpaceInterface + ?Sized,
+{
+    if let Some(cage) = cage {
+        let expected = low.get_num_faces();
+        let found = cage.get_num_faces();
+        if found != expected {
+            return Err(BakeError::CageFaceCount { expected, found });
+        }
+        for face in 0..expected {
+            let expected = low.get_num_vertices_of_face(face);
+            let found = cage.get_num_vertices_of_face(face);
+            if found != expected {
+                return Err(BakeError::CageVertexCount {
+                    face,
+                    expected,
+                    found,
+                });
+            }
+        }
+    }
+    let frames = CornerFrames::generate(low, options)?;
+    let bvh = Bvh::new(high);
+    let mut map = NormalMap::new(bake.width, bake.height, [0.0, 0.0, 1.0]);
+    for_each_texel(
+        low,
+        &frames,
+        bake.width,
+        bake.height,
+        |x, y, frame, point| {
+            let ray = match cage {
+                Some(cage) => Ray::from_cage(cage, point, frame, bake.rear_distance),
+                None => Ray::along_normal(point, frame, bake),
+            };
+            if let Some(normal) = bvh.intersect(&ray) {
+                map.set(x, y, frame.object_to_tangent(normal));
+            }
+        },
+    );
+    Ok(map)
+}
+
+struct Ray {
+    origin: [f32; 3],
+    dir: [f32; 3],
+    max: f32,
+}
+
+impl Ray {
+    fn along_normal(point: &SurfacePoint, frame: &Frame, bake: &BakeOptions) -> Self {
+        let n = normalize(frame.normal);
+        let p = point.position;
+        Ray {
+            origin: [
+                p[0] + n[0] * bake.front_distance,
+                p[1] + n[1] * bake.front_distance,
+                p[2] + n[2] * bake.front_distance,
+            ],
+            dir: [-n[0], -n[1], -n[2]],
+            max: bake.front_distance + bake.rear_distance,
+        }
+    }
+
+    /// From the point of the cage with the same barycentric coordinates
+    /// through `point`. Where the cage touches the surface the ray follows
+    /// the negated normal.
+    fn from_cage(
+        cage: &dyn MikkTSpaceInterface,
+        point: &SurfacePoint,
+        frame: &Frame,
+        rear_distance: f32,
+    ) -> Self {
+        let mut origin = [0.0; 3];
+        for (&vert, &weight) in point.verts.iter().zip(point.barycentric.iter()) {
+            let c = cage.get_position(point.face, vert);
+            for i in 0..3 {
+                origin[i] += weight * c[i];
+            }
+        }
+        let d = sub(point.position, origin);
+        let length = dot(d, d).sqrt();
+        let dir = if length > 0.0 {
+            [d[0] / length, d[1] / length, d[2] / length]
+        } else {
+            let n = normalize(frame.normal);
+            [-n[0], -n[1], -n[2]]
+        };
+        Ray {
+            origin,
+            dir,
+            max: length + rear_distance,
+        }
+    }
+}
+
+struct Triangle {
+    positions: [[f32; 3]; 3],
+    normals: [[f32; 3]; 3],
+}
+
+impl Triangle {
+    /// Distance along the ray and interpolated normal of the hit, if any.
+    /// Both sides of the triangle are hit.
+    fn intersect(&self, ray: &Ray) -> Option<(f32, [f32; 3])> {
+        let [p0, p1, p2] = self.positions;
+        let e1 = sub(p1, p0);
+        let e2 = sub(p2, p0);
+        let h = cross(ray.dir, e2);
+        let det = dot(e1, h);
+        if det == 0.0 {
+            return None;
+        }
+        let s = sub(ray.origin, p0);
+        let u = dot(s, h) / det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = cross(s, e1);
+        let v = dot(ray.dir, q) / det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = dot(e2, q) / det;
+        if !(0.0..=ray.max).contains(&t) {
+            return None;
+        }
+        let w = 1.0 - u - v;
+        let [n0, n1, n2] = self.normals;
+        let normal = [
+            w * n0[0] + u * n1[0] + v * n2[0],
+            w * n0[1] + u * n1[1] + v * n2[1],
+            w * n0[2] + u * n1[2] + v * n2[2],
+        ];
+        Some((t, normalize(normal)))
+    }
+
+    fn centroid(&self, axis: usize) -> f32 {
+        self.positions.iter().map(|p| p[axis]).sum::<f32>() / 3.0
+    }
+}
+
+/// Axis aligned bounding box.
+#[derive(Clone, Copy)]
+struct Bounds {
+    min: [f32; 3],
+    max: [f32; 3],
+}
+
+impl Bounds {
+    fn of(triangles: &[Triangle]) -> Self {
+        let mut bounds = Bounds {
+            min: [f32::INFINITY; 3],
+            max: [f32::NEG_INFINITY; 3],
+        };
+        for p in triangles.iter().flat_map(|t| t.positions.iter()) {
+            for (i, &x) in p.iter().enumerate() {
+                bounds.min[i] = bounds.min[i].min(x);
+                bounds.max[i] = bounds.max[i].max(x);
+            }
+        }
+        bounds
+    }
+
+    fn longest_axis(&self) -> usize {
+        let e = sub(self.max, self.min);
+        if e[0] >= e[1] && e[0] >= e[2] {
+            0
+        } else if e[1] >= e[2] {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Slab test against the ray segment.
+    fn hit(&self, ray: &Ray, max: f32) -> bool {
+        let (mut near, mut far) = (0.0f32, max);
+        for i in 0..3 {
+            let inv = 1.0 / ray.dir[i];
+            let mut t0 = (self.min[i] - ray.origin[i]) * inv;
+            let mut t1 = (self.max[i] - ray.origin[i]) * inv;
+            if inv < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // NaN from 0 * inf keeps the previous bounds.
+            near = if t0 > near { t0 } else { near };
+            far = if t1 < far { t1 } else { far };
+            if near > far {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Leaves hold `count` triangles starting at `first`, inner nodes have their
+/// left child right after them and their right child at `first`.
+struct Node {
+    bounds: Bounds,
+    first: usize,
+    count: usize,
+}
+
+/// Bounding volume hierarchy over the triangles of the high poly mesh.
+struct Bvh {
+    triangles: Vec<Triangle>,
+    nodes: Vec<Node>,
+}
+
+const LEAF_SIZE: usize = 4;
+
+impl Bvh {
+    fn new<H>(mesh: &H) -> Self
+    where
+        H: MikkTSpaceInterface + ?Sized,
+    {
+        let mut triangles = Vec::new();
+        for face in 0..mesh.get_num_faces() {
+            for tri in face_triangles(mesh, face) {
+                triangles.push(Triangle {
+                    positions: [
+                        mesh.get_position(face, tri[0]),
+                        mesh.get_position(face, tri[1]),
+                        mesh.get_position(face, tri[2]),
+                    ],
+                    normals: [
+                        mesh.get_normal(face, tri[0]),
+                        mesh.get_normal(face, tri[1]),
+                        mesh.get_normal(face, tri[2]),
+                    ],
+                });
+            }
+        }
+        let mut bvh = Bvh {
+            triangles,
+            nodes: Vec::new(),
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.build(0, bvh.triangles.len());
+        }
+        bvh
+    }
+
+    fn build(&mut self, start: usize, end: usize) {
+        let bounds = Bounds::of(&self.triangles[start..end]);
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            bounds,
+            first: start,
+            count: end - start,
+        });
+        if end - start <= LEAF_SIZE {
+            return;
+        }
+        let axis = bounds.longest_axis();
+        let mid = (start + end) / 2;
+        self.triangles[start..end].select_nth_unstable_by(mid - start, |a, b| {
+            a.centroid(axis)
+                .partial_cmp(&b.centroid(axis))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.build(start, mid);
+        let right = self.nodes.len();
+        self.build(mid, end);
+        self.nodes[index].first = right;
+        self.nodes[index].count = 0;
+    }
+
+    /// Normal at the closest hit along the ray.
+    fn intersect(&self, ray: &Ray) -> Option<[f32; 3]> {
+        let mut closest: Option<(f32, [f32; 3])> = None;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = match self.nodes.get(index) {
+                Some(node) => node,
+                None => continue,
+            };
+            let max = closest.map_or(ray.max, |(t, _)| t);
+            if !node.bounds.hit(ray, max) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.first);
+                stack.push(index + 1);
+                continue;
+            }
+            for triangle in &self.triangles[node.first..node.first + node.count] {
+                if let Some((t, normal)) = triangle.intersect(ray) {
+                    if closest.map_or(true, |(best, _)| t < best) {
+                        closest = Some((t, normal));
+                    }
+                }
+            }
+        }
+        closest.map(|(_, normal)| normal)
+    }
+}
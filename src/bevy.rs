@@ -0,0 +1,287 @@
+//! Tangents for `bevy::render::mesh::Mesh` through the C reference
+//! implementation.
+//!
+//! Every triangle corner gets the tangent generated for it. Vertices whose
+//! corners got different tangents are split with `reindex`: the split
+//! vertices are appended to every attribute and the index list is rewritten.
+//! Meshes without indices have one vertex per corner and never need splits.
+
+use std::error::Error;
+use std::fmt;
+
+use bevy_mesh::{Indices, Mesh, MeshAccessError, MeshVertexAttribute, VertexAttributeValues};
+
+use super::reindex::reindex;
+use super::{gen_tang_space_ex, GenError, GenOptions, GenStats, MikkTSpaceInterface};
+
+#[derive(Debug)]
+pub enum BevyTangentError {
+    /// Only triangle lists are supported.
+    UnsupportedTopology,
+    /// `ATTRIBUTE_POSITION`, `ATTRIBUTE_NORMAL` or `ATTRIBUTE_UV_0` is
+    /// missing, named by the attribute name.
+    MissingAttribute(&'static str),
+    /// An attribute doesn't have the standard format, `Float32x3` for
+    /// positions and normals and `Float32x2` for texture coordinates.
+    InvalidAttributeFormat(&'static str),
+    /// An attribute has a different number of values than
+    /// `ATTRIBUTE_POSITION`.
+    AttributeLength {
+        name: &'static str,
+        len: usize,
+        expected: usize,
+    },
+    /// An index refers to a vertex past the end of the attributes.
+    IndexOutOfBounds {
+        index: usize,
+        num_vertices: usize,
+    },
+    /// Morph targets live in a separate image that can't be split along with
+    /// the attributes.
+    MorphTargets,
+    Access(MeshAccessError),
+    Gen(GenError),
+}
+
+impl fmt::Display for BevyTangentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BevyTangentError::UnsupportedTopology => {
+                write!(f, "tangents can only be generated for triangle lists")
+            }
+            BevyTangentError::MissingAttribute(name) => {
+                write!(f, "missing vertex attribute '{}'", name)
+            }
+            BevyTangentError::InvalidAttributeFormat(name) => {
+                write!(f, "vertex attribute '{}' has an unsupported format", name)
+            }
+            BevyTangentError::AttributeLength {
+                name,
+                len,
+                expected,
+            } => write!(
+                f,
+                "vertex attribute '{}' has {} values, expected {}",
+                name, len, expected
+            ),
+            BevyTangentError::IndexOutOfBounds {
+                index,
+                num_vertices,
+            } => write!(
+                f,
+                "index {} is out of bounds for {} vertices",
+                index, num_vertices
+            ),
+            BevyTangentError::MorphTargets => {
+                write!(
+                    f,
+                    "tangents can't be generated for meshes with morph targets"
+                )
+            }
+            BevyTangentError::Access(error) => error.fmt(f),
+            BevyTangentError::Gen(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for BevyTangentError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BevyTangentError::Access(error) => Some(error),
+            BevyTangentError::Gen(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<MeshAccessError> for BevyTangentError {
+    fn from(error: MeshAccessError) -> Self {
+        BevyTangentError::Access(error)
+    }
+}
+
+impl From<GenError> for BevyTangentError {
+    fn from(error: GenError) -> Self {
+        BevyTangentError::Gen(error)
+    }
+}
+
+/// Generates tangent spaces for `mesh` with `options` and inserts them as
+/// `Mesh::ATTRIBUTE_TANGENT`, the tangent in `xyz` and the sign of the
+/// bitangent in `w` as passed to `set_tspace_basic`.
+///
+/// Vertices used by corners with different tangents are split, see the
+/// module documentation. Vertices no triangle uses get a zero tangent. All
+/// attributes must have as many values as `ATTRIBUTE_POSITION` and all indices
+/// must be in bounds. Meshes with morph targets are rejected because the
+/// targets can't be split. On error `mesh` is left unchanged.
+pub fn generate_bevy_tangents(
+    mesh: &mut Mesh,
+    options: &GenOptions,
+) -> Result<GenStats, BevyTangentError> {
+    if mesh.primitive_topology() != bevy_mesh::PrimitiveTopology::TriangleList {
+        return Err(BevyTangentError::UnsupportedTopology);
+    }
+    if mesh.try_has_morph_targets()? {
+        return Err(BevyTangentError::MorphTargets);
+    }
+    let positions = float3(mesh, Mesh::ATTRIBUTE_POSITION)?;
+    let normals = float3(mesh, Mesh::ATTRIBUTE_NORMAL)?;
+    let tex_coords = match mesh.try_attribute_option(Mesh::ATTRIBUTE_UV_0)? {
+        Some(VertexAttributeValues::Float32x2(values)) => values,
+        Some(_) => {
+            return Err(BevyTangentError::InvalidAttributeFormat(
+                Mesh::ATTRIBUTE_UV_0.name,
+            ))
+        }
+        None => {
+            return Err(BevyTangentError::MissingAttribute(
+                Mesh::ATTRIBUTE_UV_0.name,
+            ))
+        }
+    };
+    let num_vertices = positions.len();
+    for (attribute, values) in mesh.try_attributes()? {
+        if values.len() != num_vertices {
+            return Err(BevyTangentError::AttributeLength {
+                name: attribute.name,
+                len: values.len(),
+                expected: num_vertices,
+            });
+        }
+    }
+    let indices: Vec<u32> = match mesh.try_indices_option()? {
+        Some(indices) => indices.iter().map(|index| index as u32).collect(),
+        None => (0..num_vertices as u32).collect(),
+    };
+    if let Some(&index) = indices
+        .iter()
+        .find(|&&index| index as usize >= num_vertices)
+    {
+        return Err(BevyTangentError::IndexOutOfBounds {
+            index: index as usize,
+            num_vertices,
+        });
+    }
+
+    let mut geometry = Geometry {
+        indices: &indices,
+        positions,
+        normals,
+        tex_coords,
+        tangents: vec![[0.0; 4]; indices.len() / 3 * 3],
+    };
+    let stats = gen_tang_space_ex(&mut geometry, options)?;
+    let corner_tangents = geometry.tangents;
+
+    let keys: Vec<[u32; 4]> = corner_tangents
+        .iter()
+        .map(|tangent| tangent.map(f32::to_bits))
+        .collect();
+    let reindexed = reindex(&indices[..keys.len()], num_vertices, &keys);
+    let splits = reindexed.splits(num_vertices);
+
+    let mut tangents = vec![[0.0; 4]; reindexed.sources.len()];
+    for (&vertex, tangent) in reindexed.indices.iter().zip(&corner_tangents) {
+        tangents[vertex as usize] = *tangent;
+    }
+    if !splits.is_empty() {
+        // Everything is built before the mesh is touched, so that it stays
+        // unchanged if inserting the indices fails. The attributes were read
+        // above, so inserting them can't fail after that.
+        let attributes: Vec<(MeshVertexAttribute, VertexAttributeValues)> = mesh
+            .try_attributes()?
+            .map(|(attribute, values)| {
+                let mut values = values.clone();
+                append(&mut values, splits);
+                (*attribute, values)
+            })
+            .collect();
+        let mut new_indices = reindexed.indices;
+        // Corners of an incomplete last triangle aren't part of any face.
+        new_indices.extend_from_slice(&indices[keys.len()..]);
+        mesh.try_insert_indices(match mesh.try_indices_option()? {
+            Some(Indices::U16(_)) if reindexed.sources.len() <= 1 << 16 => {
+                Indices::U16(new_indices.iter().map(|&index| index as u16).collect())
+            }
+            _ => Indices::U32(new_indices),
+        })?;
+        for (attribute, values) in attributes {
+            mesh.try_insert_attribute(attribute, values)?;
+        }
+    }
+    mesh.try_insert_attribute(Mesh::ATTRIBUTE_TANGENT, tangents)?;
+    Ok(stats)
+}
+
+fn float3(mesh: &Mesh, attribute: MeshVertexAttribute) -> Result<&[[f32; 3]], BevyTangentError> {
+    match mesh.try_attribute_option(attribute)? {
+        Some(VertexAttributeValues::Float32x3(values)) => Ok(values),
+        Some(_) => Err(BevyTangentError::InvalidAttributeFormat(attribute.name)),
+        None => Err(BevyTangentError::MissingAttribute(attribute.name)),
+    }
+}
+
+/// Appends copies of the vertices `sources` to `values`.
+fn append(values: &mut VertexAttributeValues, sources: &[u32]) {
+    macro_rules! append {
+        ($($variant:ident),+) => {
+            match values {
+                $(
+                    VertexAttributeValues::$variant(values) => {
+                        for &source in sources {
+                            values.push(values[source as usize]);
+                        }
+                    }
+                )+
+            }
+        };
+    }
+    append!(
+        Float32, Sint32, Uint32, Float32x2, Sint32x2, Uint32x2, Float32x3, Sint32x3, Uint32x3,
+        Sint32x4, Uint32x4, Float32x4, Sint16x2, Snorm16x2, Uint16x2, Unorm16x2, Sint16x4,
+        Snorm16x4, Uint16x4, Unorm16x4, Sint8x2, Snorm8x2, Uint8x2, Unorm8x2, Sint8x4, Snorm8x4,
+        Uint8x4, Unorm8x4
+    );
+}
+
+/// The triangles of the mesh, collecting the tangent of every corner.
+struct Geometry<'a> {
+    indices: &'a [u32],
+    positions: &'a [[f32; 3]],
+    normals: &'a [[f32; 3]],
+    tex_coords: &'a [[f32; 2]],
+    tangents: Vec<[f32; 4]>,
+}
+
+impl<'a> Geometry<'a> {
+    fn vertex(&self, face: usize, vert: usize) -> usize {
+        self.indices[face * 3 + vert] as usize
+    }
+}
+
+impl<'a> MikkTSpaceInterface for Geometry<'a> {
+    fn get_num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn get_num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.positions[self.vertex(face, vert)]
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.normals[self.vertex(face, vert)]
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.tex_coords[self.vertex(face, vert)]
+    }
+
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {
+        self.tangents[face * 3 + vert] = [tangent[0], tangent[1], tangent[2], sign];
+    }
+}
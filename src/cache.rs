@@ -0,0 +1,302 @@
+//! Content addressed on-disk store of generated tangent spaces.
+//!
+//! The key is a SHA-256 hash of everything the result depends on: the crate
+//! version, the C sources, every attribute the generator reads and the
+//! options. `GenOptions::memory_limit` and `GenOptions::quadratic_fallback`
+//! are left out, they only decide whether generation succeeds, not what it
+//! produces. Entries are written to a temporary file unique to the call and
+//! renamed into place, so concurrent writers at worst generate the same entry
+//! twice. Entries that
+//! can't be read or fail their checksum are treated as missing and
+//! regenerated.
+//!
+//! An entry is the magic `MKTS`, a little endian `u32` format version, a
+//! `u64` corner count and 33 bytes per corner in face order: tangent,
+//! bitangent, `mag_s` and `mag_t` as little endian `f32` and a flags byte,
+//! bit 0 for `is_orientation_preserving` and bit 1 for `is_fallback`. The
+//! SHA-256 of all of that follows as a checksum.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use sha2::{Digest, Sha256};
+
+use super::tspaces::{gen_tang_space_collect, TSpace};
+use super::{FallbackPolicy, GenError, GenOptions, MikkTSpaceInterface};
+
+const MAGIC: &[u8; 4] = b"MKTS";
+const FORMAT_VERSION: u32 = 2;
+const HEADER_SIZE: usize = 16;
+const CORNER_SIZE: usize = 33;
+const CHECKSUM_SIZE: usize = 32;
+
+/// Numbers the temporary files of this process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Directory of cached tangent spaces, see `TangentCache::generate`.
+#[derive(Clone, Debug)]
+pub struct TangentCache {
+    dir: PathBuf,
+}
+
+/// SHA-256 of the inputs of one generation, displayed as hex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey(pub [u8; 32]);
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `TangentCache::generate` found the result in the cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheStatus {
+    Hit,
+    Miss,
+}
+
+#[derive(Debug)]
+pub enum CacheError {
+    Gen(GenError),
+    /// Writing a new entry failed. The tangent spaces were generated and
+    /// passed to the interface anyway.
+    Io(io::Error),
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CacheError::Gen(error) => error.fmt(f),
+            CacheError::Io(error) => write!(f, "writing the tangent cache failed: {}", error),
+        }
+    }
+}
+
+impl Error for CacheError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CacheError::Gen(error) => Some(error),
+            CacheError::Io(error) => Some(error),
+        }
+    }
+}
+
+impl From<GenError> for CacheError {
+    fn from(error: GenError) -> Self {
+        CacheError::Gen(error)
+    }
+}
+
+impl From<io::Error> for CacheError {
+    fn from(error: io::Error) -> Self {
+        CacheError::Io(error)
+    }
+}
+
+impl TangentCache {
+    /// Uses `dir` as the store, creating it if needed.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(TangentCache { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Hashes the inputs `gen_tang_space_ex` would read from `mesh`.
+    pub fn key<I>(mesh: &I, options: &GenOptions) -> CacheKey
+    where
+        I: MikkTSpaceInterface + ?Sized,
+    {
+        let mut hasher = Sha256::new();
+        hasher.update(MAGIC);
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.update([0]);
+        hasher.update(env!("MIKKTSPACE_C_HASH").as_bytes());
+        hasher.update(FORMAT_VERSION.to_le_bytes());
+
+        let num_faces = mesh.get_num_faces();
+        hasher.update((num_faces as u64).to_le_bytes());
+        for face in 0..num_faces {
+            let num_verts = mesh.get_num_vertices_of_face(face);
+            hasher.update((num_verts as u64).to_le_bytes());
+            if num_verts != 3 && num_verts != 4 {
+                continue;
+            }
+            hasher.update(mesh.get_smoothing_group(face).to_le_bytes());
+            for vert in 0..num_verts {
+                for x in mesh.get_position(face, vert) {
+                    hasher.update(x.to_bits().to_le_bytes());
+                }
+                for x in mesh.get_normal(face, vert) {
+                    hasher.update(x.to_bits().to_le_bytes());
+                }
+                for x in mesh.get_tex_coord(face, vert) {
+                    hasher.update(x.to_bits().to_le_bytes());
+                }
+                hasher.update([mesh.is_edge_sharp(face, vert) as u8]);
+            }
+        }
+
+        hasher.update(options.angular_threshold.to_bits().to_le_bytes());
+        hasher.update([options.uniform_orientation as u8]);
+        hasher.update([match options.fallback {
+            FallbackPolicy::Reference => 0,
+            FallbackPolicy::NormalBasis => 1,
+        }]);
+        CacheKey(hasher.finalize().into())
+    }
+
+    /// Path of the entry for `key`.
+    pub fn path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!("{}.tsp", key))
+    }
+
+    /// Generates tangent spaces like `gen_tang_space_ex`, reading them from
+    /// the cache if they are there and storing them otherwise.
+    ///
+    /// On a hit and on a miss `interface` receives the same calls in the same
+    /// order as from `gen_tang_space_ex`: `report_fallback` for fallback
+    /// corners, then `set_tspace` and `set_tspace_basic` for every corner of
+    /// every triangle and quad in face order. A hit doesn't run the
+    /// generator though, so `report_progress` isn't called and no `tracing`
+    /// spans are emitted. `CacheError::Io` is only returned after those calls,
+    /// when storing a new entry fails.
+    pub fn generate<I>(
+        &self,
+        interface: &mut I,
+        options: &GenOptions,
+    ) -> Result<CacheStatus, CacheError>
+    where
+        I: MikkTSpaceInterface,
+    {
+        let key = Self::key(interface, options);
+        let path = self.path(&key);
+        let num_corners = (0..interface.get_num_faces())
+            .map(|face| interface.get_num_vertices_of_face(face))
+            .sum();
+        if let Some(corners) = fs::read(&path)
+            .ok()
+            .and_then(|bytes| decode(&bytes, num_corners))
+        {
+            replay(interface, &corners);
+            return Ok(CacheStatus::Hit);
+        }
+
+        let tspaces = gen_tang_space_collect(interface, options)?;
+        replay(interface, tspaces.corners());
+        let temp = self.dir.join(format!(
+            "{}.{}.{}.tmp",
+            key,
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let written =
+            fs::write(&temp, encode(tspaces.corners())).and_then(|()| fs::rename(&temp, &path));
+        if let Err(error) = written {
+            let _ = fs::remove_file(&temp);
+            return Err(CacheError::Io(error));
+        }
+        Ok(CacheStatus::Miss)
+    }
+}
+
+fn encode(corners: &[TSpace]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + corners.len() * CORNER_SIZE + CHECKSUM_SIZE);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(corners.len() as u64).to_le_bytes());
+    for corner in corners {
+        let values = corner.tangent.iter().chain(&corner.bi_tangent);
+        for x in values.chain(&[corner.mag_s, corner.mag_t]) {
+            bytes.extend_from_slice(&x.to_le_bytes());
+        }
+        bytes.push(corner.is_orientation_preserving as u8 | (corner.is_fallback as u8) << 1);
+    }
+    let checksum = Sha256::digest(&bytes);
+    bytes.extend_from_slice(&checksum);
+    bytes
+}
+
+/// `None` unless `bytes` is a complete and intact entry of `num_corners`
+/// corners.
+fn decode(bytes: &[u8], num_corners: usize) -> Option<Vec<TSpace>> {
+    let payload_size = HEADER_SIZE + num_corners * CORNER_SIZE;
+    if bytes.len() != payload_size + CHECKSUM_SIZE
+        || &bytes[..4] != MAGIC
+        || bytes[4..8] != FORMAT_VERSION.to_le_bytes()
+        || bytes[8..16] != (num_corners as u64).to_le_bytes()
+    {
+        return None;
+    }
+    let (payload, checksum) = bytes.split_at(payload_size);
+    if Sha256::digest(payload).as_slice() != checksum {
+        return None;
+    }
+    let corners = payload[HEADER_SIZE..]
+        .chunks_exact(CORNER_SIZE)
+        .map(|chunk| {
+            let f = |i: usize| {
+                f32::from_le_bytes([
+                    chunk[4 * i],
+                    chunk[4 * i + 1],
+                    chunk[4 * i + 2],
+                    chunk[4 * i + 3],
+                ])
+            };
+            TSpace {
+                tangent: [f(0), f(1), f(2)],
+                bi_tangent: [f(3), f(4), f(5)],
+                mag_s: f(6),
+                mag_t: f(7),
+                is_orientation_preserving: chunk[32] & 1 != 0,
+                is_fallback: chunk[32] & 2 != 0,
+            }
+        })
+        .collect();
+    Some(corners)
+}
+
+/// Calls `interface` like the C code does for `corners`, given in face order.
+fn replay<I: MikkTSpaceInterface>(interface: &mut I, corners: &[TSpace]) {
+    let mut index = 0;
+    for face in 0..interface.get_num_faces() {
+        let num_verts = interface.get_num_vertices_of_face(face);
+        if num_verts != 3 && num_verts != 4 {
+            index += num_verts;
+            continue;
+        }
+        for vert in 0..num_verts {
+            let corner = &corners[index];
+            index += 1;
+            if corner.is_fallback {
+                interface.report_fallback(face, vert);
+            }
+            interface.set_tspace(
+                corner.tangent,
+                corner.bi_tangent,
+                corner.mag_s,
+                corner.mag_t,
+                corner.is_orientation_preserving,
+                face,
+                vert,
+            );
+            let sign = if corner.is_orientation_preserving {
+                1.0
+            } else {
+                -1.0
+            };
+            interface.set_tspace_basic(corner.tangent, sign, face, vert);
+        }
+    }
+}
@@ -0,0 +1,476 @@
+//! Generation in chunks for meshes too large to process at once.
+//!
+//! Faces are sorted into slabs along the longest axis of the mesh by their
+//! centroid, and every slab is generated on its own together with the faces
+//! its results depend on. The tangent space of a corner only depends on the
+//! faces around its welded vertex, i.e. the corners with identical position,
+//! normal and texture coordinates, with one exception: triangles without
+//! usable texture derivatives take the orientation of the first group that
+//! reaches them, which depends on the faces around all of their vertices. The
+//! overlap follows such triangles until it is closed. Faces keep their
+//! relative order, so every owned corner gets the same bits as in a single
+//! run over the whole mesh.
+//!
+//! Besides the interface, the faces sorted by centroid and by their range
+//! along the axis stay in memory, 20 bytes per face. Finding the overlap of a
+//! chunk needs hash sets of its faces and welded vertices, which like the
+//! allocations of the C code grow with the chunk and its overlap. The overlap
+//! has no bound of its own: where every face lacks usable derivatives, e.g.
+//! with all texture coordinates equal, it reaches every connected face and
+//! each chunk runs on the whole mesh. `ChunkOptions::max_overlap` turns that
+//! into an error.
+
+use std::collections::HashSet;
+
+use super::{gen_tang_space_ex, GenError, GenOptions, MikkTSpaceInterface};
+
+/// Options for `gen_tang_space_chunked`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkOptions {
+    /// Number of faces a chunk gets results for. The generator additionally
+    /// runs on the overlap with the neighboring chunks.
+    pub max_faces: usize,
+    /// Maximum number of faces besides its own a chunk may run on. Without
+    /// a limit the overlap can reach the whole mesh, see
+    /// `gen_tang_space_chunked`.
+    pub max_overlap: Option<usize>,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        ChunkOptions {
+            max_faces: 1 << 20,
+            max_overlap: None,
+        }
+    }
+}
+
+/// Information about a successful chunked generation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkStats {
+    pub chunks: usize,
+    /// Largest number of faces, including the overlap, the generator ran on.
+    /// At most `max_faces` plus `max_overlap`, and up to the whole mesh
+    /// without an overlap limit.
+    pub max_chunk_faces: usize,
+    /// Largest `GenStats::peak_memory` of any chunk.
+    pub peak_memory: usize,
+}
+
+/// Generates tangent spaces like `gen_tang_space_ex`, but in chunks of at
+/// most `chunk.max_faces` faces plus their overlap.
+///
+/// The overlap is every face the results of a chunk depend on. Usually that
+/// is a thin band around the chunk, but faces without usable texture
+/// derivatives chain up their neighbors, and in the worst case, e.g. with
+/// all texture coordinates equal, every chunk runs on every connected face.
+/// Memory and time then grow with the mesh and the number of chunks. With
+/// `chunk.max_overlap` set, such a chunk fails with
+/// `GenError::OverlapLimitExceeded` instead, before the generator runs on it.
+///
+/// Every corner of every triangle and quad gets exactly the same results as
+/// with `gen_tang_space_ex`, but the `set_tspace` calls are grouped by chunk
+/// instead of being in face order. `GenOptions::memory_limit` applies to
+/// every chunk on its own; when a chunk fails, the chunks before it have
+/// already been written. `report_progress` isn't called.
+///
+/// # Panics
+///
+/// If `chunk.max_faces` is zero or the mesh has more than `u32::MAX` faces.
+pub fn gen_tang_space_chunked<I>(
+    interface: &mut I,
+    options: &GenOptions,
+    chunk: &ChunkOptions,
+) -> Result<ChunkStats, GenError>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    assert!(chunk.max_faces > 0);
+    let num_faces = interface.get_num_faces();
+    assert!(num_faces <= u32::MAX as usize);
+    let slabs = Slabs::new(&*interface);
+
+    let mut stats = ChunkStats::default();
+    for owned in slabs.chunks(chunk.max_faces) {
+        let faces = slabs.overlap(&*interface, &owned, chunk.max_overlap)?;
+        let owned: HashSet<u32> = owned.iter().copied().collect();
+        let mut sub = Chunk {
+            owned: faces.iter().map(|face| owned.contains(face)).collect(),
+            faces,
+            interface: &mut *interface,
+        };
+        let chunk_stats = gen_tang_space_ex(&mut sub, options)?;
+        stats.chunks += 1;
+        stats.max_chunk_faces = stats.max_chunk_faces.max(sub.faces.len());
+        stats.peak_memory = stats.peak_memory.max(chunk_stats.peak_memory);
+    }
+    Ok(stats)
+}
+
+fn is_supported<I: MikkTSpaceInterface + ?Sized>(interface: &I, face: usize) -> bool {
+    let verts = interface.get_num_vertices_of_face(face);
+    verts == 3 || verts == 4
+}
+
+/// Bits of the attributes the reference implementation welds on. `-0.0`
+/// welds with `0.0`, so both get the same key.
+type WeldKey = [u32; 8];
+
+fn weld_key<I: MikkTSpaceInterface + ?Sized>(interface: &I, face: usize, vert: usize) -> WeldKey {
+    let p = interface.get_position(face, vert);
+    let n = interface.get_normal(face, vert);
+    let t = interface.get_tex_coord(face, vert);
+    let mut key = [0; 8];
+    for (key, value) in key.iter_mut().zip(p.iter().chain(&n).chain(&t)) {
+        *key = (value + 0.0).to_bits();
+    }
+    key
+}
+
+/// Whether any triangle the reference implementation could split `face`
+/// into might lack usable texture derivatives (`GROUP_WITH_ANY`). Errs on
+/// the side of `true`, which only grows the overlap.
+fn maybe_without_derivatives<I>(interface: &I, face: usize) -> bool
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let verts = interface.get_num_vertices_of_face(face);
+    let triangles: &[[usize; 3]] = if verts == 3 {
+        &[[0, 1, 2]]
+    } else {
+        &[[0, 1, 2], [0, 2, 3], [0, 1, 3], [1, 2, 3]]
+    };
+    let position = |vert| interface.get_position(face, vert).map(f64::from);
+    let tex_coord = |vert| interface.get_tex_coord(face, vert).map(f64::from);
+    let tiny =
+        |value: f64, scale: f64| !value.is_finite() || value <= 1e-30 || value <= 1e-5 * scale;
+    triangles.iter().any(|&[a, b, c]| {
+        let (v1, v2, v3) = (position(a), position(b), position(c));
+        let (t1, t2, t3) = (tex_coord(a), tex_coord(b), tex_coord(c));
+        let (t21x, t21y) = (t2[0] - t1[0], t2[1] - t1[1]);
+        let (t31x, t31y) = (t3[0] - t1[0], t3[1] - t1[1]);
+        let length = |v: [f64; 3]| (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+        let d1 = [v2[0] - v1[0], v2[1] - v1[1], v2[2] - v1[2]];
+        let d2 = [v3[0] - v1[0], v3[1] - v1[1], v3[2] - v1[2]];
+        let os = [0, 1, 2].map(|i| t31y * d1[i] - t21y * d2[i]);
+        let ot = [0, 1, 2].map(|i| -t31x * d1[i] + t21x * d2[i]);
+        let area = (t21x * t31y - t21y * t31x).abs();
+        tiny(area, (t21x * t31y).abs() + (t21y * t31x).abs())
+            || tiny(
+                length(os),
+                t31y.abs() * length(d1) + t21y.abs() * length(d2),
+            )
+            || tiny(
+                length(ot),
+                t31x.abs() * length(d1) + t21x.abs() * length(d2),
+            )
+            || tiny(length(os) / area, 0.0)
+            || tiny(length(ot) / area, 0.0)
+    })
+}
+
+/// Triangles and quads sorted by their centroid along one axis.
+struct Slabs {
+    axis: usize,
+    /// Centroid coordinate and face, sorted.
+    sorted: Vec<(f32, u32)>,
+    /// The faces of `sorted` by their extent along `axis`.
+    extents: Extents,
+    /// Faces with a non-finite coordinate, which are always considered.
+    unsorted: Vec<u32>,
+}
+
+impl Slabs {
+    fn new<I: MikkTSpaceInterface + ?Sized>(interface: &I) -> Self {
+        let num_faces = interface.get_num_faces();
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for face in 0..num_faces {
+            if !is_supported(interface, face) {
+                continue;
+            }
+            for vert in 0..interface.get_num_vertices_of_face(face) {
+                let p = interface.get_position(face, vert);
+                for i in 0..3 {
+                    if p[i].is_finite() {
+                        min[i] = min[i].min(p[i]);
+                        max[i] = max[i].max(p[i]);
+                    }
+                }
+            }
+        }
+        let extent = |i: usize| f64::from(max[i]) - f64::from(min[i]);
+        let axis = (0..3)
+            .max_by(|&a, &b| extent(a).total_cmp(&extent(b)))
+            .unwrap();
+
+        let mut slabs = Slabs {
+            axis,
+            sorted: Vec::new(),
+            extents: Extents::default(),
+            unsorted: Vec::new(),
+        };
+        for face in 0..num_faces {
+            if !is_supported(interface, face) {
+                continue;
+            }
+            let verts = interface.get_num_vertices_of_face(face);
+            let coordinate = |vert| interface.get_position(face, vert)[axis];
+            if !(0..verts).all(|vert| coordinate(vert).is_finite()) {
+                slabs.unsorted.push(face as u32);
+                continue;
+            }
+            let sum = (0..verts)
+                .map(|vert| f64::from(coordinate(vert)))
+                .sum::<f64>();
+            slabs
+                .sorted
+                .push(((sum / verts as f64) as f32, face as u32));
+            let min = (0..verts).map(coordinate).fold(f32::INFINITY, f32::min);
+            let max = (0..verts).map(coordinate).fold(f32::NEG_INFINITY, f32::max);
+            slabs.extents.insert(min, max, face as u32);
+        }
+        slabs
+            .sorted
+            .sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        slabs.extents.sort();
+        slabs
+    }
+
+    /// The faces of every chunk.
+    fn chunks(&self, max_faces: usize) -> impl Iterator<Item = Vec<u32>> + '_ {
+        let sorted = self.sorted.chunks(max_faces);
+        sorted
+            .map(|chunk| chunk.iter().map(|&(_, face)| face).collect())
+            .chain(self.unsorted.chunks(max_faces).map(|chunk| chunk.to_vec()))
+    }
+
+    /// `owned` and every face its results depend on, in face order. Stops as
+    /// soon as more than `max_overlap` faces were added to `owned`.
+    fn overlap<I: MikkTSpaceInterface + ?Sized>(
+        &self,
+        interface: &I,
+        owned: &[u32],
+        max_overlap: Option<usize>,
+    ) -> Result<Vec<u32>, GenError> {
+        let mut vertices = Vertices {
+            keys: HashSet::new(),
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        };
+        let mut included: HashSet<u32> = HashSet::new();
+        for &face in owned {
+            included.insert(face);
+            vertices.add(interface, face as usize, self.axis);
+        }
+
+        let mut grown = true;
+        while grown {
+            grown = false;
+            let mut candidates = Vec::new();
+            self.extents
+                .overlapping(vertices.min, vertices.max, &mut candidates);
+            candidates.extend_from_slice(&self.unsorted);
+            for face in candidates {
+                if included.contains(&face) {
+                    continue;
+                }
+                let touches = (0..interface.get_num_vertices_of_face(face as usize)).any(|vert| {
+                    vertices
+                        .keys
+                        .contains(&weld_key(interface, face as usize, vert))
+                });
+                if touches {
+                    included.insert(face);
+                    if let Some(limit) = max_overlap {
+                        if included.len() - owned.len() > limit {
+                            return Err(GenError::OverlapLimitExceeded { limit });
+                        }
+                    }
+                    if maybe_without_derivatives(interface, face as usize) {
+                        grown |= vertices.add(interface, face as usize, self.axis);
+                    }
+                }
+            }
+        }
+        let mut faces: Vec<u32> = included.into_iter().collect();
+        faces.sort_unstable();
+        Ok(faces)
+    }
+}
+
+/// Faces by their range along the axis of the slabs, bucketed by the length
+/// of that range so that a few long faces don't widen the search for all
+/// others.
+#[derive(Default)]
+struct Extents {
+    buckets: Vec<Bucket>,
+}
+
+/// Faces whose range is at most `reach` long, sorted by its start.
+struct Bucket {
+    /// Rounded up power of two length of the ranges.
+    class: i32,
+    reach: f64,
+    /// Start, end and face.
+    faces: Vec<(f32, f32, u32)>,
+}
+
+impl Extents {
+    fn insert(&mut self, min: f32, max: f32, face: u32) {
+        let length = f64::from(max) - f64::from(min);
+        let class = length.log2().ceil() as i32;
+        let bucket = match self.buckets.iter().position(|bucket| bucket.class == class) {
+            Some(bucket) => &mut self.buckets[bucket],
+            None => {
+                self.buckets.push(Bucket {
+                    class,
+                    reach: 0.0,
+                    faces: Vec::new(),
+                });
+                self.buckets.last_mut().unwrap()
+            }
+        };
+        bucket.reach = bucket.reach.max(length);
+        bucket.faces.push((min, max, face));
+    }
+
+    fn sort(&mut self) {
+        for bucket in &mut self.buckets {
+            bucket
+                .faces
+                .sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(a.2.cmp(&b.2)));
+            // Guards against rounding in the comparisons of `overlapping`.
+            bucket.reach = bucket.reach * (1.0 + 1e-6) + 1e-30;
+        }
+    }
+
+    /// Appends the faces whose range intersects `lo..=hi` to `faces`.
+    fn overlapping(&self, lo: f64, hi: f64, faces: &mut Vec<u32>) {
+        for bucket in &self.buckets {
+            let start = bucket
+                .faces
+                .partition_point(|&(min, _, _)| f64::from(min) < lo - bucket.reach);
+            let end = bucket
+                .faces
+                .partition_point(|&(min, _, _)| f64::from(min) <= hi);
+            faces.extend(
+                bucket.faces[start..end.max(start)]
+                    .iter()
+                    .filter(|&&(_, max, _)| f64::from(max) >= lo)
+                    .map(|&(_, _, face)| face),
+            );
+        }
+    }
+}
+
+/// Welded vertices whose faces are part of a chunk, and their range along
+/// the axis of the slabs.
+struct Vertices {
+    keys: HashSet<WeldKey>,
+    min: f64,
+    max: f64,
+}
+
+impl Vertices {
+    /// Adds the vertices of `face`, returning whether any was new.
+    fn add<I: MikkTSpaceInterface + ?Sized>(
+        &mut self,
+        interface: &I,
+        face: usize,
+        axis: usize,
+    ) -> bool {
+        let mut added = false;
+        for vert in 0..interface.get_num_vertices_of_face(face) {
+            if self.keys.insert(weld_key(interface, face, vert)) {
+                let coordinate = f64::from(interface.get_position(face, vert)[axis]);
+                if coordinate.is_finite() {
+                    self.min = self.min.min(coordinate);
+                    self.max = self.max.max(coordinate);
+                }
+                added = true;
+            }
+        }
+        added
+    }
+}
+
+/// The faces of one chunk, forwarding the results of `owned` faces.
+struct Chunk<'a, I: ?Sized> {
+    faces: Vec<u32>,
+    owned: Vec<bool>,
+    interface: &'a mut I,
+}
+
+impl<'a, I: MikkTSpaceInterface + ?Sized> MikkTSpaceInterface for Chunk<'a, I> {
+    fn get_num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.interface
+            .get_num_vertices_of_face(self.faces[face] as usize)
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.interface.get_position(self.faces[face] as usize, vert)
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.interface.get_normal(self.faces[face] as usize, vert)
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.interface
+            .get_tex_coord(self.faces[face] as usize, vert)
+    }
+
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        self.interface
+            .get_smoothing_group(self.faces[face] as usize)
+    }
+
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        self.interface
+            .is_edge_sharp(self.faces[face] as usize, vert)
+    }
+
+    fn report_fallback(&mut self, face: usize, vert: usize) {
+        if self.owned[face] {
+            self.interface
+                .report_fallback(self.faces[face] as usize, vert);
+        }
+    }
+
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {
+        if self.owned[face] {
+            self.interface
+                .set_tspace_basic(tangent, sign, self.faces[face] as usize, vert);
+        }
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        if self.owned[face] {
+            self.interface.set_tspace(
+                tangent,
+                bi_tangent,
+                mag_s,
+                mag_t,
+                is_orientation_preserving,
+                self.faces[face] as usize,
+                vert,
+            );
+        }
+    }
+}
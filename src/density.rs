@@ -0,0 +1,194 @@
+//! Texel density and stretch derived from `mag_s` and `mag_t`.
+//!
+//! `mag_s` and `mag_t` are the lengths of the derivatives of the position
+//! with respect to the texture coordinates, in object space units per UV
+//! unit. Their reciprocals are the number of UV units per object space unit
+//! along the two texture axes, and their ratio is how much the texture is
+//! stretched along one axis compared to the other.
+//!
+//! Per face values come from the derivatives of the face itself, computed
+//! per triangle like the reference implementation does before it averages
+//! them around every vertex into what `set_tspace` returns. Quads are split
+//! into the same triangles as in the generator, along the shorter texture
+//! space diagonal, which isn't always the split of `triangulate`. Their two
+//! triangles are weighted by object space area. Per island and overall
+//! values weight faces by their object space area. Islands are the ones of
+//! `uv_seam_report`.
+
+use super::normal_map::{cross, dot, sub};
+use super::seams::face_islands;
+use super::triangulate::generator_triangles;
+use super::MikkTSpaceInterface;
+
+/// Density and stretch of one face.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaceDensity {
+    /// Length of the derivative of the position with respect to `s`.
+    pub mag_s: f32,
+    /// Length of the derivative of the position with respect to `t`.
+    pub mag_t: f32,
+    /// UV units per object space unit, `1 / sqrt(mag_s * mag_t)`. Multiply
+    /// by the texture resolution for texels per object space unit.
+    pub density: f32,
+    /// `max(mag_s, mag_t) / min(mag_s, mag_t)`, 1 for uniform scaling.
+    pub stretch: f32,
+    /// Object space area.
+    pub area: f32,
+}
+
+/// Minimum, maximum, area weighted mean and standard deviation of a metric.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub std_dev: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IslandDensity {
+    /// Sorted face indices.
+    pub faces: Vec<usize>,
+    pub density: Statistics,
+    pub stretch: Statistics,
+}
+
+/// Result of `texel_density_report`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DensityReport {
+    /// Per face values, `None` for faces that aren't triangles or quads and
+    /// for faces with zero area in object or texture space. Quads with one
+    /// triangle of zero texture space area get the values of the other.
+    pub faces: Vec<Option<FaceDensity>>,
+    /// Islands ordered by their first face. Islands without any measurable
+    /// face are left out.
+    pub islands: Vec<IslandDensity>,
+    pub density: Statistics,
+    pub stretch: Statistics,
+}
+
+/// Derives texel density and stretch of every face of `mesh` from its
+/// surface derivatives.
+pub fn texel_density_report<I>(mesh: &I) -> DensityReport
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let islands = face_islands(mesh);
+    let faces: Vec<Option<FaceDensity>> = (0..mesh.get_num_faces())
+        .map(|face| {
+            islands[face]?;
+            face_density(mesh, face)
+        })
+        .collect();
+
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    for (face, island) in islands.iter().enumerate() {
+        if let Some(island) = *island {
+            if island == members.len() {
+                members.push(Vec::new());
+            }
+            if faces[face].is_some() {
+                members[island].push(face);
+            }
+        }
+    }
+    let measured: Vec<FaceDensity> = faces.iter().flatten().copied().collect();
+    DensityReport {
+        islands: members
+            .into_iter()
+            .filter(|faces| !faces.is_empty())
+            .map(|island| {
+                let densities: Vec<FaceDensity> =
+                    island.iter().filter_map(|&face| faces[face]).collect();
+                IslandDensity {
+                    density: Statistics::of(&densities, |d| d.density),
+                    stretch: Statistics::of(&densities, |d| d.stretch),
+                    faces: island,
+                }
+            })
+            .collect(),
+        density: Statistics::of(&measured, |d| d.density),
+        stretch: Statistics::of(&measured, |d| d.stretch),
+        faces,
+    }
+}
+
+fn face_density<I>(mesh: &I, face: usize) -> Option<FaceDensity>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let (mut area, mut mapped_area) = (0.0, 0.0);
+    let (mut mag_s, mut mag_t) = (0.0, 0.0);
+    for tri in generator_triangles(mesh, face) {
+        let p = tri.map(|vert| mesh.get_position(face, vert));
+        let uv = tri.map(|vert| mesh.get_tex_coord(face, vert));
+        let d1 = sub(p[1], p[0]);
+        let d2 = sub(p[2], p[0]);
+        let n = cross(d1, d2);
+        let tri_area = dot(n, n).sqrt() / 2.0;
+        area += tri_area;
+        // As in InitTriInfo() of mikktspace.c.
+        let (t21x, t21y) = (uv[1][0] - uv[0][0], uv[1][1] - uv[0][1]);
+        let (t31x, t31y) = (uv[2][0] - uv[0][0], uv[2][1] - uv[0][1]);
+        let signed_area_st_x2 = t21x * t31y - t21y * t31x;
+        if signed_area_st_x2 == 0.0 {
+            continue;
+        }
+        let os = [0, 1, 2].map(|i| t31y * d1[i] - t21y * d2[i]);
+        let ot = [0, 1, 2].map(|i| -t31x * d1[i] + t21x * d2[i]);
+        let abs_area = signed_area_st_x2.abs();
+        mag_s += dot(os, os).sqrt() / abs_area * tri_area;
+        mag_t += dot(ot, ot).sqrt() / abs_area * tri_area;
+        mapped_area += tri_area;
+    }
+    // Faces without texture space area have no derivatives.
+    if mapped_area == 0.0 {
+        return None;
+    }
+    mag_s /= mapped_area;
+    mag_t /= mapped_area;
+    let density = 1.0 / (mag_s * mag_t).sqrt();
+    let stretch = mag_s.max(mag_t) / mag_s.min(mag_t);
+    if area > 0.0 && density.is_finite() && stretch.is_finite() {
+        Some(FaceDensity {
+            mag_s,
+            mag_t,
+            density,
+            stretch,
+            area,
+        })
+    } else {
+        None
+    }
+}
+
+impl Statistics {
+    /// All zero for an empty slice.
+    fn of<F: Fn(&FaceDensity) -> f32>(faces: &[FaceDensity], metric: F) -> Self {
+        let total_area: f32 = faces.iter().map(|d| d.area).sum();
+        if faces.is_empty() || total_area <= 0.0 {
+            return Statistics {
+                min: 0.0,
+                max: 0.0,
+                mean: 0.0,
+                std_dev: 0.0,
+            };
+        }
+        let mean = faces.iter().map(|d| metric(d) * d.area).sum::<f32>() / total_area;
+        let variance = faces
+            .iter()
+            .map(|d| (metric(d) - mean) * (metric(d) - mean) * d.area)
+            .sum::<f32>()
+            / total_area;
+        Statistics {
+            min: faces.iter().map(&metric).fold(f32::INFINITY, f32::min),
+            max: faces.iter().map(&metric).fold(f32::NEG_INFINITY, f32::max),
+            mean,
+            std_dev: variance.sqrt(),
+        }
+    }
+}
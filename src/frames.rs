@@ -0,0 +1,158 @@
+//! Tangent frames as colored line segments, for inspection in any mesh
+//! viewer.
+//!
+//! Every corner gets three line segments, the tangent, the bitangent and the
+//! normal, starting slightly inside its face so that corners sharing a vertex
+//! stay apart. Orientation preserving corners are drawn in red, green and blue,
+//! mirrored ones in orange, cyan and purple.
+
+use std::io::{self, Write};
+
+use super::{MikkTSpaceInterface, TSpaces};
+
+/// Tangent, bitangent and normal colors of orientation preserving corners.
+const PRESERVING_COLORS: [[u8; 3]; 3] = [[230, 40, 40], [40, 200, 40], [60, 90, 255]];
+/// Tangent, bitangent and normal colors of mirrored corners.
+const MIRRORED_COLORS: [[u8; 3]; 3] = [[255, 170, 0], [0, 220, 220], [190, 60, 255]];
+
+/// One line segment from `from` to `to`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameSegment {
+    pub from: [f32; 3],
+    pub to: [f32; 3],
+    pub color: [u8; 3],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameOptions {
+    /// Length of the drawn axes relative to the mean edge length of their
+    /// face.
+    pub scale: f32,
+    /// How far the frame origins are moved from the corner towards the face
+    /// center, as a fraction of the distance.
+    pub inset: f32,
+}
+
+impl Default for FrameOptions {
+    fn default() -> Self {
+        FrameOptions {
+            scale: 0.4,
+            inset: 0.15,
+        }
+    }
+}
+
+/// Line segments of the tangent frames of every corner of `mesh`.
+///
+/// `tspaces` are the tangent spaces generated for `mesh`, e.g. by
+/// `gen_tang_space_collect`. Faces that are neither triangles nor quads get
+/// no frames.
+pub fn tangent_frames<I>(mesh: &I, tspaces: &TSpaces, options: &FrameOptions) -> Vec<FrameSegment>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let mut segments = Vec::new();
+    for face in 0..mesh.get_num_faces() {
+        let num_verts = mesh.get_num_vertices_of_face(face);
+        if num_verts != 3 && num_verts != 4 {
+            continue;
+        }
+        let mut center = [0.0; 3];
+        let mut perimeter = 0.0;
+        for vert in 0..num_verts {
+            let p = mesh.get_position(face, vert);
+            let q = mesh.get_position(face, (vert + 1) % num_verts);
+            let mut length = 0.0;
+            for i in 0..3 {
+                center[i] += p[i] / num_verts as f32;
+                length += (q[i] - p[i]) * (q[i] - p[i]);
+            }
+            perimeter += f32::sqrt(length);
+        }
+        let length = perimeter / num_verts as f32 * options.scale;
+
+        for vert in 0..num_verts {
+            let tspace = tspaces.get(face, vert);
+            let position = mesh.get_position(face, vert);
+            let mut origin = [0.0; 3];
+            for i in 0..3 {
+                origin[i] = position[i] + (center[i] - position[i]) * options.inset;
+            }
+            let colors = if tspace.is_orientation_preserving {
+                PRESERVING_COLORS
+            } else {
+                MIRRORED_COLORS
+            };
+            let normal = mesh.get_normal(face, vert);
+            for (axis, color) in [tspace.tangent, tspace.bi_tangent, normal]
+                .iter()
+                .zip(colors.iter())
+            {
+                let mut to = [0.0; 3];
+                for i in 0..3 {
+                    to[i] = origin[i] + axis[i] * length;
+                }
+                segments.push(FrameSegment {
+                    from: origin,
+                    to,
+                    color: *color,
+                });
+            }
+        }
+    }
+    segments
+}
+
+/// Writes the segments as an ASCII PLY file with colored vertices and an
+/// `edge` element.
+pub fn write_frames_ply<W: Write>(mut out: W, segments: &[FrameSegment]) -> io::Result<()> {
+    writeln!(out, "ply")?;
+    writeln!(out, "format ascii 1.0")?;
+    writeln!(out, "element vertex {}", segments.len() * 2)?;
+    writeln!(out, "property float x")?;
+    writeln!(out, "property float y")?;
+    writeln!(out, "property float z")?;
+    writeln!(out, "property uchar red")?;
+    writeln!(out, "property uchar green")?;
+    writeln!(out, "property uchar blue")?;
+    writeln!(out, "element edge {}", segments.len())?;
+    writeln!(out, "property int vertex1")?;
+    writeln!(out, "property int vertex2")?;
+    writeln!(out, "end_header")?;
+    for segment in segments {
+        for p in [segment.from, segment.to].iter() {
+            let c = segment.color;
+            writeln!(out, "{} {} {} {} {} {}", p[0], p[1], p[2], c[0], c[1], c[2])?;
+        }
+    }
+    for i in 0..segments.len() {
+        writeln!(out, "{} {}", 2 * i, 2 * i + 1)?;
+    }
+    Ok(())
+}
+
+/// Writes the segments as OBJ lines. OBJ has no colors, but most viewers
+/// accept them after the vertex position.
+pub fn write_frames_obj<W: Write>(mut out: W, segments: &[FrameSegment]) -> io::Result<()> {
+    for segment in segments {
+        for p in [segment.from, segment.to].iter() {
+            let c = segment.color;
+            writeln!(
+                out,
+                "v {} {} {} {} {} {}",
+                p[0],
+                p[1],
+                p[2],
+                c[0] as f32 / 255.0,
+                c[1] as f32 / 255.0,
+                c[2] as f32 / 255.0
+            )?;
+        }
+    }
+    for i in 0..segments.len() {
+        writeln!(out, "l {} {}", 2 * i + 1, 2 * i + 2)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,186 @@
+//! Introspection of the groups and subgroups the reference implementation
+//! builds around every vertex.
+//!
+//! Tangent spaces are computed per vertex and shared by all corners welded to
+//! it, unless `Build4RuleGroups` puts them into different groups (mirrored
+//! texture coordinates, triangles not connected around the vertex, hard edges)
+//! or `GenerateTSpaces` splits a group into subgroups at the angular
+//! threshold. `gen_tang_space_inspect` records those decisions per corner and
+//! `TSpaceInspection::explain` turns them into an answer to "why do these two
+//! corners have different tangents?".
+
+use std::cell::RefCell;
+
+use super::{
+    c_options, create_context_inspect, genTangSpaceEx, GenError, GenOptions, InterfaceWrapper,
+    MikkTSpaceInterface,
+};
+
+/// One assignment of a corner to a group, made for one of the triangles
+/// using the corner.
+///
+/// Corners of triangles get one assignment, corners shared by both triangles
+/// of a quad get one for each triangle. Their tangent space is the average of
+/// the subgroup tangent spaces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Assignment {
+    /// Group of triangles around the vertex found by `Build4RuleGroups`.
+    pub group: usize,
+    /// Distinct tangent space the group was split into by the angular
+    /// threshold. Numbered across all groups.
+    pub subgroup: usize,
+    pub is_orientation_preserving: bool,
+    /// Faces whose triangles contribute to the subgroup tangent space, sorted
+    /// and without duplicates.
+    ///
+    /// Triangles without usable texture derivatives are members of the
+    /// subgroup but add nothing to its tangent space, so they aren't listed.
+    /// The list is empty when the subgroup consists of such triangles only.
+    pub faces: Vec<usize>,
+}
+
+/// Groups and subgroups of every corner, see `gen_tang_space_inspect`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TSpaceInspection {
+    offsets: Vec<usize>,
+    corners: Vec<Vec<Assignment>>,
+}
+
+/// Why two corners received different tangent spaces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TSpaceDifference {
+    /// Both corners got exactly the same subgroups and share their tangent
+    /// space.
+    Shared,
+    /// The corners weren't welded into one vertex, because the listed
+    /// attributes differ.
+    NotWelded {
+        position: bool,
+        normal: bool,
+        tex_coord: bool,
+    },
+    /// The corner isn't part of any group. Its triangle is degenerate or has
+    /// no usable texture coordinates, so its tangent space is copied from a
+    /// neighbor or left at the fallback.
+    Ungrouped { first: bool, second: bool },
+    /// The corners are in groups of opposite orientation, typically at a
+    /// mirrored texture coordinate seam.
+    OppositeOrientation,
+    /// The corners are in different groups of the same orientation, because
+    /// their triangles aren't connected through shared edges around the
+    /// vertex, e.g. at a sharp edge or smoothing group boundary.
+    Disconnected,
+    /// The corners are in the same group which the angular threshold split
+    /// into different subgroups.
+    AngularThreshold,
+}
+
+impl TSpaceInspection {
+    fn new(interface: &dyn MikkTSpaceInterface) -> Self {
+        let mut offsets = Vec::with_capacity(interface.get_num_faces());
+        let mut num_corners = 0;
+        for face in 0..interface.get_num_faces() {
+            offsets.push(num_corners);
+            num_corners += interface.get_num_vertices_of_face(face);
+        }
+        TSpaceInspection {
+            offsets,
+            corners: vec![Vec::new(); num_corners],
+        }
+    }
+
+    pub(crate) fn record(&mut self, face: usize, vert: usize, mut assignment: Assignment) {
+        assignment.faces.sort_unstable();
+        assignment.faces.dedup();
+        self.corners[self.offsets[face] + vert].push(assignment);
+    }
+
+    /// Assignments of corner `vert` of `face`, empty for ungrouped corners.
+    pub fn corner(&self, face: usize, vert: usize) -> &[Assignment] {
+        &self.corners[self.offsets[face] + vert]
+    }
+
+    /// Explains why corner `first` and corner `second`, each given as
+    /// `(face, vert)`, received different tangent spaces. `interface` must
+    /// describe the mesh the inspection was made for.
+    pub fn explain<I>(
+        &self,
+        interface: &I,
+        first: (usize, usize),
+        second: (usize, usize),
+    ) -> TSpaceDifference
+    where
+        I: MikkTSpaceInterface + ?Sized,
+    {
+        let (f0, v0) = first;
+        let (f1, v1) = second;
+        let position = interface.get_position(f0, v0) != interface.get_position(f1, v1);
+        let normal = interface.get_normal(f0, v0) != interface.get_normal(f1, v1);
+        let tex_coord = interface.get_tex_coord(f0, v0) != interface.get_tex_coord(f1, v1);
+        if position || normal || tex_coord {
+            return TSpaceDifference::NotWelded {
+                position,
+                normal,
+                tex_coord,
+            };
+        }
+
+        let a = self.corner(f0, v0);
+        let b = self.corner(f1, v1);
+        if a.is_empty() || b.is_empty() {
+            return TSpaceDifference::Ungrouped {
+                first: a.is_empty(),
+                second: b.is_empty(),
+            };
+        }
+
+        let subgroups = |assignments: &[Assignment]| {
+            let mut ids: Vec<usize> = assignments.iter().map(|a| a.subgroup).collect();
+            ids.sort_unstable();
+            ids.dedup();
+            ids
+        };
+        if subgroups(a) == subgroups(b) {
+            return TSpaceDifference::Shared;
+        }
+        let shares_group = a.iter().any(|x| b.iter().any(|y| x.group == y.group));
+        if shares_group {
+            return TSpaceDifference::AngularThreshold;
+        }
+        let opposite = a.iter().any(|x| {
+            b.iter()
+                .any(|y| x.is_orientation_preserving != y.is_orientation_preserving)
+        });
+        if opposite {
+            TSpaceDifference::OppositeOrientation
+        } else {
+            TSpaceDifference::Disconnected
+        }
+    }
+}
+
+/// Collects the reported assignments during a generation.
+pub(crate) struct Collector(pub(crate) RefCell<TSpaceInspection>);
+
+/// Generates tangent spaces like `gen_tang_space_ex` and records the group
+/// and subgroup assignments of every corner.
+///
+/// Results are passed to `interface` as usual.
+pub fn gen_tang_space_inspect<I>(
+    interface: &mut I,
+    options: &GenOptions,
+) -> Result<TSpaceInspection, GenError>
+where
+    I: MikkTSpaceInterface,
+{
+    let collector = Collector(RefCell::new(TSpaceInspection::new(interface)));
+    let mut interface_wrapper = InterfaceWrapper::new(interface, options.memory_limit);
+    interface_wrapper.collector = Some(&collector);
+    let context = create_context_inspect(&interface_wrapper);
+    let success = unsafe { genTangSpaceEx(&context, &c_options(options)) != 0 };
+    interface_wrapper.result(success)?;
+    Ok(collector.0.into_inner())
+}
@@ -1,4 +1,56 @@
-use std::os::raw::{c_float, c_int, c_void};
+mod bake;
+#[cfg(feature = "bevy")]
+mod bevy;
+#[cfg(feature = "cache")]
+mod cache;
+mod chunked;
+mod density;
+mod frames;
+mod inspect;
+#[cfg(feature = "mmap")]
+mod mapped;
+mod normal_map;
+mod normals;
+mod ply;
+mod reindex;
+mod seams;
+mod triangulate;
+mod tspaces;
+mod typed;
+mod validate;
+
+pub use bake::{bake_normal_map, BakeError, BakeOptions};
+#[cfg(feature = "bevy")]
+pub use bevy::{generate_bevy_tangents, BevyTangentError};
+#[cfg(feature = "cache")]
+pub use cache::{CacheError, CacheKey, CacheStatus, TangentCache};
+pub use chunked::{gen_tang_space_chunked, ChunkOptions, ChunkStats};
+pub use density::{texel_density_report, DensityReport, FaceDensity, IslandDensity, Statistics};
+pub use frames::{tangent_frames, write_frames_obj, write_frames_ply, FrameOptions, FrameSegment};
+pub use inspect::{gen_tang_space_inspect, Assignment, TSpaceDifference, TSpaceInspection};
+#[cfg(feature = "mmap")]
+pub use mapped::MappedMesh;
+pub use normal_map::{object_to_tangent_space, tangent_to_object_space, NormalMap};
+pub use normals::{
+    gen_normals, gen_tang_space_normal_free, gen_tang_space_with_normals, NormalFreeInterface,
+    NormalOptions, NormalWeighting,
+};
+pub use ply::{read_ply, write_ply, PlyError, PlyFormat, PlyMesh};
+pub use reindex::{reindex, Reindexed};
+pub use seams::{uv_seam_report, SeamEdge, SeamKind, SeamReport, UvIsland};
+pub use triangulate::{check_quad_splits, split_quad, triangulate, QuadSplit, SplitMismatch};
+pub use tspaces::{gen_tang_space_collect, TSpace, TSpaces};
+pub use typed::{gen_tang_space_typed, Typed, TypedInterface, Vector2, Vector3};
+pub use validate::{gen_tang_space_validate, validate, Issue, ValidationReport, Vector};
+
+use std::alloc::{self, Layout};
+use std::cell::{Cell, RefCell};
+use std::error::Error;
+use std::fmt;
+use std::os::raw::{c_double, c_float, c_int, c_uint, c_void};
+use std::ptr;
+#[cfg(feature = "instrumentation")]
+use std::time::{Duration, Instant};
 
 #[allow(unused_variables, clippy::too_many_arguments)]
 pub trait MikkTSpaceInterface {
@@ -7,6 +59,26 @@ pub trait MikkTSpaceInterface {
     fn get_position(&self, face: usize, vert: usize) -> [f32; 3];
     fn get_normal(&self, face: usize, vert: usize) -> [f32; 3];
     fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2];
+    /// Smoothing group bit mask of `face`. Tangent spaces are never shared
+    /// across an edge between two faces whose masks have no bit in common.
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        u32::MAX
+    }
+    /// Whether the edge from `vert` to the next vertex of `face` is sharp.
+    /// Tangent spaces are never shared across sharp edges, even when the
+    /// corners on both sides weld.
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        false
+    }
+    /// Called for every corner which received a fallback tangent space, right
+    /// before its `set_tspace` calls. See `GenOptions::fallback`.
+    fn report_fallback(&mut self, face: usize, vert: usize) {}
+    /// Called periodically during generation, at most a few hundred times
+    /// per phase. Every phase ends with a call where `done` equals `total`.
+    fn report_progress(&mut self, progress: Progress) {}
+    /// Called by `gen_normals` and `gen_tang_space_with_normals` with the
+    /// generated normal of every corner.
+    fn set_normal(&mut self, normal: [f32; 3], face: usize, vert: usize) {}
     fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {}
     fn set_tspace(
         &mut self,
@@ -19,6 +91,58 @@ pub trait MikkTSpaceInterface {
         vert: usize,
     ) {
     }
+    /// Double precision position used by `gen_tang_space_f64`.
+    fn get_position_f64(&self, face: usize, vert: usize) -> [f64; 3] {
+        widen3(self.get_position(face, vert))
+    }
+    fn get_normal_f64(&self, face: usize, vert: usize) -> [f64; 3] {
+        widen3(self.get_normal(face, vert))
+    }
+    fn get_tex_coord_f64(&self, face: usize, vert: usize) -> [f64; 2] {
+        let tex_coord = self.get_tex_coord(face, vert);
+        [tex_coord[0] as f64, tex_coord[1] as f64]
+    }
+    /// Double precision result of `gen_tang_space_f64`, forwarded to
+    /// `set_tspace_basic` by default.
+    fn set_tspace_basic_f64(&mut self, tangent: [f64; 3], sign: f64, face: usize, vert: usize) {
+        self.set_tspace_basic(narrow3(tangent), sign as f32, face, vert);
+    }
+    /// Double precision result of `gen_tang_space_f64`, forwarded to
+    /// `set_tspace` by default.
+    fn set_tspace_f64(
+        &mut self,
+        tangent: [f64; 3],
+        bi_tangent: [f64; 3],
+        mag_s: f64,
+        mag_t: f64,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.set_tspace(
+            narrow3(tangent),
+            narrow3(bi_tangent),
+            mag_s as f32,
+            mag_t as f32,
+            is_orientation_preserving,
+            face,
+            vert,
+        );
+    }
+}
+
+fn widen3(v: [f32; 3]) -> [f64; 3] {
+    [v[0] as f64, v[1] as f64, v[2] as f64]
+}
+
+fn narrow3(v: [f64; 3]) -> [f32; 3] {
+    [v[0] as f32, v[1] as f32, v[2] as f32]
+}
+
+/// Access to the user data of both context flavors, used by the callbacks
+/// which don't depend on the precision.
+trait Context {
+    fn user_data(&self) -> *mut c_void;
 }
 
 #[repr(C)]
@@ -67,27 +191,354 @@ struct SMikkTSpaceInterface {
         face: c_int,
         vert: c_int,
     ),
+    get_smoothing_group: extern "C" fn(context: *const SMikkTSpaceContext, face: c_int) -> c_uint,
+    is_edge_sharp:
+        extern "C" fn(context: *const SMikkTSpaceContext, face: c_int, vert: c_int) -> c_int,
+    report_fallback: extern "C" fn(context: *const SMikkTSpaceContext, face: c_int, vert: c_int),
+    alloc: extern "C" fn(context: *const SMikkTSpaceContext, size: usize) -> *mut c_void,
+    free: extern "C" fn(context: *const SMikkTSpaceContext, ptr: *mut c_void),
+    begin_phase: Option<extern "C" fn(context: *const SMikkTSpaceContext, phase: c_int)>,
+    end_phase:
+        Option<extern "C" fn(context: *const SMikkTSpaceContext, phase: c_int, count: c_int)>,
+    report_corner: Option<
+        extern "C" fn(
+            context: *const SMikkTSpaceContext,
+            face: c_int,
+            vert: c_int,
+            group: c_int,
+            subgroup: c_int,
+            is_orientation_preserving: c_int,
+            faces: *const c_int,
+            num_faces: c_int,
+        ),
+    >,
+    report_progress: Option<
+        extern "C" fn(context: *const SMikkTSpaceContext, phase: c_int, done: c_int, total: c_int),
+    >,
+}
+
+impl Context for SMikkTSpaceContext {
+    fn user_data(&self) -> *mut c_void {
+        self.user_data
+    }
+}
+
+#[repr(C)]
+struct SMikkTSpaceContextF64 {
+    interface: *const SMikkTSpaceInterfaceF64,
+    user_data: *mut c_void,
+}
+
+impl Context for SMikkTSpaceContextF64 {
+    fn user_data(&self) -> *mut c_void {
+        self.user_data
+    }
+}
+
+#[repr(C)]
+struct SMikkTSpaceInterfaceF64 {
+    get_num_faces: extern "C" fn(context: *const SMikkTSpaceContextF64) -> c_int,
+    get_num_vertices_of_face:
+        extern "C" fn(context: *const SMikkTSpaceContextF64, face: c_int) -> c_int,
+    get_position: extern "C" fn(
+        context: *const SMikkTSpaceContextF64,
+        pos_out: *mut c_double,
+        face: c_int,
+        vert: c_int,
+    ),
+    get_normal: extern "C" fn(
+        context: *const SMikkTSpaceContextF64,
+        norm_out: *mut c_double,
+        face: c_int,
+        vert: c_int,
+    ),
+    get_tex_coord: extern "C" fn(
+        context: *const SMikkTSpaceContextF64,
+        texc_out: *mut c_double,
+        face: c_int,
+        vert: c_int,
+    ),
+    set_tspace_basic: extern "C" fn(
+        context: *const SMikkTSpaceContextF64,
+        tangent: *const c_double,
+        sign: c_double,
+        face: c_int,
+        vert: c_int,
+    ),
+    set_tspace: extern "C" fn(
+        context: *const SMikkTSpaceContextF64,
+        tangent: *const c_double,
+        bi_tangent: *const c_double,
+        mag_s: c_double,
+        mag_t: c_double,
+        is_orientation_preserving: c_int,
+        face: c_int,
+        vert: c_int,
+    ),
+    get_smoothing_group:
+        extern "C" fn(context: *const SMikkTSpaceContextF64, face: c_int) -> c_uint,
+    is_edge_sharp:
+        extern "C" fn(context: *const SMikkTSpaceContextF64, face: c_int, vert: c_int) -> c_int,
+    report_fallback: extern "C" fn(context: *const SMikkTSpaceContextF64, face: c_int, vert: c_int),
+    alloc: extern "C" fn(context: *const SMikkTSpaceContextF64, size: usize) -> *mut c_void,
+    free: extern "C" fn(context: *const SMikkTSpaceContextF64, ptr: *mut c_void),
+    begin_phase: Option<extern "C" fn(context: *const SMikkTSpaceContextF64, phase: c_int)>,
+    end_phase:
+        Option<extern "C" fn(context: *const SMikkTSpaceContextF64, phase: c_int, count: c_int)>,
+    report_corner: Option<
+        extern "C" fn(
+            context: *const SMikkTSpaceContextF64,
+            face: c_int,
+            vert: c_int,
+            group: c_int,
+            subgroup: c_int,
+            is_orientation_preserving: c_int,
+            faces: *const c_int,
+            num_faces: c_int,
+        ),
+    >,
+    report_progress: Option<
+        extern "C" fn(
+            context: *const SMikkTSpaceContextF64,
+            phase: c_int,
+            done: c_int,
+            total: c_int,
+        ),
+    >,
+}
+
+#[repr(C)]
+struct SMikkTSpaceOptions {
+    angular_threshold: c_float,
+    uniform_orientation: c_int,
+    fallback: c_int,
+    quadratic_fallback: c_int,
 }
 
 #[link(name = "mikktspace")]
 extern "C" {
     fn genTangSpaceDefault(context: *const SMikkTSpaceContext) -> c_int;
     fn genTangSpace(context: *const SMikkTSpaceContext, angular_threshold: c_float) -> c_int;
+    fn genTangSpaceEx(
+        context: *const SMikkTSpaceContext,
+        options: *const SMikkTSpaceOptions,
+    ) -> c_int;
+    fn genTangSpaceExF64(
+        context: *const SMikkTSpaceContextF64,
+        options: *const SMikkTSpaceOptions,
+    ) -> c_int;
 }
 
-extern "C" fn get_num_faces_callback(context: *const SMikkTSpaceContext) -> c_int {
+/// Options for `gen_tang_space_ex`.
+///
+/// The default options produce exactly the same results as
+/// `gen_tang_space_default`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenOptions {
+    /// Angular threshold in degrees, 180 disables the threshold.
+    pub angular_threshold: f32,
+    /// Force every corner of a face to share one orientation.
+    ///
+    /// The reference implementation lets degenerate triangles and triangles
+    /// without usable texture coordinates vary between left and right handed
+    /// at their vertices. With this set the orientation of the first corner
+    /// derived from a healthy triangle is used for the whole face.
+    pub uniform_orientation: bool,
+    /// What to return for corners no tangent space could be derived for.
+    pub fallback: FallbackPolicy,
+    /// Maximum number of bytes the C code may have allocated at once.
+    ///
+    /// Generation fails with `GenError::MemoryLimitExceeded` before any
+    /// result is written when an allocation would exceed it.
+    /// `TangentGenerator` counts the scratch buffers it keeps as well and
+    /// releases them when they are in the way.
+    pub memory_limit: Option<usize>,
+    /// Weld vertices and search neighbors with the allocation free paths of
+    /// the reference implementation when their buffers can't be allocated,
+    /// instead of failing.
+    ///
+    /// Both paths take time quadratic in the number of triangles, which can
+    /// mean hours for large meshes. `gen_tang_space` and
+    /// `gen_tang_space_default` always fall back, like the reference
+    /// implementation.
+    pub quadratic_fallback: bool,
+}
+
+impl Default for GenOptions {
+    fn default() -> Self {
+        GenOptions {
+            angular_threshold: 180.0,
+            uniform_orientation: false,
+            fallback: FallbackPolicy::Reference,
+            memory_limit: None,
+            quadratic_fallback: false,
+        }
+    }
+}
+
+/// Information about a successful generation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenStats {
+    /// Largest number of bytes held for the C code at once.
+    ///
+    /// For `TangentGenerator` this is the rounded up capacity of its scratch
+    /// buffers, including the ones kept from earlier calls.
+    pub peak_memory: usize,
+    /// Time spent and items produced by every phase, indexed by `Phase`.
+    #[cfg(feature = "instrumentation")]
+    pub phases: [PhaseStats; Phase::COUNT],
+}
+
+#[cfg(feature = "instrumentation")]
+impl GenStats {
+    pub fn phase(&self, phase: Phase) -> &PhaseStats {
+        &self.phases[phase as usize]
+    }
+}
+
+/// The phases of the reference implementation, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    /// Welding of identical corners, `GenerateSharedVerticesIndexList`.
+    /// Counts all triangles.
+    Weld,
+    /// Marking of degenerate triangles. Counts degenerate triangles.
+    Degenerates,
+    /// `InitTriInfo` and the splitting of hard edges. Counts the remaining
+    /// triangles.
+    TriInfo,
+    /// `Build4RuleGroups`. Counts active groups.
+    Groups,
+    /// `GenerateTSpaces`. Counts subgroups, i.e. distinct tangent spaces.
+    TSpaces,
+    /// `DegenEpilogue`. Counts degenerate triangles.
+    DegenEpilogue,
+}
+
+impl Phase {
+    pub const COUNT: usize = 6;
+    pub const ALL: [Phase; Phase::COUNT] = [
+        Phase::Weld,
+        Phase::Degenerates,
+        Phase::TriInfo,
+        Phase::Groups,
+        Phase::TSpaces,
+        Phase::DegenEpilogue,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Weld => "weld",
+            Phase::Degenerates => "degenerates",
+            Phase::TriInfo => "tri_info",
+            Phase::Groups => "groups",
+            Phase::TSpaces => "tspaces",
+            Phase::DegenEpilogue => "degen_epilogue",
+        }
+    }
+}
+
+#[cfg(feature = "instrumentation")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhaseStats {
+    pub duration: Duration,
+    pub count: usize,
+}
+
+/// Progress of a running generation, see
+/// `MikkTSpaceInterface::report_progress`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Progress {
+    pub phase: Phase,
+    /// Items of the phase processed so far. What an item is depends on the
+    /// phase, e.g. triangles or groups.
+    pub done: usize,
+    pub total: usize,
+}
+
+impl Progress {
+    /// Completed fraction of the current phase, 1 for empty phases.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+
+    /// Completed fraction of the whole generation, counting every phase
+    /// equally. Only suitable for progress bars, the phases differ in cost.
+    pub fn overall(&self) -> f32 {
+        (self.phase as usize as f32 + self.fraction()) / Phase::COUNT as f32
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GenError {
+    /// The reference implementation failed, because the mesh contains no
+    /// triangles or quads or because an allocation failed.
+    Failed,
+    /// An allocation of `requested` bytes would have exceeded
+    /// `GenOptions::memory_limit`.
+    MemoryLimitExceeded { limit: usize, requested: usize },
+    /// A chunk of `gen_tang_space_chunked` would have needed more than
+    /// `ChunkOptions::max_overlap` faces besides its own.
+    OverlapLimitExceeded { limit: usize },
+}
+
+impl fmt::Display for GenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GenError::Failed => write!(f, "tangent space generation failed"),
+            GenError::MemoryLimitExceeded { limit, requested } => write!(
+                f,
+                "allocating {} bytes would exceed the memory limit of {} bytes",
+                requested, limit
+            ),
+            GenError::OverlapLimitExceeded { limit } => write!(
+                f,
+                "a chunk would overlap with more than {} other faces",
+                limit
+            ),
+        }
+    }
+}
+
+impl Error for GenError {}
+
+/// Tangent spaces for corners whose texture coordinates are unusable, e.g.
+/// because every triangle around the vertex has zero UV area.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FallbackPolicy {
+    /// Keep the initialization values of the reference implementation,
+    /// tangent (1, 0, 0) and bitangent (0, 1, 0). These are generally not
+    /// perpendicular to the normal.
+    Reference,
+    /// Build an orthonormal basis around the vertex normal (Duff et al.)
+    /// and report the corner through `MikkTSpaceInterface::report_fallback`.
+    NormalBasis,
+}
+
+extern "C" fn get_num_faces_callback<C: Context>(context: *const C) -> c_int {
     unsafe {
-        let interface = &(*((*context).user_data as *const InterfaceWrapper)).interface;
+        let interface = &(*((*context).user_data() as *const InterfaceWrapper)).interface;
         interface.get_num_faces() as c_int
     }
 }
 
-extern "C" fn get_num_vertices_of_face_callback(
-    context: *const SMikkTSpaceContext,
+extern "C" fn get_num_vertices_of_face_callback<C: Context>(
+    context: *const C,
     face: c_int,
 ) -> c_int {
     unsafe {
-        let interface = &(*((*context).user_data as *const InterfaceWrapper)).interface;
+        let interface = &(*((*context).user_data() as *const InterfaceWrapper)).interface;
         interface.get_num_vertices_of_face(face as usize) as c_int
     }
 }
@@ -136,6 +587,108 @@ extern "C" fn get_tex_coord_callback(
     }
 }
 
+extern "C" fn get_smoothing_group_callback<C: Context>(context: *const C, face: c_int) -> c_uint {
+    unsafe {
+        let interface = &(*((*context).user_data() as *const InterfaceWrapper)).interface;
+        interface.get_smoothing_group(face as usize) as c_uint
+    }
+}
+
+extern "C" fn is_edge_sharp_callback<C: Context>(
+    context: *const C,
+    face: c_int,
+    vert: c_int,
+) -> c_int {
+    unsafe {
+        let interface = &(*((*context).user_data() as *const InterfaceWrapper)).interface;
+        interface.is_edge_sharp(face as usize, vert as usize) as c_int
+    }
+}
+
+extern "C" fn report_fallback_callback<C: Context>(context: *const C, face: c_int, vert: c_int) {
+    unsafe {
+        let interface = &mut (*((*context).user_data() as *mut InterfaceWrapper)).interface;
+        interface.report_fallback(face as usize, vert as usize);
+    }
+}
+
+extern "C" fn alloc_callback<C: Context>(context: *const C, size: usize) -> *mut c_void {
+    unsafe {
+        let allocator = &(*((*context).user_data() as *const InterfaceWrapper)).allocator;
+        allocator.alloc(size)
+    }
+}
+
+extern "C" fn free_callback<C: Context>(context: *const C, ptr: *mut c_void) {
+    unsafe {
+        let allocator = &(*((*context).user_data() as *const InterfaceWrapper)).allocator;
+        allocator.free(ptr);
+    }
+}
+
+#[cfg(feature = "instrumentation")]
+extern "C" fn begin_phase_callback<C: Context>(context: *const C, phase: c_int) {
+    unsafe {
+        let instrumentation =
+            &(*((*context).user_data() as *const InterfaceWrapper)).instrumentation;
+        instrumentation.begin(Phase::ALL[phase as usize]);
+    }
+}
+
+#[cfg(feature = "instrumentation")]
+extern "C" fn end_phase_callback<C: Context>(context: *const C, phase: c_int, count: c_int) {
+    unsafe {
+        let instrumentation =
+            &(*((*context).user_data() as *const InterfaceWrapper)).instrumentation;
+        instrumentation.end(Phase::ALL[phase as usize], count as usize);
+    }
+}
+
+extern "C" fn report_progress_callback<C: Context>(
+    context: *const C,
+    phase: c_int,
+    done: c_int,
+    total: c_int,
+) {
+    unsafe {
+        let interface = &mut (*((*context).user_data() as *mut InterfaceWrapper)).interface;
+        interface.report_progress(Progress {
+            phase: Phase::ALL[phase as usize],
+            done: done as usize,
+            total: total as usize,
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+extern "C" fn report_corner_callback(
+    context: *const SMikkTSpaceContext,
+    face: c_int,
+    vert: c_int,
+    group: c_int,
+    subgroup: c_int,
+    is_orientation_preserving: c_int,
+    faces: *const c_int,
+    num_faces: c_int,
+) {
+    unsafe {
+        let interface_wrapper = &*((*context).user_data as *const InterfaceWrapper);
+        if let Some(collector) = interface_wrapper.collector {
+            let faces = std::slice::from_raw_parts(faces, num_faces as usize);
+            collector.0.borrow_mut().record(
+                face as usize,
+                vert as usize,
+                Assignment {
+                    group: group as usize,
+                    subgroup: subgroup as usize,
+                    is_orientation_preserving: is_orientation_preserving != 0,
+                    faces: faces.iter().map(|&face| face as usize).collect(),
+                },
+            );
+        }
+    }
+}
+
 extern "C" fn set_tspace_basic_callback(
     context: *const SMikkTSpaceContext,
     tangent: *const c_float,
@@ -180,6 +733,94 @@ extern "C" fn set_tspace_callback(
     }
 }
 
+extern "C" fn get_position_f64_callback(
+    context: *const SMikkTSpaceContextF64,
+    pos_out: *mut c_double,
+    face: c_int,
+    vert: c_int,
+) {
+    unsafe {
+        let interface = &(*((*context).user_data as *const InterfaceWrapper)).interface;
+        let pos = interface.get_position_f64(face as usize, vert as usize);
+        *pos_out.offset(0) = pos[0];
+        *pos_out.offset(1) = pos[1];
+        *pos_out.offset(2) = pos[2];
+    }
+}
+
+extern "C" fn get_normal_f64_callback(
+    context: *const SMikkTSpaceContextF64,
+    norm_out: *mut c_double,
+    face: c_int,
+    vert: c_int,
+) {
+    unsafe {
+        let interface = &(*((*context).user_data as *const InterfaceWrapper)).interface;
+        let normal = interface.get_normal_f64(face as usize, vert as usize);
+        *norm_out.offset(0) = normal[0];
+        *norm_out.offset(1) = normal[1];
+        *norm_out.offset(2) = normal[2];
+    }
+}
+
+extern "C" fn get_tex_coord_f64_callback(
+    context: *const SMikkTSpaceContextF64,
+    texc_out: *mut c_double,
+    face: c_int,
+    vert: c_int,
+) {
+    unsafe {
+        let interface = &(*((*context).user_data as *const InterfaceWrapper)).interface;
+        let tex_coord = interface.get_tex_coord_f64(face as usize, vert as usize);
+        *texc_out.offset(0) = tex_coord[0];
+        *texc_out.offset(1) = tex_coord[1];
+    }
+}
+
+extern "C" fn set_tspace_basic_f64_callback(
+    context: *const SMikkTSpaceContextF64,
+    tangent: *const c_double,
+    sign: c_double,
+    face: c_int,
+    vert: c_int,
+) {
+    unsafe {
+        let interface = &mut (*((*context).user_data as *mut InterfaceWrapper)).interface;
+        let tangent_arr = [*tangent.offset(0), *tangent.offset(1), *tangent.offset(2)];
+        interface.set_tspace_basic_f64(tangent_arr, sign, face as usize, vert as usize);
+    }
+}
+
+extern "C" fn set_tspace_f64_callback(
+    context: *const SMikkTSpaceContextF64,
+    tangent: *const c_double,
+    bi_tangent: *const c_double,
+    mag_s: c_double,
+    mag_t: c_double,
+    is_orientation_preserving: c_int,
+    face: c_int,
+    vert: c_int,
+) {
+    unsafe {
+        let interface = &mut (*((*context).user_data as *mut InterfaceWrapper)).interface;
+        let tangent_arr = [*tangent.offset(0), *tangent.offset(1), *tangent.offset(2)];
+        let bi_tangent_arr = [
+            *bi_tangent.offset(0),
+            *bi_tangent.offset(1),
+            *bi_tangent.offset(2),
+        ];
+        interface.set_tspace_f64(
+            tangent_arr,
+            bi_tangent_arr,
+            mag_s,
+            mag_t,
+            is_orientation_preserving != 0,
+            face as usize,
+            vert as usize,
+        );
+    }
+}
+
 const MIKK_INTERFACE: SMikkTSpaceInterface = SMikkTSpaceInterface {
     get_num_faces: get_num_faces_callback,
     get_num_vertices_of_face: get_num_vertices_of_face_callback,
@@ -188,10 +829,281 @@ const MIKK_INTERFACE: SMikkTSpaceInterface = SMikkTSpaceInterface {
     get_tex_coord: get_tex_coord_callback,
     set_tspace_basic: set_tspace_basic_callback,
     set_tspace: set_tspace_callback,
+    get_smoothing_group: get_smoothing_group_callback,
+    is_edge_sharp: is_edge_sharp_callback,
+    report_fallback: report_fallback_callback,
+    alloc: alloc_callback,
+    free: free_callback,
+    #[cfg(feature = "instrumentation")]
+    begin_phase: Some(begin_phase_callback),
+    #[cfg(feature = "instrumentation")]
+    end_phase: Some(end_phase_callback),
+    #[cfg(not(feature = "instrumentation"))]
+    begin_phase: None,
+    #[cfg(not(feature = "instrumentation"))]
+    end_phase: None,
+    report_corner: None,
+    report_progress: Some(report_progress_callback),
+};
+
+const MIKK_INTERFACE_INSPECT: SMikkTSpaceInterface = SMikkTSpaceInterface {
+    report_corner: Some(report_corner_callback),
+    ..MIKK_INTERFACE
 };
 
+const MIKK_INTERFACE_F64: SMikkTSpaceInterfaceF64 = SMikkTSpaceInterfaceF64 {
+    get_num_faces: get_num_faces_callback,
+    get_num_vertices_of_face: get_num_vertices_of_face_callback,
+    get_position: get_position_f64_callback,
+    get_normal: get_normal_f64_callback,
+    get_tex_coord: get_tex_coord_f64_callback,
+    set_tspace_basic: set_tspace_basic_f64_callback,
+    set_tspace: set_tspace_f64_callback,
+    get_smoothing_group: get_smoothing_group_callback,
+    is_edge_sharp: is_edge_sharp_callback,
+    report_fallback: report_fallback_callback,
+    alloc: alloc_callback,
+    free: free_callback,
+    #[cfg(feature = "instrumentation")]
+    begin_phase: Some(begin_phase_callback),
+    #[cfg(feature = "instrumentation")]
+    end_phase: Some(end_phase_callback),
+    #[cfg(not(feature = "instrumentation"))]
+    begin_phase: None,
+    #[cfg(not(feature = "instrumentation"))]
+    end_phase: None,
+    report_corner: None,
+    report_progress: Some(report_progress_callback),
+};
+
+/// Allocations of the C code, served by the Rust global allocator.
+///
+/// Every block is prefixed with its capacity, since `free` doesn't get it.
+/// When pooling, freed blocks are kept for reuse by later allocations instead
+/// of being returned to the global allocator.
+/// The memory limit and the peak apply to the capacity of every block held,
+/// including the free ones in the pool.
+struct Allocator {
+    limit: Option<usize>,
+    held: Cell<usize>,
+    peak: Cell<usize>,
+    refused: Cell<Option<usize>>,
+    pooled: bool,
+    pool: RefCell<Vec<Block>>,
+}
+
+/// A free block owned by a pool.
+struct Block {
+    ptr: *mut u8,
+    capacity: usize,
+}
+
+/// Size of the block prefix, which also keeps the alignment malloc() gives.
+const ALLOC_HEADER: usize = 16;
+
+impl Allocator {
+    fn new(limit: Option<usize>) -> Self {
+        Allocator {
+            limit,
+            held: Cell::new(0),
+            peak: Cell::new(0),
+            refused: Cell::new(None),
+            pooled: false,
+            pool: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn with_pool(limit: Option<usize>, pool: Vec<Block>) -> Self {
+        let held = pool.iter().map(|block| block.capacity).sum();
+        Allocator {
+            held: Cell::new(held),
+            peak: Cell::new(held),
+            pooled: true,
+            pool: RefCell::new(pool),
+            ..Allocator::new(limit)
+        }
+    }
+
+    fn layout(capacity: usize) -> Option<Layout> {
+        let total = capacity.checked_add(ALLOC_HEADER)?;
+        Layout::from_size_align(total, ALLOC_HEADER).ok()
+    }
+
+    /// Takes the smallest pooled block of at least `size` bytes.
+    fn take_pooled(&self, size: usize) -> Option<Block> {
+        let mut pool = self.pool.borrow_mut();
+        let best = pool
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| block.capacity >= size)
+            .min_by_key(|(_, block)| block.capacity)
+            .map(|(i, _)| i)?;
+        Some(pool.swap_remove(best))
+    }
+
+    /// Returns a pooled block to the global allocator.
+    unsafe fn release_pooled(&self) -> bool {
+        match self.pool.borrow_mut().pop() {
+            Some(block) => {
+                self.held.set(self.held.get() - block.capacity);
+                alloc::dealloc(block.ptr, Self::layout(block.capacity).unwrap());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Picks the capacity of a new block for `size` bytes within the limit,
+    /// releasing pooled blocks to make room.
+    unsafe fn capacity(&self, size: usize) -> Option<usize> {
+        // Round pooled blocks up so they fit slightly larger meshes.
+        let rounded = if self.pooled {
+            size.checked_next_power_of_two().unwrap_or(size)
+        } else {
+            size
+        };
+        let limit = match self.limit {
+            Some(limit) => limit,
+            None => return Some(rounded),
+        };
+        let fits = |capacity: usize| self.held.get().saturating_add(capacity) <= limit;
+        while !fits(rounded) && self.release_pooled() {}
+        if fits(rounded) {
+            Some(rounded)
+        } else if fits(size) {
+            Some(size)
+        } else {
+            self.refused.set(Some(size));
+            None
+        }
+    }
+
+    unsafe fn alloc(&self, size: usize) -> *mut c_void {
+        let block = match self.take_pooled(size) {
+            Some(block) => block,
+            None => {
+                let capacity = match self.capacity(size) {
+                    Some(capacity) => capacity,
+                    None => return ptr::null_mut(),
+                };
+                let layout = match Self::layout(capacity) {
+                    Some(layout) => layout,
+                    None => return ptr::null_mut(),
+                };
+                let ptr = alloc::alloc(layout);
+                if ptr.is_null() {
+                    return ptr::null_mut();
+                }
+                self.held.set(self.held.get() + capacity);
+                self.peak.set(self.peak.get().max(self.held.get()));
+                Block { ptr, capacity }
+            }
+        };
+        (block.ptr as *mut usize).write(block.capacity);
+        block.ptr.add(ALLOC_HEADER) as *mut c_void
+    }
+
+    unsafe fn free(&self, ptr: *mut c_void) {
+        if ptr.is_null() {
+            return;
+        }
+        let block = (ptr as *mut u8).sub(ALLOC_HEADER);
+        let capacity = (block as *mut usize).read();
+        if self.pooled {
+            self.pool.borrow_mut().push(Block {
+                ptr: block,
+                capacity,
+            });
+        } else {
+            self.held.set(self.held.get() - capacity);
+            alloc::dealloc(block, Self::layout(capacity).unwrap());
+        }
+    }
+
+    fn error(&self) -> GenError {
+        match (self.limit, self.refused.get()) {
+            (Some(limit), Some(requested)) => GenError::MemoryLimitExceeded { limit, requested },
+            _ => GenError::Failed,
+        }
+    }
+}
+
+/// Phase timings, collected through the phase callbacks.
+#[cfg(feature = "instrumentation")]
+#[derive(Default)]
+struct Instrumentation {
+    phases: Cell<[PhaseStats; Phase::COUNT]>,
+    started: Cell<Option<Instant>>,
+    #[cfg(feature = "tracing")]
+    span: RefCell<Option<tracing::span::EnteredSpan>>,
+}
+
+#[cfg(feature = "instrumentation")]
+impl Instrumentation {
+    fn begin(&self, phase: Phase) {
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::debug_span!(
+                "mikktspace",
+                phase = phase.name(),
+                count = tracing::field::Empty
+            );
+            *self.span.borrow_mut() = Some(span.entered());
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = phase;
+        self.started.set(Some(Instant::now()));
+    }
+
+    fn end(&self, phase: Phase, count: usize) {
+        let mut phases = self.phases.get();
+        if let Some(started) = self.started.take() {
+            phases[phase as usize].duration += started.elapsed();
+        }
+        phases[phase as usize].count = count;
+        self.phases.set(phases);
+        #[cfg(feature = "tracing")]
+        {
+            if let Some(span) = self.span.borrow_mut().take() {
+                span.record("count", count);
+            }
+        }
+    }
+}
+
 struct InterfaceWrapper<'a> {
     interface: &'a mut dyn MikkTSpaceInterface,
+    allocator: Allocator,
+    #[cfg(feature = "instrumentation")]
+    instrumentation: Instrumentation,
+    collector: Option<&'a inspect::Collector>,
+}
+
+impl<'a> InterfaceWrapper<'a> {
+    fn new(interface: &'a mut dyn MikkTSpaceInterface, memory_limit: Option<usize>) -> Self {
+        Self::with_allocator(interface, Allocator::new(memory_limit))
+    }
+
+    fn with_allocator(interface: &'a mut dyn MikkTSpaceInterface, allocator: Allocator) -> Self {
+        InterfaceWrapper {
+            interface,
+            allocator,
+            #[cfg(feature = "instrumentation")]
+            instrumentation: Instrumentation::default(),
+            collector: None,
+        }
+    }
+
+    fn result(&self, success: bool) -> Result<GenStats, GenError> {
+        if !success {
+            return Err(self.allocator.error());
+        }
+        Ok(GenStats {
+            peak_memory: self.allocator.peak.get(),
+            #[cfg(feature = "instrumentation")]
+            phases: self.instrumentation.phases.get(),
+        })
+    }
 }
 
 fn create_context(interface_wrapper: &InterfaceWrapper) -> SMikkTSpaceContext {
@@ -201,11 +1113,37 @@ fn create_context(interface_wrapper: &InterfaceWrapper) -> SMikkTSpaceContext {
     }
 }
 
+fn create_context_inspect(interface_wrapper: &InterfaceWrapper) -> SMikkTSpaceContext {
+    SMikkTSpaceContext {
+        interface: &MIKK_INTERFACE_INSPECT as *const _,
+        user_data: interface_wrapper as *const _ as *mut _,
+    }
+}
+
+fn create_context_f64(interface_wrapper: &InterfaceWrapper) -> SMikkTSpaceContextF64 {
+    SMikkTSpaceContextF64 {
+        interface: &MIKK_INTERFACE_F64 as *const _,
+        user_data: interface_wrapper as *const _ as *mut _,
+    }
+}
+
+fn c_options(options: &GenOptions) -> SMikkTSpaceOptions {
+    SMikkTSpaceOptions {
+        angular_threshold: options.angular_threshold,
+        uniform_orientation: options.uniform_orientation as c_int,
+        fallback: match options.fallback {
+            FallbackPolicy::Reference => 0,
+            FallbackPolicy::NormalBasis => 1,
+        },
+        quadratic_fallback: options.quadratic_fallback as c_int,
+    }
+}
+
 pub fn gen_tang_space_default<I>(interface: &mut I) -> bool
 where
     I: MikkTSpaceInterface,
 {
-    let interface_wrapper = InterfaceWrapper { interface };
+    let interface_wrapper = InterfaceWrapper::new(interface, None);
     let context = create_context(&interface_wrapper);
     unsafe { genTangSpaceDefault(&context) != 0 }
 }
@@ -214,7 +1152,389 @@ pub fn gen_tang_space<I>(interface: &mut I, angular_threshold: f32) -> bool
 where
     I: MikkTSpaceInterface,
 {
-    let interface_wrapper = InterfaceWrapper { interface };
+    let interface_wrapper = InterfaceWrapper::new(interface, None);
     let context = create_context(&interface_wrapper);
     unsafe { genTangSpace(&context, angular_threshold) != 0 }
 }
+
+/// Generates tangent spaces with the given options.
+///
+/// All memory the C code needs is allocated through the Rust global
+/// allocator. Returns the peak number of bytes it held at once.
+pub fn gen_tang_space_ex<I>(interface: &mut I, options: &GenOptions) -> Result<GenStats, GenError>
+where
+    I: MikkTSpaceInterface,
+{
+    let interface_wrapper = InterfaceWrapper::new(interface, options.memory_limit);
+    let context = create_context(&interface_wrapper);
+    let success = unsafe { genTangSpaceEx(&context, &c_options(options)) != 0 };
+    interface_wrapper.result(success)
+}
+
+/// Generates tangent spaces in double precision.
+///
+/// Attributes are read through `get_position_f64`, `get_normal_f64` and
+/// `get_tex_coord_f64` and results are returned through `set_tspace_f64` and
+/// `set_tspace_basic_f64`. All of these forward to their single precision
+/// counterparts by default, so only the methods which benefit from the extra
+/// precision need to be implemented, typically `get_position_f64` for meshes
+/// in large world or georeferenced coordinates.
+///
+/// The algorithm is identical to `gen_tang_space_ex`, only evaluated in `f64`.
+/// On well-scaled meshes both paths weld and group identically and the
+/// results differ only by single precision rounding: on the unit cube of the
+/// regression test and on a bumpy 16x16 unit grid the largest difference of
+/// any tangent or bitangent component is `1.2e-7`, one unit in the last place
+/// of `f32` (see `tests/f64_test.rs`). Since results are not
+/// bit-identical, use `gen_tang_space_ex` when output must match other tools
+/// running the reference implementation.
+pub fn gen_tang_space_f64<I>(interface: &mut I, options: &GenOptions) -> Result<GenStats, GenError>
+where
+    I: MikkTSpaceInterface,
+{
+    let interface_wrapper = InterfaceWrapper::new(interface, options.memory_limit);
+    let context = create_context_f64(&interface_wrapper);
+    let success = unsafe { genTangSpaceExF64(&context, &c_options(options)) != 0 };
+    interface_wrapper.result(success)
+}
+
+/// Generates tangent spaces for many meshes in a row without per mesh heap
+/// traffic.
+///
+/// The generator keeps the buffers the C code frees and hands them out again
+/// to later calls, growing the set only when a mesh needs more than any
+/// previous one. Results are identical to `gen_tang_space_ex` and
+/// `gen_tang_space_f64` with the same options.
+pub struct TangentGenerator {
+    options: GenOptions,
+    scratch: Vec<Block>,
+}
+
+impl TangentGenerator {
+    pub fn new(options: GenOptions) -> Self {
+        TangentGenerator {
+            options,
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn options(&self) -> &GenOptions {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: GenOptions) {
+        self.options = options;
+    }
+
+    /// Number of bytes held in scratch buffers between calls.
+    pub fn scratch_capacity(&self) -> usize {
+        self.scratch.iter().map(|block| block.capacity).sum()
+    }
+
+    /// Releases all scratch buffers.
+    pub fn clear_scratch(&mut self) {
+        for block in self.scratch.drain(..) {
+            unsafe { alloc::dealloc(block.ptr, Allocator::layout(block.capacity).unwrap()) };
+        }
+    }
+
+    pub fn generate<I>(&mut self, interface: &mut I) -> Result<GenStats, GenError>
+    where
+        I: MikkTSpaceInterface,
+    {
+        let interface_wrapper = self.wrap(interface);
+        let context = create_context(&interface_wrapper);
+        let success = unsafe { genTangSpaceEx(&context, &c_options(&self.options)) != 0 };
+        self.unwrap(interface_wrapper, success)
+    }
+
+    pub fn generate_f64<I>(&mut self, interface: &mut I) -> Result<GenStats, GenError>
+    where
+        I: MikkTSpaceInterface,
+    {
+        let interface_wrapper = self.wrap(interface);
+        let context = create_context_f64(&interface_wrapper);
+        let success = unsafe { genTangSpaceExF64(&context, &c_options(&self.options)) != 0 };
+        self.unwrap(interface_wrapper, success)
+    }
+
+    fn wrap<'a>(&mut self, interface: &'a mut dyn MikkTSpaceInterface) -> InterfaceWrapper<'a> {
+        let pool = std::mem::take(&mut self.scratch);
+        InterfaceWrapper::with_allocator(
+            interface,
+            Allocator::with_pool(self.options.memory_limit, pool),
+        )
+    }
+
+    fn unwrap(
+        &mut self,
+        interface_wrapper: InterfaceWrapper,
+        success: bool,
+    ) -> Result<GenStats, GenError> {
+        let result = interface_wrapper.result(success);
+        // The C code frees everything it allocates before returning.
+        self.scratch = interface_wrapper.allocator.pool.into_inner();
+        result
+    }
+}
+
+// The scratch blocks are exclusively owned by the generator.
+unsafe impl Send for TangentGenerator {}
+
+impl Default for TangentGenerator {
+    fn default() -> Self {
+        TangentGenerator::new(GenOptions::default())
+    }
+}
+
+impl Drop for TangentGenerator {
+    fn drop(&mut self) {
+        self.clear_scratch();
+    }
+}
+
+/// Interface for meshes carrying several texture coordinate sets.
+///
+/// Tangent spaces are generated independently for every set and handed back
+/// together with the index of the set they belong to. The methods are those
+/// of `MikkTSpaceInterface` with the additional `set`.
+#[allow(unused_variables, clippy::too_many_arguments)]
+pub trait MikkTSpaceMultiInterface {
+    fn get_num_faces(&self) -> usize;
+    fn get_num_vertices_of_face(&self, face: usize) -> usize;
+    /// Number of texture coordinate sets, `set` runs from 0 to this.
+    fn get_num_tex_coord_sets(&self) -> usize;
+    /// Read once per corner and shared by all sets.
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3];
+    /// Read once per corner and shared by all sets.
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3];
+    /// Texture coordinates of the corner in `set`.
+    fn get_tex_coord(&self, set: usize, face: usize, vert: usize) -> [f32; 2];
+    /// See `MikkTSpaceInterface::get_smoothing_group`, the same for all sets.
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        u32::MAX
+    }
+    /// See `MikkTSpaceInterface::is_edge_sharp`, the same for all sets.
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        false
+    }
+    /// See `MikkTSpaceInterface::report_progress`. The sets are generated one
+    /// after another, each running through all phases.
+    fn report_progress(&mut self, set: usize, progress: Progress) {}
+    /// See `MikkTSpaceInterface::report_fallback`.
+    fn report_fallback(&mut self, set: usize, face: usize, vert: usize) {}
+    /// See `MikkTSpaceInterface::set_tspace_basic`.
+    fn set_tspace_basic(
+        &mut self,
+        set: usize,
+        tangent: [f32; 3],
+        sign: f32,
+        face: usize,
+        vert: usize,
+    ) {
+    }
+    /// See `MikkTSpaceInterface::set_tspace`.
+    fn set_tspace(
+        &mut self,
+        set: usize,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+    }
+}
+
+/// Positions and normals of all supported faces, fetched once and shared by
+/// every texture coordinate set.
+struct CornerCache {
+    num_vertices: Vec<usize>,
+    offsets: Vec<usize>,
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+}
+
+impl CornerCache {
+    fn new<I: MikkTSpaceMultiInterface + ?Sized>(interface: &I) -> Self {
+        let num_faces = interface.get_num_faces();
+        let mut cache = CornerCache {
+            num_vertices: Vec::with_capacity(num_faces),
+            offsets: Vec::with_capacity(num_faces),
+            positions: Vec::new(),
+            normals: Vec::new(),
+        };
+        for face in 0..num_faces {
+            let verts = interface.get_num_vertices_of_face(face);
+            cache.num_vertices.push(verts);
+            cache.offsets.push(cache.positions.len());
+            if verts != 3 && verts != 4 {
+                continue;
+            }
+            for vert in 0..verts {
+                cache.positions.push(interface.get_position(face, vert));
+                cache.normals.push(interface.get_normal(face, vert));
+            }
+        }
+        cache
+    }
+
+    fn tex_coords<I: MikkTSpaceMultiInterface + ?Sized>(
+        &self,
+        interface: &I,
+        set: usize,
+    ) -> Vec<[f32; 2]> {
+        let mut tex_coords = Vec::with_capacity(self.positions.len());
+        for (face, &verts) in self.num_vertices.iter().enumerate() {
+            if verts != 3 && verts != 4 {
+                continue;
+            }
+            for vert in 0..verts {
+                tex_coords.push(interface.get_tex_coord(set, face, vert));
+            }
+        }
+        tex_coords
+    }
+}
+
+struct ChannelAdapter<'a, I: ?Sized> {
+    interface: &'a mut I,
+    cache: &'a CornerCache,
+    tex_coords: Vec<[f32; 2]>,
+    set: usize,
+}
+
+impl<'a, I: MikkTSpaceMultiInterface + ?Sized> MikkTSpaceInterface for ChannelAdapter<'a, I> {
+    fn get_num_faces(&self) -> usize {
+        self.cache.num_vertices.len()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.cache.num_vertices[face]
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.cache.positions[self.cache.offsets[face] + vert]
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.cache.normals[self.cache.offsets[face] + vert]
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.tex_coords[self.cache.offsets[face] + vert]
+    }
+
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        self.interface.get_smoothing_group(face)
+    }
+
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        self.interface.is_edge_sharp(face, vert)
+    }
+
+    fn report_progress(&mut self, progress: Progress) {
+        self.interface.report_progress(self.set, progress);
+    }
+
+    fn report_fallback(&mut self, face: usize, vert: usize) {
+        self.interface.report_fallback(self.set, face, vert);
+    }
+
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {
+        self.interface
+            .set_tspace_basic(self.set, tangent, sign, face, vert);
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.interface.set_tspace(
+            self.set,
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            face,
+            vert,
+        );
+    }
+}
+
+pub fn gen_tang_space_multi_default<I>(interface: &mut I) -> bool
+where
+    I: MikkTSpaceMultiInterface,
+{
+    gen_tang_space_multi(interface, 180.0)
+}
+
+/// Generates tangent spaces for every texture coordinate set of the mesh.
+///
+/// Each set produces exactly the same results as running `gen_tang_space`
+/// on a mesh exposing just that set. Returns `false` if generation failed
+/// for any set.
+pub fn gen_tang_space_multi<I>(interface: &mut I, angular_threshold: f32) -> bool
+where
+    I: MikkTSpaceMultiInterface,
+{
+    let options = GenOptions {
+        angular_threshold,
+        quadratic_fallback: true,
+        ..GenOptions::default()
+    };
+    let cache = CornerCache::new(interface);
+    let mut ret = true;
+    for set in 0..interface.get_num_tex_coord_sets() {
+        ret &= gen_tang_space_set(interface, &cache, set, &options).is_ok();
+    }
+    ret
+}
+
+/// Generates tangent spaces for every texture coordinate set of the mesh
+/// with `options`, returning the statistics of every set.
+///
+/// Only the position and normal reads are shared between the sets. Welding
+/// compares texture coordinates, so welding and everything built on it, the
+/// degenerate triangles, the triangle derivatives and the neighbor search,
+/// run once per set. Stops at the first set that fails.
+pub fn gen_tang_space_multi_ex<I>(
+    interface: &mut I,
+    options: &GenOptions,
+) -> Result<Vec<GenStats>, GenError>
+where
+    I: MikkTSpaceMultiInterface,
+{
+    let cache = CornerCache::new(interface);
+    (0..interface.get_num_tex_coord_sets())
+        .map(|set| gen_tang_space_set(interface, &cache, set, options))
+        .collect()
+}
+
+fn gen_tang_space_set<I>(
+    interface: &mut I,
+    cache: &CornerCache,
+    set: usize,
+    options: &GenOptions,
+) -> Result<GenStats, GenError>
+where
+    I: MikkTSpaceMultiInterface,
+{
+    let tex_coords = cache.tex_coords(interface, set);
+    let mut adapter = ChannelAdapter {
+        interface,
+        cache,
+        tex_coords,
+        set,
+    };
+    gen_tang_space_ex(&mut adapter, options)
+}
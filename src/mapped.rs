@@ -0,0 +1,151 @@
+//! Meshes in memory-mapped files, for `gen_tang_space_chunked` on meshes
+//! larger than memory.
+//!
+//! All files are little endian. The vertex file holds `x y z nx ny nz s t`
+//! as 8 `f32` per vertex, the index file 3 `u32` per triangle. The tangent
+//! file is written with 4 `f32` per corner in triangle order, the tangent
+//! and the sign of the bitangent as passed to `set_tspace_basic`.
+
+use std::convert::TryInto;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+use memmap2::{Mmap, MmapMut};
+
+use super::MikkTSpaceInterface;
+
+const VERTEX_SIZE: usize = 32;
+const TRIANGLE_SIZE: usize = 12;
+const TANGENT_SIZE: usize = 16;
+
+/// A triangle mesh read from and written to memory-mapped files, see the
+/// module documentation for the layout.
+pub struct MappedMesh {
+    vertices: Mmap,
+    indices: Mmap,
+    tangents: MmapMut,
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_f32(bytes: &[u8], index: usize) -> f32 {
+    f32::from_le_bytes(bytes[index * 4..index * 4 + 4].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8], index: usize) -> u32 {
+    u32::from_le_bytes(bytes[index * 4..index * 4 + 4].try_into().unwrap())
+}
+
+impl MappedMesh {
+    /// Maps the `vertices` and `indices` files and creates, or truncates,
+    /// the `tangents` file, sized for every corner. Every index is checked
+    /// against the number of vertices.
+    ///
+    /// # Safety
+    ///
+    /// The files must not be modified or truncated by anything else while
+    /// the mesh is alive, see `memmap2::Mmap::map`.
+    pub unsafe fn open<P, Q, R>(vertices: P, indices: Q, tangents: R) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+        R: AsRef<Path>,
+    {
+        let vertices = Mmap::map(&File::open(vertices)?)?;
+        let indices = Mmap::map(&File::open(indices)?)?;
+        if vertices.len() % VERTEX_SIZE != 0 {
+            return Err(invalid_data(format!(
+                "vertex file size {} isn't a multiple of {}",
+                vertices.len(),
+                VERTEX_SIZE
+            )));
+        }
+        if indices.len() % TRIANGLE_SIZE != 0 {
+            return Err(invalid_data(format!(
+                "index file size {} isn't a multiple of {}",
+                indices.len(),
+                TRIANGLE_SIZE
+            )));
+        }
+        let num_vertices = vertices.len() / VERTEX_SIZE;
+        for i in 0..indices.len() / 4 {
+            let index = read_u32(&indices, i);
+            if index as usize >= num_vertices {
+                return Err(invalid_data(format!(
+                    "vertex index {} out of bounds for {} vertices",
+                    index, num_vertices
+                )));
+            }
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(tangents)?;
+        let num_corners = indices.len() / 4;
+        file.set_len((num_corners * TANGENT_SIZE) as u64)?;
+        let tangents = MmapMut::map_mut(&file)?;
+        Ok(MappedMesh {
+            vertices,
+            indices,
+            tangents,
+        })
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.vertices.len() / VERTEX_SIZE
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.indices.len() / TRIANGLE_SIZE
+    }
+
+    /// Writes the tangents generated so far to the file.
+    pub fn flush(&self) -> io::Result<()> {
+        self.tangents.flush()
+    }
+
+    /// Attribute `offset` of the vertex of a corner, in `f32`s.
+    fn attribute(&self, face: usize, vert: usize, offset: usize) -> f32 {
+        let vertex = read_u32(&self.indices, face * 3 + vert) as usize;
+        read_f32(&self.vertices, vertex * 8 + offset)
+    }
+}
+
+impl MikkTSpaceInterface for MappedMesh {
+    fn get_num_faces(&self) -> usize {
+        self.num_triangles()
+    }
+
+    fn get_num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        [0, 1, 2].map(|i| self.attribute(face, vert, i))
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        [3, 4, 5].map(|i| self.attribute(face, vert, i))
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        [6, 7].map(|i| self.attribute(face, vert, i))
+    }
+
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {
+        let offset = (face * 3 + vert) * TANGENT_SIZE;
+        let values = [tangent[0], tangent[1], tangent[2], sign];
+        for (bytes, value) in self.tangents[offset..offset + TANGENT_SIZE]
+            .chunks_exact_mut(4)
+            .zip(&values)
+        {
+            bytes.copy_from_slice(&value.to_le_bytes());
+        }
+    }
+}
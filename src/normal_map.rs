@@ -0,0 +1,330 @@
+//! CPU implementation of the normal map sampler described in `mikktspace.h`.
+//!
+//! Triangles are rasterized in texture space and the tangent frames generated
+//! at their corners are interpolated without normalization, exactly as a
+//! renderer's vertex to pixel shader interpolation does. Tangent space normals
+//! are transformed to object space with the fast pixel shader transform and
+//! object space normals back with its exact inverse:
+//!
+//! ```text
+//! vNout = normalize( vNt.x * vT + vNt.y * vB + vNt.z * vN );
+//!
+//! row0 = cross(vB, vN); row1 = cross(vN, vT); row2 = cross(vT, vB);
+//! fSign = dot(vT, row0) < 0 ? -1 : 1;
+//! vNt = normalize( fSign * float3(dot(vNout,row0), dot(vNout,row1), dot(vNout,row2)) );
+//! ```
+//!
+//! The bitangent is reconstructed per vertex as `sign * cross(vN, vT)` before
+//! interpolation. Texel `(x, y)` is sampled at texture coordinate
+//! `((x + 0.5) / width, (y + 0.5) / height)`, so row 0 of the map is at
+//! `v = 0`. Flip the rows for images stored with `v = 0` at the bottom.
+
+use super::triangulate::face_triangles;
+use super::tspaces::{gen_tang_space_collect, TSpaces};
+use super::{GenError, GenOptions, MikkTSpaceInterface};
+
+/// A map of unit length normals, one per texel, stored row by row.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NormalMap {
+    pub width: usize,
+    pub height: usize,
+    pub texels: Vec<[f32; 3]>,
+}
+
+impl NormalMap {
+    /// A map with every texel set to `normal`.
+    pub fn new(width: usize, height: usize, normal: [f32; 3]) -> Self {
+        NormalMap {
+            width,
+            height,
+            texels: vec![normal; width * height],
+        }
+    }
+
+    /// Decodes 8 bit RGB texels, mapping `0..=255` to `-1.0..=1.0`.
+    pub fn from_rgb8(width: usize, height: usize, rgb: &[u8]) -> Self {
+        assert_eq!(rgb.len(), width * height * 3);
+        let decode = |c: u8| c as f32 / 255.0 * 2.0 - 1.0;
+        NormalMap {
+            width,
+            height,
+            texels: rgb
+                .chunks(3)
+                .map(|c| normalize([decode(c[0]), decode(c[1]), decode(c[2])]))
+                .collect(),
+        }
+    }
+
+    /// Encodes the texels as 8 bit RGB.
+    pub fn to_rgb8(&self) -> Vec<u8> {
+        let encode = |x: f32| ((x * 0.5 + 0.5) * 255.0).round().clamp(0.0, 255.0) as u8;
+        self.texels
+            .iter()
+            .flat_map(|n| n.iter().map(|&x| encode(x)))
+            .collect()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> [f32; 3] {
+        self.texels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, normal: [f32; 3]) {
+        self.texels[y * self.width + x] = normal;
+    }
+}
+
+/// Converts an object space normal map into a tangent space one matching the
+/// tangent spaces `gen_tang_space_ex` generates for `mesh` with `options`.
+///
+/// Texels not covered by any triangle keep their value.
+pub fn object_to_tangent_space<I>(
+    mesh: &mut I,
+    options: &GenOptions,
+    map: &NormalMap,
+) -> Result<NormalMap, GenError>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let frames = CornerFrames::generate(mesh, options)?;
+    let mut out = map.clone();
+    for_each_texel(mesh, &frames, map.width, map.height, |x, y, frame, _| {
+        out.set(x, y, frame.object_to_tangent(map.get(x, y)));
+    });
+    Ok(out)
+}
+
+/// Converts a tangent space normal map into an object space one, the inverse
+/// of `object_to_tangent_space`.
+///
+/// Texels not covered by any triangle keep their value.
+pub fn tangent_to_object_space<I>(
+    mesh: &mut I,
+    options: &GenOptions,
+    map: &NormalMap,
+) -> Result<NormalMap, GenError>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let frames = CornerFrames::generate(mesh, options)?;
+    let mut out = map.clone();
+    for_each_texel(mesh, &frames, map.width, map.height, |x, y, frame, _| {
+        out.set(x, y, frame.tangent_to_object(map.get(x, y)));
+    });
+    Ok(out)
+}
+
+/// Unnormalized interpolated tangent frame at a texel.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Frame {
+    pub(crate) tangent: [f32; 3],
+    pub(crate) bi_tangent: [f32; 3],
+    pub(crate) normal: [f32; 3],
+}
+
+impl Frame {
+    /// The pixel shader transform.
+    pub(crate) fn tangent_to_object(&self, n: [f32; 3]) -> [f32; 3] {
+        let (t, b, v) = (self.tangent, self.bi_tangent, self.normal);
+        normalize([
+            n[0] * t[0] + n[1] * b[0] + n[2] * v[0],
+            n[0] * t[1] + n[1] * b[1] + n[2] * v[1],
+            n[0] * t[2] + n[1] * b[2] + n[2] * v[2],
+        ])
+    }
+
+    /// The exact inverse of the pixel shader transform.
+    pub(crate) fn object_to_tangent(&self, n: [f32; 3]) -> [f32; 3] {
+        let row0 = cross(self.bi_tangent, self.normal);
+        let row1 = cross(self.normal, self.tangent);
+        let row2 = cross(self.tangent, self.bi_tangent);
+        let sign = if dot(self.tangent, row0) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        normalize([
+            sign * dot(n, row0),
+            sign * dot(n, row1),
+            sign * dot(n, row2),
+        ])
+    }
+}
+
+/// Per corner tangent frames as a renderer would see them after the vertex
+/// shader.
+pub(crate) struct CornerFrames {
+    tspaces: TSpaces,
+    frames: Vec<Frame>,
+}
+
+impl CornerFrames {
+    pub(crate) fn generate<I>(mesh: &mut I, options: &GenOptions) -> Result<Self, GenError>
+    where
+        I: MikkTSpaceInterface + ?Sized,
+    {
+        let tspaces = gen_tang_space_collect(mesh, options)?;
+        let mut frames = Vec::with_capacity(tspaces.corners().len());
+        for face in 0..mesh.get_num_faces() {
+            for vert in 0..mesh.get_num_vertices_of_face(face) {
+                let tspace = tspaces.get(face, vert);
+                let normal = mesh.get_normal(face, vert);
+                let b = cross(normal, tspace.tangent);
+                let sign = if tspace.is_orientation_preserving {
+                    1.0
+                } else {
+                    -1.0
+                };
+                frames.push(Frame {
+                    tangent: tspace.tangent,
+                    bi_tangent: [sign * b[0], sign * b[1], sign * b[2]],
+                    normal,
+                });
+            }
+        }
+        Ok(CornerFrames { tspaces, frames })
+    }
+
+    pub(crate) fn get(&self, face: usize, vert: usize) -> &Frame {
+        &self.frames[self.tspaces.index(face, vert)]
+    }
+
+    /// `1.0` for orientation preserving corners, `-1.0` for mirrored ones.
+    pub(crate) fn sign(&self, face: usize, vert: usize) -> f32 {
+        if self.tspaces.get(face, vert).is_orientation_preserving {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+}
+
+/// Calls `f` with the barycentric coordinates of every texel center inside a
+/// texture space triangle. Texels on shared edges may be visited twice.
+pub(crate) fn rasterize<F>(width: usize, height: usize, uv: [[f32; 2]; 3], mut f: F)
+where
+    F: FnMut(usize, usize, [f32; 3]),
+{
+    let p: Vec<[f32; 2]> = uv
+        .iter()
+        .map(|t| [t[0] * width as f32, t[1] * height as f32])
+        .collect();
+    let edge = |a: [f32; 2], b: [f32; 2], c: [f32; 2]| {
+        (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+    };
+    let area = edge(p[0], p[1], p[2]);
+    if area == 0.0 || !area.is_finite() {
+        return;
+    }
+    let min = |i: usize| p.iter().map(|q| q[i]).fold(f32::INFINITY, f32::min);
+    let max = |i: usize| p.iter().map(|q| q[i]).fold(f32::NEG_INFINITY, f32::max);
+    let x0 = (min(0) - 0.5).ceil().max(0.0) as usize;
+    let y0 = (min(1) - 0.5).ceil().max(0.0) as usize;
+    let x1 = ((max(0) - 0.5).floor() + 1.0).max(0.0).min(width as f32) as usize;
+    let y1 = ((max(1) - 0.5).floor() + 1.0).max(0.0).min(height as f32) as usize;
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let c = [x as f32 + 0.5, y as f32 + 0.5];
+            let b = [
+                edge(p[1], p[2], c) / area,
+                edge(p[2], p[0], c) / area,
+                edge(p[0], p[1], c) / area,
+            ];
+            if b.iter().all(|&w| w >= 0.0) {
+                f(x, y, b);
+            }
+        }
+    }
+}
+
+/// Where the center of a texel lies on the mesh.
+pub(crate) struct SurfacePoint {
+    pub(crate) face: usize,
+    /// Corners of the triangle of `face` containing the point.
+    pub(crate) verts: [usize; 3],
+    pub(crate) barycentric: [f32; 3],
+    pub(crate) position: [f32; 3],
+}
+
+/// Calls `f` with the interpolated frame and position of every texel covered
+/// by `mesh`.
+pub(crate) fn for_each_texel<I, F>(
+    mesh: &I,
+    frames: &CornerFrames,
+    width: usize,
+    height: usize,
+    mut f: F,
+) where
+    I: MikkTSpaceInterface + ?Sized,
+    F: FnMut(usize, usize, &Frame, &SurfacePoint),
+{
+    for face in 0..mesh.get_num_faces() {
+        for tri in face_triangles(mesh, face) {
+            let uv = [
+                mesh.get_tex_coord(face, tri[0]),
+                mesh.get_tex_coord(face, tri[1]),
+                mesh.get_tex_coord(face, tri[2]),
+            ];
+            let positions = [
+                mesh.get_position(face, tri[0]),
+                mesh.get_position(face, tri[1]),
+                mesh.get_position(face, tri[2]),
+            ];
+            let corners = [
+                frames.get(face, tri[0]),
+                frames.get(face, tri[1]),
+                frames.get(face, tri[2]),
+            ];
+            rasterize(width, height, uv, |x, y, b| {
+                let lerp = |v: [[f32; 3]; 3]| {
+                    let mut out = [0.0; 3];
+                    for i in 0..3 {
+                        out[i] = b[0] * v[0][i] + b[1] * v[1][i] + b[2] * v[2][i];
+                    }
+                    out
+                };
+                let frame = Frame {
+                    tangent: lerp([corners[0].tangent, corners[1].tangent, corners[2].tangent]),
+                    bi_tangent: lerp([
+                        corners[0].bi_tangent,
+                        corners[1].bi_tangent,
+                        corners[2].bi_tangent,
+                    ]),
+                    normal: lerp([corners[0].normal, corners[1].normal, corners[2].normal]),
+                };
+                let point = SurfacePoint {
+                    face,
+                    verts: tri,
+                    barycentric: b,
+                    position: lerp(positions),
+                };
+                f(x, y, &frame, &point);
+            });
+        }
+    }
+}
+
+pub(crate) fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len = dot(v, v).sqrt();
+    if len > 0.0 {
+        [v[0] / len, v[1] / len, v[2] / len]
+    } else {
+        v
+    }
+}
+
+pub(crate) fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
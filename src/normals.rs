@@ -0,0 +1,444 @@
+//! Vertex normal generation for meshes that come without normals.
+//!
+//! Corners are smoothed with all faces around the same position, compared
+//! bit for bit. Every face contributes its unit normal weighted by its area
+//! or by its angle at the corner. With a smoothing angle below 180 degrees
+//! only faces whose normals are within that angle of the corner's own face
+//! contribute, which keeps hard edges hard. Faces only reach each other
+//! through shared edges that aren't sharp and between faces with a common
+//! smoothing group bit, as in the tangent space pass. Contributions are
+//! summed in face order, so the result doesn't depend on hashing.
+
+use std::collections::HashMap;
+
+use super::normal_map::{cross, dot, normalize, sub};
+use super::{gen_tang_space_ex, GenError, GenOptions, GenStats, MikkTSpaceInterface, Progress};
+
+/// How faces around a vertex are weighted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NormalWeighting {
+    /// By face area, larger faces pull the normal towards them.
+    Area,
+    /// By the angle of the face at the corner, independent of how the
+    /// surface is tessellated.
+    Angle,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NormalOptions {
+    pub weighting: NormalWeighting,
+    /// Maximum angle in degrees between the normals of two faces that are
+    /// smoothed together, 180 smooths all faces around a vertex.
+    pub smoothing_angle: f32,
+}
+
+impl Default for NormalOptions {
+    fn default() -> Self {
+        NormalOptions {
+            weighting: NormalWeighting::Angle,
+            smoothing_angle: 180.0,
+        }
+    }
+}
+
+/// `MikkTSpaceInterface` without `get_normal`, for meshes that have no
+/// normals. The methods are the same, see there. Pass it to
+/// `gen_tang_space_normal_free`.
+#[allow(unused_variables, clippy::too_many_arguments)]
+pub trait NormalFreeInterface {
+    fn get_num_faces(&self) -> usize;
+    fn get_num_vertices_of_face(&self, face: usize) -> usize;
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3];
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2];
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        u32::MAX
+    }
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        false
+    }
+    fn report_fallback(&mut self, face: usize, vert: usize) {}
+    fn report_progress(&mut self, progress: Progress) {}
+    fn set_normal(&mut self, normal: [f32; 3], face: usize, vert: usize) {}
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {}
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+    }
+}
+
+/// Generates a normal for every corner of `interface` and passes it to
+/// `MikkTSpaceInterface::set_normal`. `get_normal` isn't called.
+///
+/// Corners no face with an area contributes to get a zero normal.
+pub fn gen_normals<I>(interface: &mut I, options: &NormalOptions)
+where
+    I: MikkTSpaceInterface,
+{
+    CornerNormals::generate(interface, options).set(interface);
+}
+
+/// Generates normals like `gen_normals` and then tangent spaces like
+/// `gen_tang_space_ex` using those normals.
+///
+/// All normals are passed to `set_normal` before the first `set_tspace` call.
+pub fn gen_tang_space_with_normals<I>(
+    interface: &mut I,
+    normal_options: &NormalOptions,
+    options: &GenOptions,
+) -> Result<GenStats, GenError>
+where
+    I: MikkTSpaceInterface,
+{
+    let normals = CornerNormals::generate(interface, normal_options);
+    normals.set(interface);
+    gen_tang_space_ex(&mut WithNormals { interface, normals }, options)
+}
+
+/// `gen_tang_space_with_normals` for meshes without normals.
+pub fn gen_tang_space_normal_free<N>(
+    mesh: &mut N,
+    normal_options: &NormalOptions,
+    options: &GenOptions,
+) -> Result<GenStats, GenError>
+where
+    N: NormalFreeInterface,
+{
+    gen_tang_space_with_normals(&mut NormalFree(mesh), normal_options, options)
+}
+
+struct CornerNormals {
+    offsets: Vec<usize>,
+    normals: Vec<[f32; 3]>,
+}
+
+impl CornerNormals {
+    fn generate<I>(mesh: &I, options: &NormalOptions) -> Self
+    where
+        I: MikkTSpaceInterface + ?Sized,
+    {
+        let num_faces = mesh.get_num_faces();
+        let mut offsets = Vec::with_capacity(num_faces);
+        let mut face_normals = Vec::with_capacity(num_faces);
+        // Weighted unit face normal per corner.
+        let mut contributions = Vec::new();
+        let mut vertices: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+        for face in 0..num_faces {
+            offsets.push(contributions.len());
+            let num_verts = mesh.get_num_vertices_of_face(face);
+            let positions: Vec<[f32; 3]> = (0..num_verts)
+                .map(|vert| mesh.get_position(face, vert))
+                .collect();
+            let newell = newell(&positions);
+            let area = dot(newell, newell).sqrt() / 2.0;
+            let normal = normalize(newell);
+            face_normals.push(normal);
+            for (vert, p) in positions.iter().enumerate() {
+                let weight = match options.weighting {
+                    NormalWeighting::Area => area,
+                    NormalWeighting::Angle => {
+                        let prev = positions[(vert + num_verts - 1) % num_verts];
+                        let next = positions[(vert + 1) % num_verts];
+                        angle(sub(prev, *p), sub(next, *p))
+                    }
+                };
+                contributions.push([normal[0] * weight, normal[1] * weight, normal[2] * weight]);
+                let faces = vertices.entry(position_key(*p)).or_default();
+                if faces.last() != Some(&face) {
+                    faces.push(face);
+                }
+            }
+        }
+
+        let fans: HashMap<[u32; 3], Vec<usize>> = vertices
+            .iter()
+            .map(|(&key, faces)| (key, fans(mesh, key, faces)))
+            .collect();
+
+        let cos_threshold = (options.smoothing_angle.to_radians()).cos();
+        let smooth_all = options.smoothing_angle >= 180.0;
+        let mut normals = Vec::with_capacity(contributions.len());
+        for face in 0..num_faces {
+            for vert in 0..mesh.get_num_vertices_of_face(face) {
+                let p = mesh.get_position(face, vert);
+                let key = position_key(p);
+                let own = face_normals[face];
+                let faces = &vertices[&key];
+                let fans = &fans[&key];
+                let own_fan = fans[faces.iter().position(|&other| other == face).unwrap()];
+                let mut sum = [0.0; 3];
+                for (&other, &fan) in faces.iter().zip(fans) {
+                    if fan != own_fan {
+                        continue;
+                    }
+                    if !smooth_all && other != face && dot(face_normals[other], own) < cos_threshold
+                    {
+                        continue;
+                    }
+                    let other_verts = mesh.get_num_vertices_of_face(other);
+                    for other_vert in 0..other_verts {
+                        if mesh.get_position(other, other_vert) != p {
+                            continue;
+                        }
+                        let c = contributions[offsets[other] + other_vert];
+                        sum = [sum[0] + c[0], sum[1] + c[1], sum[2] + c[2]];
+                    }
+                }
+                let normal = normalize(sum);
+                normals.push(if dot(normal, normal) > 0.0 {
+                    normal
+                } else {
+                    own
+                });
+            }
+        }
+        CornerNormals { offsets, normals }
+    }
+
+    fn get(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.normals[self.offsets[face] + vert]
+    }
+
+    fn set<I: MikkTSpaceInterface>(&self, interface: &mut I) {
+        for face in 0..interface.get_num_faces() {
+            for vert in 0..interface.get_num_vertices_of_face(face) {
+                interface.set_normal(self.get(face, vert), face, vert);
+            }
+        }
+    }
+}
+
+/// Replaces the normals of the wrapped interface by generated ones.
+struct WithNormals<'a, I> {
+    interface: &'a mut I,
+    normals: CornerNormals,
+}
+
+impl<'a, I: MikkTSpaceInterface> MikkTSpaceInterface for WithNormals<'a, I> {
+    fn get_num_faces(&self) -> usize {
+        self.interface.get_num_faces()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.interface.get_num_vertices_of_face(face)
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.interface.get_position(face, vert)
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.normals.get(face, vert)
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.interface.get_tex_coord(face, vert)
+    }
+
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        self.interface.get_smoothing_group(face)
+    }
+
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        self.interface.is_edge_sharp(face, vert)
+    }
+
+    fn report_progress(&mut self, progress: Progress) {
+        self.interface.report_progress(progress);
+    }
+
+    fn report_fallback(&mut self, face: usize, vert: usize) {
+        self.interface.report_fallback(face, vert);
+    }
+
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {
+        self.interface.set_tspace_basic(tangent, sign, face, vert);
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.interface.set_tspace(
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            face,
+            vert,
+        );
+    }
+}
+
+/// Bits of a position, the same for `-0.0` and `0.0`.
+fn position_key(p: [f32; 3]) -> [u32; 3] {
+    // Adding zero turns -0.0 into 0.0.
+    [p[0] + 0.0, p[1] + 0.0, p[2] + 0.0].map(f32::to_bits)
+}
+
+/// The fan of every face in `faces` around the vertex at `key`, as the index
+/// of its first face. Faces sharing an edge at the vertex are in the same fan
+/// unless either marks the edge sharp or their smoothing groups have no bit
+/// in common, like tangent spaces are split by the generator.
+fn fans<I>(mesh: &I, key: [u32; 3], faces: &[usize]) -> Vec<usize>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    // The other end and sharpness of the edges of every face at the vertex.
+    let edges: Vec<Vec<([u32; 3], bool)>> = faces
+        .iter()
+        .map(|&face| {
+            let num_verts = mesh.get_num_vertices_of_face(face);
+            let mut edges = Vec::new();
+            for vert in 0..num_verts {
+                if position_key(mesh.get_position(face, vert)) != key {
+                    continue;
+                }
+                let prev = (vert + num_verts - 1) % num_verts;
+                let next = (vert + 1) % num_verts;
+                edges.push((
+                    position_key(mesh.get_position(face, next)),
+                    mesh.is_edge_sharp(face, vert),
+                ));
+                edges.push((
+                    position_key(mesh.get_position(face, prev)),
+                    mesh.is_edge_sharp(face, prev),
+                ));
+            }
+            edges
+        })
+        .collect();
+
+    let mut fans: Vec<usize> = (0..faces.len()).collect();
+    fn root(fans: &mut [usize], mut i: usize) -> usize {
+        while fans[i] != i {
+            fans[i] = fans[fans[i]];
+            i = fans[i];
+        }
+        i
+    }
+    for i in 0..faces.len() {
+        for j in i + 1..faces.len() {
+            if mesh.get_smoothing_group(faces[i]) & mesh.get_smoothing_group(faces[j]) == 0 {
+                continue;
+            }
+            let connected = edges[i].iter().any(|&(end, sharp)| {
+                end != key
+                    && !sharp
+                    && edges[j]
+                        .iter()
+                        .any(|&(other_end, other_sharp)| other_end == end && !other_sharp)
+            });
+            if connected {
+                let (a, b) = (root(&mut fans, i), root(&mut fans, j));
+                fans[a.max(b)] = a.min(b);
+            }
+        }
+    }
+    (0..faces.len()).map(|i| root(&mut fans, i)).collect()
+}
+
+/// Passes a `NormalFreeInterface` to `gen_tang_space_with_normals`, which
+/// never reads its normals.
+struct NormalFree<'a, N>(&'a mut N);
+
+impl<'a, N: NormalFreeInterface> MikkTSpaceInterface for NormalFree<'a, N> {
+    fn get_num_faces(&self) -> usize {
+        self.0.get_num_faces()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.0.get_num_vertices_of_face(face)
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.0.get_position(face, vert)
+    }
+
+    fn get_normal(&self, _face: usize, _vert: usize) -> [f32; 3] {
+        unreachable!("replaced by the generated normals")
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.0.get_tex_coord(face, vert)
+    }
+
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        self.0.get_smoothing_group(face)
+    }
+
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        self.0.is_edge_sharp(face, vert)
+    }
+
+    fn report_progress(&mut self, progress: Progress) {
+        self.0.report_progress(progress);
+    }
+
+    fn report_fallback(&mut self, face: usize, vert: usize) {
+        self.0.report_fallback(face, vert);
+    }
+
+    fn set_normal(&mut self, normal: [f32; 3], face: usize, vert: usize) {
+        self.0.set_normal(normal, face, vert);
+    }
+
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {
+        self.0.set_tspace_basic(tangent, sign, face, vert);
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.0.set_tspace(
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            face,
+            vert,
+        );
+    }
+}
+
+/// Normal of a polygon by Newell's method, its length is twice the area for
+/// planar polygons.
+pub(crate) fn newell(positions: &[[f32; 3]]) -> [f32; 3] {
+    let mut normal = [0.0; 3];
+    for (i, p) in positions.iter().enumerate() {
+        let q = positions[(i + 1) % positions.len()];
+        normal[0] += (p[1] - q[1]) * (p[2] + q[2]);
+        normal[1] += (p[2] - q[2]) * (p[0] + q[0]);
+        normal[2] += (p[0] - q[0]) * (p[1] + q[1]);
+    }
+    normal
+}
+
+/// Angle between two vectors in radians, zero if either has no length.
+fn angle(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let c = cross(a, b);
+    dot(c, c).sqrt().atan2(dot(a, b))
+}
@@ -0,0 +1,714 @@
+//! Stanford PLY import and export.
+//!
+//! `read_ply` reads ASCII and binary files with a `vertex` element holding
+//! `x y z`, optionally `nx ny nz`, and texture coordinates as `s t`, `u v`,
+//! `texture_u texture_v` or `texture_s texture_t`, and a `face` element with
+//! a `vertex_indices` (or `vertex_index`) list. Other elements and
+//! properties are skipped. `write_ply` writes `x y z nx ny nz s t`, without
+//! `nx ny nz` when the mesh has no normals, followed by `tx ty tz tw` when
+//! the mesh has tangents.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use super::reindex::reindex;
+use super::{
+    gen_tang_space_ex, gen_tang_space_with_normals, GenError, GenOptions, GenStats,
+    MikkTSpaceInterface, NormalOptions,
+};
+
+const TEX_COORD_NAMES: [[&str; 2]; 4] = [
+    ["s", "t"],
+    ["u", "v"],
+    ["texture_u", "texture_v"],
+    ["texture_s", "texture_t"],
+];
+
+/// An indexed polygon mesh as stored in a PLY file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlyMesh {
+    pub positions: Vec<[f32; 3]>,
+    /// Normal of every vertex, or empty. Filled in by `generate_tangents`
+    /// if empty.
+    pub normals: Vec<[f32; 3]>,
+    pub tex_coords: Vec<[f32; 2]>,
+    /// Tangent and bitangent sign of every vertex, or empty. Filled in by
+    /// `generate_tangents` or read from `tx ty tz tw`.
+    pub tangents: Vec<[f32; 4]>,
+    pub faces: Vec<Vec<u32>>,
+}
+
+/// Encoding of the data after the PLY header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug)]
+pub enum PlyError {
+    Io(io::Error),
+    /// The header or the data is malformed, e.g. an unknown property type
+    /// or a face index out of bounds. Also returned by
+    /// `PlyMesh::generate_tangents` for meshes that couldn't have been read.
+    Invalid(String),
+    /// A required vertex property or the face index list is missing.
+    MissingProperty(&'static str),
+    Gen(GenError),
+}
+
+impl fmt::Display for PlyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlyError::Io(error) => write!(f, "reading the PLY file failed: {}", error),
+            PlyError::Invalid(message) => write!(f, "invalid PLY file: {}", message),
+            PlyError::MissingProperty(name) => write!(f, "missing PLY property '{}'", name),
+            PlyError::Gen(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for PlyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PlyError::Io(error) => Some(error),
+            PlyError::Gen(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PlyError {
+    fn from(error: io::Error) -> Self {
+        PlyError::Io(error)
+    }
+}
+
+impl From<GenError> for PlyError {
+    fn from(error: GenError) -> Self {
+        PlyError::Gen(error)
+    }
+}
+
+fn invalid<T, S: Into<String>>(message: S) -> Result<T, PlyError> {
+    Err(PlyError::Invalid(message.into()))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Result<Self, PlyError> {
+        Ok(match name {
+            "char" | "int8" => ScalarType::I8,
+            "uchar" | "uint8" => ScalarType::U8,
+            "short" | "int16" => ScalarType::I16,
+            "ushort" | "uint16" => ScalarType::U16,
+            "int" | "int32" => ScalarType::I32,
+            "uint" | "uint32" => ScalarType::U32,
+            "float" | "float32" => ScalarType::F32,
+            "double" | "float64" => ScalarType::F64,
+            _ => return invalid(format!("unknown property type '{}'", name)),
+        })
+    }
+}
+
+#[derive(Debug)]
+enum PropertyType {
+    Scalar(ScalarType),
+    List(ScalarType, ScalarType),
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    ty: PropertyType,
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn find(&self, name: &str) -> Option<usize> {
+        self.properties.iter().position(|p| p.name == name)
+    }
+
+    /// Positions of the scalar properties `names`, if all of them exist.
+    fn find_all<const N: usize>(&self, names: [&str; N]) -> Option<[usize; N]> {
+        let mut found = [0; N];
+        for (found, name) in found.iter_mut().zip(names) {
+            *found = self.find(name)?;
+            if !matches!(self.properties[*found].ty, PropertyType::Scalar(_)) {
+                return None;
+            }
+        }
+        Some(found)
+    }
+}
+
+struct Header {
+    format: PlyFormat,
+    elements: Vec<Element>,
+}
+
+fn read_header<R: BufRead>(reader: &mut R) -> Result<Header, PlyError> {
+    let mut line = String::new();
+    let mut next_line = |line: &mut String| -> Result<(), PlyError> {
+        line.clear();
+        if reader.read_line(line)? == 0 {
+            return invalid("unexpected end of header");
+        }
+        Ok(())
+    };
+    next_line(&mut line)?;
+    if line.trim_end() != "ply" {
+        return invalid("missing 'ply' magic");
+    }
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        next_line(&mut line)?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["end_header"] => break,
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            ["format", name, "1.0"] => {
+                format = Some(match *name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return invalid(format!("unknown format '{}'", name)),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: match count.parse() {
+                    Ok(count) => count,
+                    Err(_) => return invalid(format!("invalid element count '{}'", count)),
+                },
+                properties: Vec::new(),
+            }),
+            ["property", rest @ ..] => {
+                let element = match elements.last_mut() {
+                    Some(element) => element,
+                    None => return invalid("property outside of an element"),
+                };
+                let (ty, name) = match rest {
+                    ["list", count, item, name] => (
+                        PropertyType::List(ScalarType::parse(count)?, ScalarType::parse(item)?),
+                        name,
+                    ),
+                    [ty, name] => (PropertyType::Scalar(ScalarType::parse(ty)?), name),
+                    _ => return invalid(format!("malformed line '{}'", line.trim_end())),
+                };
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    ty,
+                });
+            }
+            _ => return invalid(format!("malformed line '{}'", line.trim_end())),
+        }
+    }
+    match format {
+        Some(format) => Ok(Header { format, elements }),
+        None => invalid("missing format"),
+    }
+}
+
+/// Reads the values after the header, one at a time.
+enum Values<R> {
+    Ascii { reader: R, line: String, pos: usize },
+    Binary { reader: R, big_endian: bool },
+}
+
+impl<R: BufRead> Values<R> {
+    fn next(&mut self, ty: ScalarType) -> Result<f64, PlyError> {
+        match self {
+            Values::Ascii { reader, line, pos } => {
+                let token = loop {
+                    let rest = &line[*pos..];
+                    let start = rest.len() - rest.trim_start().len();
+                    let len = rest[start..]
+                        .find(char::is_whitespace)
+                        .unwrap_or(rest.len() - start);
+                    if len > 0 {
+                        *pos += start + len;
+                        break &rest[start..start + len];
+                    }
+                    line.clear();
+                    *pos = 0;
+                    if reader.read_line(line)? == 0 {
+                        return invalid("unexpected end of data");
+                    }
+                };
+                // Parsing `float`s as `f32` avoids double rounding.
+                let value = match ty {
+                    ScalarType::F32 => token.parse::<f32>().map(f64::from).ok(),
+                    ScalarType::F64 => token.parse::<f64>().ok(),
+                    _ => token.parse::<i64>().ok().map(|value| value as f64),
+                };
+                match value {
+                    Some(value) => Ok(value),
+                    None => invalid(format!("invalid value '{}'", token)),
+                }
+            }
+            Values::Binary { reader, big_endian } => {
+                macro_rules! read {
+                    ($ty:ty) => {{
+                        let mut bytes = [0; std::mem::size_of::<$ty>()];
+                        reader.read_exact(&mut bytes)?;
+                        if *big_endian {
+                            <$ty>::from_be_bytes(bytes) as f64
+                        } else {
+                            <$ty>::from_le_bytes(bytes) as f64
+                        }
+                    }};
+                }
+                Ok(match ty {
+                    ScalarType::I8 => read!(i8),
+                    ScalarType::U8 => read!(u8),
+                    ScalarType::I16 => read!(i16),
+                    ScalarType::U16 => read!(u16),
+                    ScalarType::I32 => read!(i32),
+                    ScalarType::U32 => read!(u32),
+                    ScalarType::F32 => read!(f32),
+                    ScalarType::F64 => read!(f64),
+                })
+            }
+        }
+    }
+
+    fn count(&mut self, ty: ScalarType) -> Result<usize, PlyError> {
+        let count = self.next(ty)?;
+        if count < 0.0 || count.fract() != 0.0 {
+            return invalid(format!("invalid list length {}", count));
+        }
+        Ok(count as usize)
+    }
+}
+
+/// Reads a PLY file in any of the three formats.
+pub fn read_ply<R: BufRead>(mut reader: R) -> Result<PlyMesh, PlyError> {
+    let header = read_header(&mut reader)?;
+    let mut values = match header.format {
+        PlyFormat::Ascii => Values::Ascii {
+            reader,
+            line: String::new(),
+            pos: 0,
+        },
+        format => Values::Binary {
+            reader,
+            big_endian: format == PlyFormat::BinaryBigEndian,
+        },
+    };
+
+    let mut mesh = PlyMesh::default();
+    let mut has_vertices = false;
+    let mut has_faces = false;
+    let mut scalars = Vec::new();
+    for element in &header.elements {
+        match element.name.as_str() {
+            "vertex" => {
+                has_vertices = true;
+                let position = element
+                    .find_all(["x", "y", "z"])
+                    .ok_or(PlyError::MissingProperty("x"))?;
+                let normal = element.find_all(["nx", "ny", "nz"]);
+                let tex_coord = TEX_COORD_NAMES
+                    .iter()
+                    .find_map(|&names| element.find_all(names))
+                    .ok_or(PlyError::MissingProperty("s"))?;
+                let tangent = element.find_all(["tx", "ty", "tz", "tw"]);
+                for _ in 0..element.count {
+                    scalars.clear();
+                    for property in &element.properties {
+                        match property.ty {
+                            PropertyType::Scalar(ty) => scalars.push(values.next(ty)? as f32),
+                            PropertyType::List(count, item) => {
+                                // Keeps the property positions of `find_all`.
+                                scalars.push(0.0);
+                                for _ in 0..values.count(count)? {
+                                    values.next(item)?;
+                                }
+                            }
+                        }
+                    }
+                    mesh.positions.push(position.map(|i| scalars[i]));
+                    if let Some(normal) = normal {
+                        mesh.normals.push(normal.map(|i| scalars[i]));
+                    }
+                    mesh.tex_coords.push(tex_coord.map(|i| scalars[i]));
+                    if let Some(tangent) = tangent {
+                        mesh.tangents.push(tangent.map(|i| scalars[i]));
+                    }
+                }
+            }
+            "face" => {
+                has_faces = true;
+                let indices = element
+                    .find("vertex_indices")
+                    .or_else(|| element.find("vertex_index"))
+                    .ok_or(PlyError::MissingProperty("vertex_indices"))?;
+                for _ in 0..element.count {
+                    for (i, property) in element.properties.iter().enumerate() {
+                        match property.ty {
+                            PropertyType::Scalar(ty) => {
+                                values.next(ty)?;
+                            }
+                            PropertyType::List(count, item) => {
+                                let count = values.count(count)?;
+                                // The count comes from the file, so it isn't
+                                // trusted with an allocation up front.
+                                let mut face = Vec::new();
+                                for _ in 0..count {
+                                    face.push(values.next(item)?);
+                                }
+                                if i == indices {
+                                    mesh.faces.push(to_indices(face)?);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    for property in &element.properties {
+                        match property.ty {
+                            PropertyType::Scalar(ty) => {
+                                values.next(ty)?;
+                            }
+                            PropertyType::List(count, item) => {
+                                for _ in 0..values.count(count)? {
+                                    values.next(item)?;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if !has_vertices {
+        return Err(PlyError::MissingProperty("x"));
+    }
+    if !has_faces {
+        return Err(PlyError::MissingProperty("vertex_indices"));
+    }
+    mesh.check_indices()?;
+    Ok(mesh)
+}
+
+fn to_indices(face: Vec<f64>) -> Result<Vec<u32>, PlyError> {
+    face.into_iter()
+        .map(|index| {
+            if index < 0.0 || index > u32::MAX as f64 || index.fract() != 0.0 {
+                invalid(format!("invalid vertex index {}", index))
+            } else {
+                Ok(index as u32)
+            }
+        })
+        .collect()
+}
+
+/// Writes `mesh` in `format`. Faces are written as `uchar` counts followed
+/// by `uint` indices, so faces with more than 255 vertices are rejected.
+pub fn write_ply<W: Write>(mut out: W, mesh: &PlyMesh, format: PlyFormat) -> io::Result<()> {
+    if let Err(message) = mesh.check_lengths() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    }
+    let has_normals = !mesh.normals.is_empty();
+    let has_tangents = !mesh.tangents.is_empty();
+    let num_vertices = mesh.positions.len();
+    if mesh.faces.iter().any(|face| face.len() > u8::MAX as usize) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "faces with more than 255 vertices can't be written",
+        ));
+    }
+
+    writeln!(out, "ply")?;
+    writeln!(
+        out,
+        "format {} 1.0",
+        match format {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+            PlyFormat::BinaryBigEndian => "binary_big_endian",
+        }
+    )?;
+    writeln!(out, "element vertex {}", num_vertices)?;
+    let mut names = vec!["x", "y", "z"];
+    if has_normals {
+        names.extend_from_slice(&["nx", "ny", "nz"]);
+    }
+    names.extend_from_slice(&["s", "t"]);
+    if has_tangents {
+        names.extend_from_slice(&["tx", "ty", "tz", "tw"]);
+    }
+    for name in &names {
+        writeln!(out, "property float {}", name)?;
+    }
+    writeln!(out, "element face {}", mesh.faces.len())?;
+    writeln!(out, "property list uchar uint vertex_indices")?;
+    writeln!(out, "end_header")?;
+
+    let mut vertex = Vec::with_capacity(names.len());
+    for i in 0..num_vertices {
+        vertex.clear();
+        vertex.extend_from_slice(&mesh.positions[i]);
+        if has_normals {
+            vertex.extend_from_slice(&mesh.normals[i]);
+        }
+        vertex.extend_from_slice(&mesh.tex_coords[i]);
+        if has_tangents {
+            vertex.extend_from_slice(&mesh.tangents[i]);
+        }
+        match format {
+            PlyFormat::Ascii => {
+                let line: Vec<String> = vertex.iter().map(f32::to_string).collect();
+                writeln!(out, "{}", line.join(" "))?;
+            }
+            PlyFormat::BinaryLittleEndian => {
+                for value in &vertex {
+                    out.write_all(&value.to_le_bytes())?;
+                }
+            }
+            PlyFormat::BinaryBigEndian => {
+                for value in &vertex {
+                    out.write_all(&value.to_be_bytes())?;
+                }
+            }
+        }
+    }
+    for face in &mesh.faces {
+        match format {
+            PlyFormat::Ascii => {
+                write!(out, "{}", face.len())?;
+                for index in face {
+                    write!(out, " {}", index)?;
+                }
+                writeln!(out)?;
+            }
+            PlyFormat::BinaryLittleEndian => {
+                out.write_all(&[face.len() as u8])?;
+                for index in face {
+                    out.write_all(&index.to_le_bytes())?;
+                }
+            }
+            PlyFormat::BinaryBigEndian => {
+                out.write_all(&[face.len() as u8])?;
+                for index in face {
+                    out.write_all(&index.to_be_bytes())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl PlyMesh {
+    /// Generates tangent spaces with `options` and stores them in
+    /// `tangents`, the sign of the bitangent in `w` as passed to
+    /// `set_tspace_basic`. Meshes without normals get them from
+    /// `gen_tang_space_with_normals` with the default `NormalOptions`,
+    /// stored in `normals`.
+    ///
+    /// Vertices whose corners got different tangents or normals are split
+    /// with `reindex`, appending copies to every attribute and rewriting the
+    /// faces. Faces other than triangles and quads are ignored by the
+    /// generator and keep their vertices; vertices only they use get a zero
+    /// tangent, and a zero normal if normals were generated.
+    ///
+    /// Attributes of differing lengths and face indices out of bounds are
+    /// reported as `PlyError::Invalid`, leaving the mesh unchanged.
+    pub fn generate_tangents(&mut self, options: &GenOptions) -> Result<GenStats, PlyError> {
+        if let Err(message) = self.check_lengths() {
+            return invalid(message);
+        }
+        self.check_indices()?;
+        let mut offsets = Vec::with_capacity(self.faces.len());
+        let mut num_corners = 0;
+        for face in &self.faces {
+            offsets.push(num_corners);
+            num_corners += face.len();
+        }
+        let has_normals = !self.normals.is_empty();
+        let mut corners = Corners {
+            mesh: self,
+            offsets: &offsets,
+            tangents: vec![[0.0; 4]; num_corners],
+            normals: vec![[0.0; 3]; num_corners],
+        };
+        let stats = if has_normals {
+            gen_tang_space_ex(&mut corners, options)?
+        } else {
+            gen_tang_space_with_normals(&mut corners, &NormalOptions::default(), options)?
+        };
+        let Corners {
+            tangents: corner_tangents,
+            normals: corner_normals,
+            ..
+        } = corners;
+
+        let supported = |face: &Vec<u32>| face.len() == 3 || face.len() == 4;
+        let mut indices = Vec::new();
+        let mut keys = Vec::new();
+        for (face, &offset) in self.faces.iter().zip(&offsets) {
+            if supported(face) {
+                indices.extend_from_slice(face);
+                let corners = offset..offset + face.len();
+                keys.extend(
+                    corner_tangents[corners.clone()]
+                        .iter()
+                        .zip(&corner_normals[corners])
+                        .map(|(tangent, normal)| {
+                            (tangent.map(f32::to_bits), normal.map(f32::to_bits))
+                        }),
+                );
+            }
+        }
+        let num_vertices = self.positions.len();
+        let reindexed = reindex(&indices, num_vertices, &keys);
+        for &source in reindexed.splits(num_vertices) {
+            let source = source as usize;
+            self.positions.push(self.positions[source]);
+            if has_normals {
+                self.normals.push(self.normals[source]);
+            }
+            self.tex_coords.push(self.tex_coords[source]);
+        }
+
+        self.tangents = vec![[0.0; 4]; reindexed.sources.len()];
+        if !has_normals {
+            self.normals = vec![[0.0; 3]; reindexed.sources.len()];
+        }
+        let mut new_indices = reindexed.indices.into_iter();
+        for (face, &offset) in self.faces.iter_mut().zip(&offsets) {
+            if supported(face) {
+                for (vert, index) in face.iter_mut().enumerate() {
+                    *index = new_indices.next().unwrap();
+                    self.tangents[*index as usize] = corner_tangents[offset + vert];
+                    if !has_normals {
+                        self.normals[*index as usize] = corner_normals[offset + vert];
+                    }
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    fn vertex(&self, face: usize, vert: usize) -> usize {
+        self.faces[face][vert] as usize
+    }
+
+    /// Checks that the optional attributes are empty or as long as
+    /// `positions`, like `read_ply` produces them.
+    fn check_lengths(&self) -> Result<(), &'static str> {
+        let num_vertices = self.positions.len();
+        let optional = |len: usize| len == 0 || len == num_vertices;
+        if self.tex_coords.len() != num_vertices
+            || !optional(self.normals.len())
+            || !optional(self.tangents.len())
+        {
+            return Err("vertex attributes differ in length");
+        }
+        Ok(())
+    }
+
+    fn check_indices(&self) -> Result<(), PlyError> {
+        let num_vertices = self.positions.len();
+        for face in &self.faces {
+            if let Some(&index) = face.iter().find(|&&index| index as usize >= num_vertices) {
+                return invalid(format!(
+                    "vertex index {} out of bounds for {} vertices",
+                    index, num_vertices
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the vertex attributes, so the mesh can be passed to the other
+/// functions of this crate. Use `generate_tangents` to store the results.
+/// Without normals `get_normal` returns zero like the default, so only
+/// `gen_tang_space_with_normals` gives meaningful results.
+impl MikkTSpaceInterface for PlyMesh {
+    fn get_num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.faces[face].len()
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.positions[self.vertex(face, vert)]
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        if self.normals.is_empty() {
+            return [0.0; 3];
+        }
+        self.normals[self.vertex(face, vert)]
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.tex_coords[self.vertex(face, vert)]
+    }
+}
+
+/// `PlyMesh` collecting the tangent of every corner.
+struct Corners<'a> {
+    mesh: &'a PlyMesh,
+    offsets: &'a [usize],
+    tangents: Vec<[f32; 4]>,
+    /// Generated normals, if the mesh has none.
+    normals: Vec<[f32; 3]>,
+}
+
+impl<'a> MikkTSpaceInterface for Corners<'a> {
+    fn get_num_faces(&self) -> usize {
+        self.mesh.get_num_faces()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.mesh.get_num_vertices_of_face(face)
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.get_position(face, vert)
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.get_normal(face, vert)
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.mesh.get_tex_coord(face, vert)
+    }
+
+    fn set_normal(&mut self, normal: [f32; 3], face: usize, vert: usize) {
+        self.normals[self.offsets[face] + vert] = normal;
+    }
+
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {
+        self.tangents[self.offsets[face] + vert] = [tangent[0], tangent[1], tangent[2], sign];
+    }
+}
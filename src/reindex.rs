@@ -0,0 +1,66 @@
+//! Storing per corner results in an indexed mesh.
+//!
+//! Tangent spaces are generated for every corner, so two corners sharing a
+//! vertex can get different ones. `mikktspace.h` warns that writing them
+//! into the existing index list is wrong: one of the corners would silently
+//! get the other's tangent. Instead such vertices have to be split.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// New indices for an indexed mesh whose corners carry their own values, see
+/// `reindex`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reindexed {
+    /// New vertex of every corner.
+    pub indices: Vec<u32>,
+    /// Original vertex every new vertex copies its attributes from. The
+    /// original vertices keep their place, split vertices are appended.
+    pub sources: Vec<u32>,
+}
+
+impl Reindexed {
+    /// Original vertices of the appended, split vertices, i.e. `sources`
+    /// without the original vertices.
+    pub fn splits(&self, num_vertices: usize) -> &[u32] {
+        &self.sources[num_vertices..]
+    }
+}
+
+/// Splits the vertices of an indexed mesh whose corners got different
+/// `keys`.
+///
+/// `indices` holds the vertex of every corner, `keys` the value of every
+/// corner, e.g. the bits of its tangent. The first corner using a vertex
+/// keeps it, corners with a key already seen at the vertex share that
+/// vertex, and every other key gets a new vertex.
+///
+/// # Panics
+///
+/// If `indices` and `keys` differ in length or an index is out of bounds.
+pub fn reindex<K>(indices: &[u32], num_vertices: usize, keys: &[K]) -> Reindexed
+where
+    K: Eq + Hash + Copy,
+{
+    assert_eq!(indices.len(), keys.len());
+    let mut sources: Vec<u32> = (0..num_vertices as u32).collect();
+    let mut first: Vec<Option<K>> = vec![None; num_vertices];
+    let mut splits: HashMap<(u32, K), u32> = HashMap::new();
+    let indices = indices
+        .iter()
+        .zip(keys)
+        .map(|(&vertex, &key)| match first[vertex as usize] {
+            None => {
+                first[vertex as usize] = Some(key);
+                vertex
+            }
+            Some(kept) if kept == key => vertex,
+            Some(_) => *splits.entry((vertex, key)).or_insert_with(|| {
+                sources.push(vertex);
+                sources.len() as u32 - 1
+            }),
+        })
+        .collect();
+    Reindexed { indices, sources }
+}
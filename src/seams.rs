@@ -0,0 +1,277 @@
+//! Report of mirrored UV islands and the edges where tangent spaces split.
+//!
+//! Edges are matched between faces by the positions of their end points,
+//! compared bit for bit. An edge whose texture coordinates agree on both
+//! sides connects its faces into one UV island. An edge is a seam if the
+//! tangent spaces generated on its two sides differ at either end point,
+//! which a normal map baked for one side shows as a visible discontinuity
+//! unless the baker and renderer agree on the tangent spaces exactly.
+
+use std::collections::HashMap;
+
+use super::normal_map::CornerFrames;
+use super::{GenError, GenOptions, MikkTSpaceInterface};
+
+/// Corners of the faces using an edge, keyed by its sorted end points.
+type EdgeMap = HashMap<([u32; 3], [u32; 3]), Vec<(usize, usize)>>;
+
+/// Why the tangent spaces on the two sides of a seam differ.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SeamKind {
+    /// One side is mirrored in texture space and the other isn't.
+    Mirror,
+    /// The texture coordinates differ, the edge is on the border of two UV
+    /// islands.
+    Uv,
+    /// The normals differ, or the edge is marked sharp or separates
+    /// smoothing groups.
+    Hard,
+    /// Nothing differs at the edge itself. The tangent spaces were split by
+    /// the angular threshold or because the triangles around a vertex aren't
+    /// connected.
+    Split,
+}
+
+/// An edge where tangent spaces split.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeamEdge {
+    /// The edge from corner `vert` to the next corner of `face`, as
+    /// `(face, vert)`, on both sides of the seam.
+    pub first: (usize, usize),
+    pub second: (usize, usize),
+    pub kind: SeamKind,
+}
+
+/// Faces connected through edges with identical texture coordinates on both
+/// sides.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UvIsland {
+    /// Sorted face indices.
+    pub faces: Vec<usize>,
+    pub preserving_corners: usize,
+    pub mirrored_corners: usize,
+}
+
+impl UvIsland {
+    /// Whether most corners of the island are mirrored in texture space.
+    pub fn is_mirrored(&self) -> bool {
+        self.mirrored_corners > self.preserving_corners
+    }
+}
+
+/// Result of `uv_seam_report`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeamReport {
+    /// Islands ordered by their first face.
+    pub islands: Vec<UvIsland>,
+    /// Seams ordered by `first`.
+    pub seams: Vec<SeamEdge>,
+    /// Corners with `is_orientation_preserving` set, right handed.
+    pub preserving_corners: usize,
+    /// Corners mirrored in texture space, left handed.
+    pub mirrored_corners: usize,
+}
+
+impl SeamReport {
+    pub fn mirrored_islands(&self) -> impl Iterator<Item = &UvIsland> {
+        self.islands.iter().filter(|island| island.is_mirrored())
+    }
+}
+
+/// Generates tangent spaces for `mesh` with `options` and reports its UV
+/// islands, their orientation and the seams between tangent spaces.
+///
+/// Only triangles and quads are considered, like by the tangent space
+/// generation.
+pub fn uv_seam_report<I>(mesh: &mut I, options: &GenOptions) -> Result<SeamReport, GenError>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let frames = CornerFrames::generate(mesh, options)?;
+    let edges = edge_map(mesh);
+    let mut report = SeamReport::default();
+    for sides in edges.values() {
+        for (i, &first) in sides.iter().enumerate() {
+            for &second in &sides[i + 1..] {
+                let pairs = corner_pairs(mesh, first, second);
+                let split = pairs.iter().any(|&(a, b)| {
+                    frames.get(a.0, a.1).tangent != frames.get(b.0, b.1).tangent
+                        || frames.sign(a.0, a.1) != frames.sign(b.0, b.1)
+                });
+                if !split {
+                    continue;
+                }
+                let mirror = pairs
+                    .iter()
+                    .any(|&(a, b)| frames.sign(a.0, a.1) != frames.sign(b.0, b.1));
+                let hard = pairs
+                    .iter()
+                    .any(|&(a, b)| mesh.get_normal(a.0, a.1) != mesh.get_normal(b.0, b.1))
+                    || mesh.is_edge_sharp(first.0, first.1)
+                    || mesh.is_edge_sharp(second.0, second.1)
+                    || mesh.get_smoothing_group(first.0) & mesh.get_smoothing_group(second.0) == 0;
+                let kind = if mirror {
+                    SeamKind::Mirror
+                } else if !same_uv(mesh, &pairs) {
+                    SeamKind::Uv
+                } else if hard {
+                    SeamKind::Hard
+                } else {
+                    SeamKind::Split
+                };
+                report.seams.push(SeamEdge {
+                    first,
+                    second,
+                    kind,
+                });
+            }
+        }
+    }
+    report
+        .seams
+        .sort_unstable_by_key(|seam| (seam.first, seam.second));
+
+    for (face, island) in islands_from(mesh, &edges).into_iter().enumerate() {
+        let index = match island {
+            Some(index) => index,
+            None => continue,
+        };
+        if index == report.islands.len() {
+            report.islands.push(UvIsland {
+                faces: Vec::new(),
+                preserving_corners: 0,
+                mirrored_corners: 0,
+            });
+        }
+        let island = &mut report.islands[index];
+        island.faces.push(face);
+        for vert in 0..mesh.get_num_vertices_of_face(face) {
+            if frames.sign(face, vert) > 0.0 {
+                island.preserving_corners += 1;
+                report.preserving_corners += 1;
+            } else {
+                island.mirrored_corners += 1;
+                report.mirrored_corners += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// UV island of every face, numbered in the order of their first face.
+/// `None` for faces that are neither triangles nor quads.
+pub(crate) fn face_islands<I>(mesh: &I) -> Vec<Option<usize>>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    islands_from(mesh, &edge_map(mesh))
+}
+
+fn islands_from<I>(mesh: &I, edges: &EdgeMap) -> Vec<Option<usize>>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let num_faces = mesh.get_num_faces();
+    let mut parents: Vec<usize> = (0..num_faces).collect();
+    for sides in edges.values() {
+        for (i, &first) in sides.iter().enumerate() {
+            for &second in &sides[i + 1..] {
+                if same_uv(mesh, &corner_pairs(mesh, first, second)) {
+                    union(&mut parents, first.0, second.0);
+                }
+            }
+        }
+    }
+    let mut numbers: HashMap<usize, usize> = HashMap::new();
+    (0..num_faces)
+        .map(|face| {
+            if num_verts(mesh, face) == 0 {
+                return None;
+            }
+            let root = find(&mut parents, face);
+            let next = numbers.len();
+            Some(*numbers.entry(root).or_insert(next))
+        })
+        .collect()
+}
+
+fn edge_map<I>(mesh: &I) -> EdgeMap
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let mut edges = EdgeMap::new();
+    for face in 0..mesh.get_num_faces() {
+        let n = num_verts(mesh, face);
+        for vert in 0..n {
+            let (a, b) = (
+                position_key(mesh, face, vert),
+                position_key(mesh, face, (vert + 1) % n),
+            );
+            let edge = if a <= b { (a, b) } else { (b, a) };
+            edges.entry(edge).or_default().push((face, vert));
+        }
+    }
+    edges
+}
+
+/// Number of corners of triangles and quads, zero for other faces.
+fn num_verts<I>(mesh: &I, face: usize) -> usize
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    match mesh.get_num_vertices_of_face(face) {
+        n @ 3..=4 => n,
+        _ => 0,
+    }
+}
+
+fn position_key<I>(mesh: &I, face: usize, vert: usize) -> [u32; 3]
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let p = mesh.get_position(face, vert);
+    // Adding zero turns -0.0 into 0.0.
+    [p[0] + 0.0, p[1] + 0.0, p[2] + 0.0].map(f32::to_bits)
+}
+
+type CornerPairs = [((usize, usize), (usize, usize)); 2];
+
+/// The corners of `first` and `second` at the two ends of their shared edge.
+fn corner_pairs<I>(mesh: &I, first: (usize, usize), second: (usize, usize)) -> CornerPairs
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let next =
+        |(face, vert): (usize, usize)| (face, (vert + 1) % mesh.get_num_vertices_of_face(face));
+    if position_key(mesh, first.0, first.1) == position_key(mesh, second.0, second.1) {
+        [(first, second), (next(first), next(second))]
+    } else {
+        [(first, next(second)), (next(first), second)]
+    }
+}
+
+fn same_uv<I>(mesh: &I, pairs: &CornerPairs) -> bool
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    pairs
+        .iter()
+        .all(|&(a, b)| mesh.get_tex_coord(a.0, a.1) == mesh.get_tex_coord(b.0, b.1))
+}
+
+fn find(parents: &mut [usize], mut face: usize) -> usize {
+    while parents[face] != face {
+        parents[face] = parents[parents[face]];
+        face = parents[face];
+    }
+    face
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    parents[b] = a;
+}
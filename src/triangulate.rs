@@ -0,0 +1,166 @@
+//! Quad triangulation following the recommendation in `mikktspace.h`.
+//!
+//! The tangent spaces at the corners don't depend on how quads are split, but
+//! the interpolated tangent space does. A normal map only renders without
+//! seams if the baker and the renderer split every quad along the same
+//! diagonal. The header suggests the shorter diagonal, and the shorter texture
+//! space diagonal if both have the same length, which doesn't depend on the
+//! order of the corners and works with mirroring.
+
+use super::MikkTSpaceInterface;
+
+/// Diagonal a quad is split along.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QuadSplit {
+    /// Corners 0 and 2, giving the triangles `[0, 1, 2]` and `[0, 2, 3]`.
+    Diagonal02,
+    /// Corners 1 and 3, giving the triangles `[0, 1, 3]` and `[1, 2, 3]`.
+    Diagonal13,
+}
+
+impl QuadSplit {
+    /// Picks the shorter of the two diagonals of a quad. If they have exactly
+    /// the same length the shorter texture space diagonal is picked, and
+    /// `Diagonal02` if those have the same length too.
+    pub fn shortest(positions: [[f32; 3]; 4], tex_coords: [[f32; 2]; 4]) -> Self {
+        let d02 = distance_squared(positions[0], positions[2]);
+        let d13 = distance_squared(positions[1], positions[3]);
+        let split_02 = if d02 != d13 {
+            d02 < d13
+        } else {
+            let t = |i: usize| [tex_coords[i][0], tex_coords[i][1], 0.0];
+            distance_squared(t(0), t(2)) <= distance_squared(t(1), t(3))
+        };
+        if split_02 {
+            QuadSplit::Diagonal02
+        } else {
+            QuadSplit::Diagonal13
+        }
+    }
+
+    /// The two triangles as corner indices of the quad, keeping its winding.
+    pub fn triangles(self) -> [[usize; 3]; 2] {
+        match self {
+            QuadSplit::Diagonal02 => [[0, 1, 2], [0, 2, 3]],
+            QuadSplit::Diagonal13 => [[0, 1, 3], [1, 2, 3]],
+        }
+    }
+}
+
+/// The split `QuadSplit::shortest` picks for `face`, `None` if it isn't a
+/// quad.
+pub fn split_quad<I>(mesh: &I, face: usize) -> Option<QuadSplit>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    if mesh.get_num_vertices_of_face(face) != 4 {
+        return None;
+    }
+    let positions = [0, 1, 2, 3].map(|vert| mesh.get_position(face, vert));
+    let tex_coords = [0, 1, 2, 3].map(|vert| mesh.get_tex_coord(face, vert));
+    Some(QuadSplit::shortest(positions, tex_coords))
+}
+
+/// Triangulates every face of `mesh`, splitting quads with `split_quad`.
+///
+/// Every triangle is given as three `(face, vert)` corners. Faces that are
+/// neither triangles nor quads are skipped, like the tangent space generation
+/// does.
+pub fn triangulate<I>(mesh: &I) -> Vec<[(usize, usize); 3]>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let mut triangles = Vec::with_capacity(mesh.get_num_faces() * 2);
+    for face in 0..mesh.get_num_faces() {
+        for tri in face_triangles(mesh, face) {
+            triangles.push([(face, tri[0]), (face, tri[1]), (face, tri[2])]);
+        }
+    }
+    triangles
+}
+
+/// A quad split differently than `split_quad` would.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplitMismatch {
+    pub face: usize,
+    pub expected: QuadSplit,
+    pub found: QuadSplit,
+}
+
+/// Compares the split of every quad of `mesh` as reported by `runtime` with
+/// the one of `split_quad` and returns the quads where they differ.
+///
+/// `runtime` is called with the face index of every quad.
+pub fn check_quad_splits<I, F>(mesh: &I, mut runtime: F) -> Vec<SplitMismatch>
+where
+    I: MikkTSpaceInterface + ?Sized,
+    F: FnMut(usize) -> QuadSplit,
+{
+    let mut mismatches = Vec::new();
+    for face in 0..mesh.get_num_faces() {
+        if let Some(expected) = split_quad(mesh, face) {
+            let found = runtime(face);
+            if found != expected {
+                mismatches.push(SplitMismatch {
+                    face,
+                    expected,
+                    found,
+                });
+            }
+        }
+    }
+    mismatches
+}
+
+/// Triangles of a face as corner indices.
+pub(crate) fn face_triangles<I>(mesh: &I, face: usize) -> Vec<[usize; 3]>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    match mesh.get_num_vertices_of_face(face) {
+        3 => vec![[0, 1, 2]],
+        4 => split_quad(mesh, face).map_or_else(Vec::new, |split| split.triangles().to_vec()),
+        _ => Vec::new(),
+    }
+}
+
+/// Triangles of a face as corner indices, with quads split like the
+/// generator splits them: along the shorter texture space diagonal, and the
+/// shorter diagonal if both have the same length.
+pub(crate) fn generator_triangles<I>(mesh: &I, face: usize) -> Vec<[usize; 3]>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    match mesh.get_num_vertices_of_face(face) {
+        3 => vec![[0, 1, 2]],
+        4 => {
+            // As in GenerateInitialVerticesIndexList() of mikktspace.c.
+            let t = |vert: usize| {
+                let uv = mesh.get_tex_coord(face, vert);
+                [uv[0], uv[1], 0.0]
+            };
+            let t02 = distance_squared(t(0), t(2));
+            let t13 = distance_squared(t(1), t(3));
+            let split_02 = if t02 != t13 {
+                t02 < t13
+            } else {
+                let p = |vert: usize| mesh.get_position(face, vert);
+                distance_squared(p(0), p(2)) <= distance_squared(p(1), p(3))
+            };
+            let split = if split_02 {
+                QuadSplit::Diagonal02
+            } else {
+                QuadSplit::Diagonal13
+            };
+            split.triangles().to_vec()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn distance_squared(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let d = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+    d[0] * d[0] + d[1] * d[1] + d[2] * d[2]
+}
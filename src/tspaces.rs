@@ -0,0 +1,154 @@
+//! Generated tangent spaces as plain data, for callers who would rather not
+//! implement `set_tspace`.
+
+use super::{gen_tang_space_ex, GenError, GenOptions, MikkTSpaceInterface, Progress};
+
+/// The values `set_tspace` receives for one corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TSpace {
+    pub tangent: [f32; 3],
+    pub bi_tangent: [f32; 3],
+    pub mag_s: f32,
+    pub mag_t: f32,
+    pub is_orientation_preserving: bool,
+    /// Whether the corner was passed to `report_fallback`.
+    pub is_fallback: bool,
+}
+
+impl Default for TSpace {
+    fn default() -> Self {
+        TSpace {
+            tangent: [0.0; 3],
+            bi_tangent: [0.0; 3],
+            mag_s: 0.0,
+            mag_t: 0.0,
+            is_orientation_preserving: true,
+            is_fallback: false,
+        }
+    }
+}
+
+/// Tangent spaces of every corner, see `gen_tang_space_collect`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TSpaces {
+    offsets: Vec<usize>,
+    corners: Vec<TSpace>,
+}
+
+impl TSpaces {
+    pub(crate) fn new<I>(mesh: &I) -> Self
+    where
+        I: MikkTSpaceInterface + ?Sized,
+    {
+        let mut offsets = Vec::with_capacity(mesh.get_num_faces());
+        let mut num_corners = 0;
+        for face in 0..mesh.get_num_faces() {
+            offsets.push(num_corners);
+            num_corners += mesh.get_num_vertices_of_face(face);
+        }
+        TSpaces {
+            offsets,
+            corners: vec![TSpace::default(); num_corners],
+        }
+    }
+
+    /// Tangent space of corner `vert` of `face`. Corners of faces that are
+    /// neither triangles nor quads keep `TSpace::default()`.
+    pub fn get(&self, face: usize, vert: usize) -> &TSpace {
+        &self.corners[self.index(face, vert)]
+    }
+
+    /// Index of corner `vert` of `face` in `corners`.
+    pub fn index(&self, face: usize, vert: usize) -> usize {
+        self.offsets[face] + vert
+    }
+
+    /// All corners, face by face.
+    pub fn corners(&self) -> &[TSpace] {
+        &self.corners
+    }
+
+    pub(crate) fn get_mut(&mut self, face: usize, vert: usize) -> &mut TSpace {
+        let index = self.index(face, vert);
+        &mut self.corners[index]
+    }
+}
+
+/// Generates tangent spaces like `gen_tang_space_ex` and returns them instead
+/// of calling `set_tspace`. Progress is still passed to `report_progress`.
+pub fn gen_tang_space_collect<I>(mesh: &mut I, options: &GenOptions) -> Result<TSpaces, GenError>
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let tspaces = TSpaces::new(mesh);
+    let mut capture = Capture { mesh, tspaces };
+    gen_tang_space_ex(&mut capture, options)?;
+    Ok(capture.tspaces)
+}
+
+/// Forwards the mesh attributes and records the results.
+struct Capture<'a, I: ?Sized> {
+    mesh: &'a mut I,
+    tspaces: TSpaces,
+}
+
+impl<'a, I: MikkTSpaceInterface + ?Sized> MikkTSpaceInterface for Capture<'a, I> {
+    fn get_num_faces(&self) -> usize {
+        self.mesh.get_num_faces()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.mesh.get_num_vertices_of_face(face)
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.get_position(face, vert)
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.get_normal(face, vert)
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.mesh.get_tex_coord(face, vert)
+    }
+
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        self.mesh.get_smoothing_group(face)
+    }
+
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        self.mesh.is_edge_sharp(face, vert)
+    }
+
+    fn report_progress(&mut self, progress: Progress) {
+        self.mesh.report_progress(progress);
+    }
+
+    fn report_fallback(&mut self, face: usize, vert: usize) {
+        self.tspaces.get_mut(face, vert).is_fallback = true;
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        let corner = self.tspaces.get_mut(face, vert);
+        *corner = TSpace {
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            is_fallback: corner.is_fallback,
+        };
+    }
+}
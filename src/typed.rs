@@ -0,0 +1,260 @@
+//! `MikkTSpaceInterface` with the vector types of the caller's math library.
+//!
+//! Implement `TypedInterface` with the `Vec3` and `Vec2` types of the mesh
+//! and wrap it in `Typed` wherever a `MikkTSpaceInterface` is expected, or
+//! call `gen_tang_space_typed`. Vectors are converted to and from arrays
+//! through `Vector3` and `Vector2`, which are implemented for arrays and,
+//! with the features of the same name, for `glam`, `nalgebra` and `mint`.
+
+use super::{gen_tang_space_ex, GenError, GenOptions, GenStats, MikkTSpaceInterface, Progress};
+
+/// A three component `f32` vector.
+pub trait Vector3: Copy {
+    fn from_array(v: [f32; 3]) -> Self;
+    fn to_array(self) -> [f32; 3];
+}
+
+/// A two component `f32` vector.
+pub trait Vector2: Copy {
+    fn from_array(v: [f32; 2]) -> Self;
+    fn to_array(self) -> [f32; 2];
+}
+
+/// `MikkTSpaceInterface` with `Vec3` and `Vec2` in place of `[f32; 3]` and
+/// `[f32; 2]`. The methods are the same, see there.
+#[allow(unused_variables, clippy::too_many_arguments)]
+pub trait TypedInterface {
+    type Vec3: Vector3;
+    type Vec2: Vector2;
+
+    fn get_num_faces(&self) -> usize;
+    fn get_num_vertices_of_face(&self, face: usize) -> usize;
+    fn get_position(&self, face: usize, vert: usize) -> Self::Vec3;
+    fn get_normal(&self, face: usize, vert: usize) -> Self::Vec3;
+    fn get_tex_coord(&self, face: usize, vert: usize) -> Self::Vec2;
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        u32::MAX
+    }
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        false
+    }
+    fn report_fallback(&mut self, face: usize, vert: usize) {}
+    fn report_progress(&mut self, progress: Progress) {}
+    fn set_normal(&mut self, normal: Self::Vec3, face: usize, vert: usize) {}
+    fn set_tspace_basic(&mut self, tangent: Self::Vec3, sign: f32, face: usize, vert: usize) {}
+    fn set_tspace(
+        &mut self,
+        tangent: Self::Vec3,
+        bi_tangent: Self::Vec3,
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+    }
+}
+
+impl<T: TypedInterface + ?Sized> TypedInterface for &mut T {
+    type Vec3 = T::Vec3;
+    type Vec2 = T::Vec2;
+
+    fn get_num_faces(&self) -> usize {
+        (**self).get_num_faces()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        (**self).get_num_vertices_of_face(face)
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> Self::Vec3 {
+        (**self).get_position(face, vert)
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> Self::Vec3 {
+        (**self).get_normal(face, vert)
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> Self::Vec2 {
+        (**self).get_tex_coord(face, vert)
+    }
+
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        (**self).get_smoothing_group(face)
+    }
+
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        (**self).is_edge_sharp(face, vert)
+    }
+
+    fn report_fallback(&mut self, face: usize, vert: usize) {
+        (**self).report_fallback(face, vert);
+    }
+
+    fn report_progress(&mut self, progress: Progress) {
+        (**self).report_progress(progress);
+    }
+
+    fn set_normal(&mut self, normal: Self::Vec3, face: usize, vert: usize) {
+        (**self).set_normal(normal, face, vert);
+    }
+
+    fn set_tspace_basic(&mut self, tangent: Self::Vec3, sign: f32, face: usize, vert: usize) {
+        (**self).set_tspace_basic(tangent, sign, face, vert);
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: Self::Vec3,
+        bi_tangent: Self::Vec3,
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        (**self).set_tspace(
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            face,
+            vert,
+        );
+    }
+}
+
+/// Implements `MikkTSpaceInterface` for a `TypedInterface`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Typed<T>(pub T);
+
+/// Generates tangent spaces like `gen_tang_space_ex` for a `TypedInterface`.
+pub fn gen_tang_space_typed<T>(mesh: &mut T, options: &GenOptions) -> Result<GenStats, GenError>
+where
+    T: TypedInterface,
+{
+    gen_tang_space_ex(&mut Typed(mesh), options)
+}
+
+impl<T: TypedInterface> MikkTSpaceInterface for Typed<T> {
+    fn get_num_faces(&self) -> usize {
+        self.0.get_num_faces()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.0.get_num_vertices_of_face(face)
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.0.get_position(face, vert).to_array()
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.0.get_normal(face, vert).to_array()
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.0.get_tex_coord(face, vert).to_array()
+    }
+
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        self.0.get_smoothing_group(face)
+    }
+
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        self.0.is_edge_sharp(face, vert)
+    }
+
+    fn report_fallback(&mut self, face: usize, vert: usize) {
+        self.0.report_fallback(face, vert);
+    }
+
+    fn report_progress(&mut self, progress: Progress) {
+        self.0.report_progress(progress);
+    }
+
+    fn set_normal(&mut self, normal: [f32; 3], face: usize, vert: usize) {
+        self.0.set_normal(T::Vec3::from_array(normal), face, vert);
+    }
+
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {
+        self.0
+            .set_tspace_basic(T::Vec3::from_array(tangent), sign, face, vert);
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.0.set_tspace(
+            T::Vec3::from_array(tangent),
+            T::Vec3::from_array(bi_tangent),
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            face,
+            vert,
+        );
+    }
+}
+
+impl Vector3 for [f32; 3] {
+    fn from_array(v: [f32; 3]) -> Self {
+        v
+    }
+
+    fn to_array(self) -> [f32; 3] {
+        self
+    }
+}
+
+impl Vector2 for [f32; 2] {
+    fn from_array(v: [f32; 2]) -> Self {
+        v
+    }
+
+    fn to_array(self) -> [f32; 2] {
+        self
+    }
+}
+
+/// Implements `Vector3` or `Vector2` for types with `From` conversions from
+/// and into arrays.
+#[cfg(any(feature = "glam", feature = "nalgebra", feature = "mint"))]
+macro_rules! impl_vector {
+    ($trait:ident, $n:literal, $($ty:ty),+) => {
+        $(
+            impl $trait for $ty {
+                fn from_array(v: [f32; $n]) -> Self {
+                    v.into()
+                }
+
+                fn to_array(self) -> [f32; $n] {
+                    self.into()
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "glam")]
+impl_vector!(Vector3, 3, glam::Vec3, glam::Vec3A);
+#[cfg(feature = "glam")]
+impl_vector!(Vector2, 2, glam::Vec2);
+
+#[cfg(feature = "nalgebra")]
+impl_vector!(Vector3, 3, nalgebra::Vector3<f32>, nalgebra::Point3<f32>);
+#[cfg(feature = "nalgebra")]
+impl_vector!(Vector2, 2, nalgebra::Vector2<f32>, nalgebra::Point2<f32>);
+
+#[cfg(feature = "mint")]
+impl_vector!(Vector3, 3, mint::Vector3<f32>, mint::Point3<f32>);
+#[cfg(feature = "mint")]
+impl_vector!(Vector2, 2, mint::Vector2<f32>, mint::Point2<f32>);
@@ -0,0 +1,291 @@
+//! Checks of generated tangent spaces against the promises of `mikktspace.h`.
+//!
+//! `m_setTSpace` returns unit length tangents and bitangents which are both
+//! perpendicular to the vertex normal, once for every corner of every
+//! triangle and quad. `gen_tang_space_validate` generates tangent spaces and
+//! checks every result against the mesh it was generated for, `validate`
+//! checks stored results.
+
+use super::normal_map::dot;
+use super::{
+    gen_tang_space_ex, GenError, GenOptions, MikkTSpaceInterface, Progress, TSpace, TSpaces,
+};
+
+/// Which of the returned vectors an issue is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Vector {
+    Tangent,
+    BiTangent,
+}
+
+/// A single problem found by `validate` or `gen_tang_space_validate`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Issue {
+    /// A component of the tangent, bitangent or magnitudes is NaN or
+    /// infinite.
+    NotFinite { face: usize, vert: usize },
+    /// The vector's length differs from 1 by more than the tolerance.
+    NotUnitLength {
+        face: usize,
+        vert: usize,
+        vector: Vector,
+        length: f32,
+    },
+    /// The cosine of the angle between the vector and the vertex normal
+    /// exceeds the tolerance.
+    NotPerpendicular {
+        face: usize,
+        vert: usize,
+        vector: Vector,
+        cosine: f32,
+    },
+    /// A corner of a triangle or quad received no tangent space. Only found
+    /// by `gen_tang_space_validate`.
+    Missing { face: usize, vert: usize },
+    /// A corner received `count` tangent spaces. Only found by
+    /// `gen_tang_space_validate`.
+    Repeated {
+        face: usize,
+        vert: usize,
+        count: usize,
+    },
+    /// The corners of the face aren't all orientation preserving or all
+    /// mirrored. See `GenOptions::uniform_orientation`.
+    MixedSigns { face: usize },
+}
+
+/// Result of `validate` and `gen_tang_space_validate`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationReport {
+    /// Number of corners of triangles and quads that were checked.
+    pub corners: usize,
+    /// Problems in face order, corner issues before face issues.
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks tangent spaces generated for `mesh` earlier, e.g. loaded from a
+/// cache or a file.
+///
+/// `tolerance` bounds both the deviation of the vector lengths from 1 and
+/// the cosine between each vector and the normalized vertex normal, `1e-4`
+/// leaves room for single precision rounding. Corners with a zero normal
+/// are not checked for perpendicularity.
+///
+/// Note that with `FallbackPolicy::Reference` corners without usable texture
+/// coordinates get vectors which aren't perpendicular to the normal, and
+/// without `uniform_orientation` degenerate faces may have mixed signs.
+///
+/// # Panics
+///
+/// If `tspaces` doesn't have as many corners as `mesh`.
+pub fn validate<I>(mesh: &I, tspaces: &TSpaces, tolerance: f32) -> ValidationReport
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let num_corners: usize = (0..mesh.get_num_faces())
+        .map(|face| mesh.get_num_vertices_of_face(face))
+        .sum();
+    assert_eq!(tspaces.corners().len(), num_corners);
+    check(mesh, tspaces, None, tolerance)
+}
+
+/// Generates tangent spaces like `gen_tang_space_ex` and checks the results
+/// like `validate`.
+///
+/// Results are passed to `interface` as usual. Additionally reports corners
+/// which received no tangent space or more than one.
+pub fn gen_tang_space_validate<I>(
+    interface: &mut I,
+    options: &GenOptions,
+    tolerance: f32,
+) -> Result<ValidationReport, GenError>
+where
+    I: MikkTSpaceInterface,
+{
+    let tspaces = TSpaces::new(interface);
+    let counts = vec![0; tspaces.corners().len()];
+    let mut validator = Validator {
+        interface,
+        tspaces,
+        counts,
+    };
+    gen_tang_space_ex(&mut validator, options)?;
+    Ok(check(
+        validator.interface,
+        &validator.tspaces,
+        Some(&validator.counts),
+        tolerance,
+    ))
+}
+
+/// Checks every corner of a triangle or quad. `counts` holds the number of
+/// tangent spaces each corner received, stored results have one each.
+fn check<I>(
+    mesh: &I,
+    tspaces: &TSpaces,
+    counts: Option<&[usize]>,
+    tolerance: f32,
+) -> ValidationReport
+where
+    I: MikkTSpaceInterface + ?Sized,
+{
+    let mut report = ValidationReport::default();
+    for face in 0..mesh.get_num_faces() {
+        let num_verts = mesh.get_num_vertices_of_face(face);
+        if num_verts != 3 && num_verts != 4 {
+            continue;
+        }
+        let mut signs = [false; 2];
+        for vert in 0..num_verts {
+            report.corners += 1;
+            let count = counts.map_or(1, |counts| counts[tspaces.index(face, vert)]);
+            match count {
+                0 => {
+                    report.issues.push(Issue::Missing { face, vert });
+                    continue;
+                }
+                1 => {}
+                count => report.issues.push(Issue::Repeated { face, vert, count }),
+            }
+            let corner = tspaces.get(face, vert);
+            signs[corner.is_orientation_preserving as usize] = true;
+            let finite = corner
+                .tangent
+                .iter()
+                .chain(corner.bi_tangent.iter())
+                .chain([corner.mag_s, corner.mag_t].iter())
+                .all(|x| x.is_finite());
+            if !finite {
+                report.issues.push(Issue::NotFinite { face, vert });
+                continue;
+            }
+            let normal = mesh.get_normal(face, vert);
+            let normal_length = length(normal);
+            for &(vector, v) in [
+                (Vector::Tangent, corner.tangent),
+                (Vector::BiTangent, corner.bi_tangent),
+            ]
+            .iter()
+            {
+                let length = length(v);
+                if (length - 1.0).abs() > tolerance {
+                    report.issues.push(Issue::NotUnitLength {
+                        face,
+                        vert,
+                        vector,
+                        length,
+                    });
+                }
+                if normal_length > 0.0 && length > 0.0 {
+                    let cosine = dot(v, normal) / (length * normal_length);
+                    if cosine.abs() > tolerance {
+                        report.issues.push(Issue::NotPerpendicular {
+                            face,
+                            vert,
+                            vector,
+                            cosine,
+                        });
+                    }
+                }
+            }
+        }
+        if signs[0] && signs[1] {
+            report.issues.push(Issue::MixedSigns { face });
+        }
+    }
+    report
+}
+
+/// Forwards everything to the wrapped interface and records the results.
+struct Validator<'a, I> {
+    interface: &'a mut I,
+    tspaces: TSpaces,
+    counts: Vec<usize>,
+}
+
+impl<'a, I: MikkTSpaceInterface> MikkTSpaceInterface for Validator<'a, I> {
+    fn get_num_faces(&self) -> usize {
+        self.interface.get_num_faces()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.interface.get_num_vertices_of_face(face)
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.interface.get_position(face, vert)
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.interface.get_normal(face, vert)
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.interface.get_tex_coord(face, vert)
+    }
+
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        self.interface.get_smoothing_group(face)
+    }
+
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        self.interface.is_edge_sharp(face, vert)
+    }
+
+    fn report_progress(&mut self, progress: Progress) {
+        self.interface.report_progress(progress);
+    }
+
+    fn report_fallback(&mut self, face: usize, vert: usize) {
+        self.tspaces.get_mut(face, vert).is_fallback = true;
+        self.interface.report_fallback(face, vert);
+    }
+
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], sign: f32, face: usize, vert: usize) {
+        self.interface.set_tspace_basic(tangent, sign, face, vert);
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.counts[self.tspaces.index(face, vert)] += 1;
+        let corner = self.tspaces.get_mut(face, vert);
+        *corner = TSpace {
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            is_fallback: corner.is_fallback,
+        };
+        self.interface.set_tspace(
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            face,
+            vert,
+        );
+    }
+}
+
+fn length(v: [f32; 3]) -> f32 {
+    dot(v, v).sqrt()
+}
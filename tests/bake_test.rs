@@ -0,0 +1,141 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+const SIZE: usize = 64;
+
+/// Flat square at height `z` covering the grid, facing `+z`.
+fn plane(z: f32) -> Recorder {
+    Recorder::new(Mesh {
+        faces: vec![vec![0, 1, 2, 3]],
+        positions: vec![
+            [-1.0, -1.0, z],
+            [2.0, -1.0, z],
+            [2.0, 2.0, z],
+            [-1.0, 2.0, z],
+        ],
+        normals: vec![[0.0, 0.0, 1.0]; 4],
+        tex_coords: vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+    })
+}
+
+fn bake(front_distance: f32) -> BakeOptions {
+    BakeOptions {
+        width: SIZE,
+        height: SIZE,
+        front_distance,
+        rear_distance: 0.0,
+    }
+}
+
+#[test]
+fn baked_normals_survive_the_sampler() {
+    let mut low = Recorder::new(make_grid(8));
+    let options = GenOptions::default();
+    let baked = bake_normal_map(&mut low, &plane(0.2), None, &options, &bake(0.5)).unwrap();
+    let object = tangent_to_object_space(&mut low, &options, &baked).unwrap();
+    // Texels above u = 0.5 aren't covered by the mirrored grid.
+    assert_eq!(baked.get(SIZE - 1, 0), [0.0, 0.0, 1.0]);
+    let mut covered = 0;
+    for y in 0..SIZE {
+        for x in 0..SIZE / 2 {
+            let n = object.get(x, y);
+            assert!((n[2] - 1.0).abs() < 1e-5, "{:?} at {} {}", n, x, y);
+            covered += 1;
+        }
+    }
+    assert_eq!(covered, SIZE * SIZE / 2);
+}
+
+#[test]
+fn misses_keep_the_low_poly_normal() {
+    let mut low = Recorder::new(make_grid(8));
+    let baked = bake_normal_map(
+        &mut low,
+        &plane(0.2),
+        None,
+        &GenOptions::default(),
+        &bake(0.05),
+    )
+    .unwrap();
+    assert!(baked.texels.iter().all(|&n| n == [0.0, 0.0, 1.0]));
+}
+
+#[test]
+fn baking_the_low_poly_mesh_is_nearly_flat() {
+    let mut low = Recorder::new(make_grid(8));
+    let high = Recorder::new(make_grid(8));
+    let baked =
+        bake_normal_map(&mut low, &high, None, &GenOptions::default(), &bake(0.01)).unwrap();
+    for n in baked.texels.iter() {
+        assert!(n[2] > 0.99, "{:?}", n);
+    }
+}
+
+#[test]
+fn rays_start_at_the_cage() {
+    let mut low = Recorder::new(make_grid(8));
+    let mut cage = Recorder::new(make_grid(8));
+    for p in &mut cage.mesh.positions {
+        p[2] += 0.5;
+    }
+    let options = GenOptions::default();
+    let along_normals = bake_normal_map(&mut low, &plane(0.2), None, &options, &bake(0.5)).unwrap();
+    // The front distance is ignored, the cage alone decides where rays start.
+    let from_cage =
+        bake_normal_map(&mut low, &plane(0.2), Some(&cage), &options, &bake(0.05)).unwrap();
+    assert!(from_cage.texels.iter().any(|&n| n != [0.0, 0.0, 1.0]));
+    for (a, b) in from_cage.texels.iter().zip(along_normals.texels.iter()) {
+        for i in 0..3 {
+            assert!((a[i] - b[i]).abs() < 1e-5, "{:?} {:?}", a, b);
+        }
+    }
+
+    // A cage below the high poly surface misses it.
+    for p in &mut cage.mesh.positions {
+        p[2] -= 0.4;
+    }
+    let below = bake_normal_map(&mut low, &plane(0.2), Some(&cage), &options, &bake(0.5)).unwrap();
+    assert!(below.texels.iter().all(|&n| n == [0.0, 0.0, 1.0]));
+}
+
+#[test]
+fn cage_must_match_the_low_poly_faces() {
+    let mut low = Recorder::new(make_grid(8));
+    let cage = Recorder::new(make_grid(4));
+    let error = bake_normal_map(
+        &mut low,
+        &plane(0.2),
+        Some(&cage),
+        &GenOptions::default(),
+        &bake(0.5),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error,
+        BakeError::CageFaceCount {
+            expected: 64,
+            found: 16
+        }
+    );
+
+    let mut cage = Recorder::new(make_grid(8));
+    cage.mesh.faces[5].pop();
+    let error = bake_normal_map(
+        &mut low,
+        &plane(0.2),
+        Some(&cage),
+        &GenOptions::default(),
+        &bake(0.5),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error,
+        BakeError::CageVertexCount {
+            face: 5,
+            expected: 4,
+            found: 3
+        }
+    );
+}
@@ -0,0 +1,161 @@
+#![cfg(feature = "bevy")]
+
+mod common;
+
+use bevy_mesh::{Indices, Mesh, MeshBuilder, SphereKind, SphereMeshBuilder, VertexAttributeValues};
+use common::*;
+use mikktspace_sys::*;
+
+fn sphere() -> Mesh {
+    SphereMeshBuilder::new(
+        1.0,
+        SphereKind::Uv {
+            sectors: 16,
+            stacks: 8,
+        },
+    )
+    .build()
+}
+
+fn float3(mesh: &Mesh, attribute: bevy_mesh::MeshVertexAttribute) -> Vec<[f32; 3]> {
+    match mesh.attribute(attribute) {
+        Some(VertexAttributeValues::Float32x3(values)) => values.clone(),
+        _ => panic!("missing {}", attribute.name),
+    }
+}
+
+fn tangents(mesh: &Mesh) -> Vec<[f32; 4]> {
+    match mesh.attribute(Mesh::ATTRIBUTE_TANGENT) {
+        Some(VertexAttributeValues::Float32x4(values)) => values.clone(),
+        _ => panic!("missing tangents"),
+    }
+}
+
+/// The triangles of `mesh` as a test mesh.
+fn to_common(mesh: &Mesh) -> common::Mesh {
+    let indices: Vec<u32> = mesh.indices().unwrap().iter().map(|i| i as u32).collect();
+    common::Mesh {
+        faces: indices.chunks(3).map(|tri| tri.to_vec()).collect(),
+        positions: float3(mesh, Mesh::ATTRIBUTE_POSITION),
+        normals: float3(mesh, Mesh::ATTRIBUTE_NORMAL),
+        tex_coords: match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            Some(VertexAttributeValues::Float32x2(values)) => values.clone(),
+            _ => panic!("missing uvs"),
+        },
+    }
+}
+
+/// Checks that every corner of `mesh` got the reference tangent.
+fn assert_reference(original: &Mesh, mesh: &Mesh) {
+    let mut expected = Recorder::new(to_common(original));
+    gen_tang_space_ex(&mut expected, &GenOptions::default()).unwrap();
+    let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+    let tangents = tangents(mesh);
+    assert_eq!(tangents.len(), mesh.count_vertices());
+    for output in &expected.outputs {
+        let tangent = tangents[indices[output.face * 3 + output.vert]];
+        assert_eq!([tangent[0], tangent[1], tangent[2]], output.tangent);
+        assert_eq!(tangent[3] > 0.0, output.is_orientation_preserving);
+    }
+}
+
+#[test]
+fn sphere_matches_reference() {
+    let original = sphere();
+    let mut mesh = original.clone();
+    generate_bevy_tangents(&mut mesh, &GenOptions::default()).unwrap();
+    assert_reference(&original, &mesh);
+    assert_eq!(
+        float3(&mesh, Mesh::ATTRIBUTE_POSITION)[..original.count_vertices()],
+        float3(&original, Mesh::ATTRIBUTE_POSITION)[..]
+    );
+}
+
+#[test]
+fn mirrored_corners_split_vertices() {
+    let mut mesh = sphere();
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ],
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 4]);
+    // The second triangle is mirrored in texture space.
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_UV_0,
+        vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [2.0, 0.0]],
+    );
+    mesh.insert_indices(Indices::U16(vec![0, 1, 2, 0, 2, 3]));
+    let original = mesh.clone();
+
+    generate_bevy_tangents(&mut mesh, &GenOptions::default()).unwrap();
+    // Vertices 0 and 2 are shared by both triangles.
+    assert_eq!(mesh.count_vertices(), 6);
+    assert!(matches!(mesh.indices(), Some(Indices::U16(_))));
+    assert_reference(&original, &mesh);
+    let signs: Vec<f32> = tangents(&mesh).iter().map(|t| t[3]).collect();
+    assert_eq!(signs, [1.0, 1.0, 1.0, -1.0, -1.0, -1.0]);
+}
+
+#[test]
+fn missing_tex_coords_are_reported() {
+    let mut mesh = sphere();
+    mesh.remove_attribute(Mesh::ATTRIBUTE_UV_0);
+    let error = generate_bevy_tangents(&mut mesh, &GenOptions::default()).unwrap_err();
+    assert!(matches!(
+        error,
+        BevyTangentError::MissingAttribute(name) if name == Mesh::ATTRIBUTE_UV_0.name
+    ));
+    assert!(!mesh.contains_attribute(Mesh::ATTRIBUTE_TANGENT));
+}
+
+#[test]
+fn attribute_lengths_must_match_positions() {
+    let mut mesh = sphere();
+    let mut normals = float3(&mesh, Mesh::ATTRIBUTE_NORMAL);
+    normals.pop();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    let error = generate_bevy_tangents(&mut mesh, &GenOptions::default()).unwrap_err();
+    assert!(matches!(
+        error,
+        BevyTangentError::AttributeLength { name, .. } if name == Mesh::ATTRIBUTE_NORMAL.name
+    ));
+
+    // Attributes that are only copied for split vertices are checked too.
+    let mut mesh = sphere();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0f32; 4]; 3]);
+    let error = generate_bevy_tangents(&mut mesh, &GenOptions::default()).unwrap_err();
+    assert!(matches!(
+        error,
+        BevyTangentError::AttributeLength { name, len: 3, .. } if name == Mesh::ATTRIBUTE_COLOR.name
+    ));
+    assert!(!mesh.contains_attribute(Mesh::ATTRIBUTE_TANGENT));
+}
+
+#[test]
+fn indices_must_be_in_bounds() {
+    let mut mesh = sphere();
+    let num_vertices = mesh.count_vertices();
+    let mut indices: Vec<u32> = mesh.indices().unwrap().iter().map(|i| i as u32).collect();
+    indices[4] = num_vertices as u32;
+    mesh.insert_indices(Indices::U32(indices));
+    let error = generate_bevy_tangents(&mut mesh, &GenOptions::default()).unwrap_err();
+    assert!(matches!(
+        error,
+        BevyTangentError::IndexOutOfBounds { index, num_vertices: n } if index == n && n == num_vertices
+    ));
+    assert!(!mesh.contains_attribute(Mesh::ATTRIBUTE_TANGENT));
+}
+
+#[test]
+fn morph_targets_are_rejected() {
+    let mut mesh = sphere();
+    mesh.set_morph_targets(Default::default());
+    let error = generate_bevy_tangents(&mut mesh, &GenOptions::default()).unwrap_err();
+    assert!(matches!(error, BevyTangentError::MorphTargets));
+    assert!(!mesh.contains_attribute(Mesh::ATTRIBUTE_TANGENT));
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "cache")]
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::*;
+use mikktspace_sys::*;
+
+fn cache_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("mikktspace-cache-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn hit_and_miss_match_direct_generation() {
+    let options = GenOptions {
+        fallback: FallbackPolicy::NormalBasis,
+        ..GenOptions::default()
+    };
+    let mut direct = Recorder::new(make_cube());
+    gen_tang_space_ex(&mut direct, &options).unwrap();
+    assert!(!direct.fallbacks.is_empty());
+
+    let dir = cache_dir("hit");
+    let cache = TangentCache::new(&dir).unwrap();
+    for &expected in &[CacheStatus::Miss, CacheStatus::Hit] {
+        let mut recorder = Recorder::new(make_cube());
+        assert_eq!(cache.generate(&mut recorder, &options).unwrap(), expected);
+        assert_eq!(recorder.outputs, direct.outputs);
+        assert_eq!(recorder.fallbacks, direct.fallbacks);
+        match expected {
+            CacheStatus::Miss => assert_eq!(recorder.progress, direct.progress),
+            CacheStatus::Hit => assert!(recorder.progress.is_empty()),
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn key_depends_on_options_and_attributes() {
+    let mesh = Recorder::new(make_grid(4));
+    let key = TangentCache::key(&mesh, &GenOptions::default());
+    assert_eq!(key, TangentCache::key(&mesh, &GenOptions::default()));
+    let options = GenOptions {
+        angular_threshold: 90.0,
+        ..GenOptions::default()
+    };
+    assert_ne!(key, TangentCache::key(&mesh, &options));
+    let options = GenOptions {
+        memory_limit: Some(1 << 20),
+        ..GenOptions::default()
+    };
+    assert_eq!(key, TangentCache::key(&mesh, &options));
+
+    let mut moved = Recorder::new(make_grid(4));
+    moved.mesh.tex_coords[0][0] += 0.5;
+    assert_ne!(key, TangentCache::key(&moved, &GenOptions::default()));
+}
+
+#[test]
+fn corrupt_entry_is_regenerated() {
+    let dir = cache_dir("corrupt");
+    let cache = TangentCache::new(&dir).unwrap();
+    let mut recorder = Recorder::new(make_grid(4));
+    cache
+        .generate(&mut recorder, &GenOptions::default())
+        .unwrap();
+    let path = cache.path(&TangentCache::key(&recorder, &GenOptions::default()));
+    let bytes = fs::read(&path).unwrap();
+    fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+
+    let mut again = Recorder::new(make_grid(4));
+    assert_eq!(
+        cache.generate(&mut again, &GenOptions::default()).unwrap(),
+        CacheStatus::Miss
+    );
+    assert_eq!(again.outputs, recorder.outputs);
+    assert_eq!(fs::read(&path).unwrap(), bytes);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn flipped_bits_fail_the_checksum() {
+    let dir = cache_dir("checksum");
+    let cache = TangentCache::new(&dir).unwrap();
+    let mut recorder = Recorder::new(make_grid(4));
+    cache
+        .generate(&mut recorder, &GenOptions::default())
+        .unwrap();
+    let path = cache.path(&TangentCache::key(&recorder, &GenOptions::default()));
+    let bytes = fs::read(&path).unwrap();
+    let mut corrupt = bytes.clone();
+    // The sign bit of the first tangent's x component.
+    corrupt[19] ^= 0x80;
+    fs::write(&path, &corrupt).unwrap();
+
+    let mut again = Recorder::new(make_grid(4));
+    assert_eq!(
+        cache.generate(&mut again, &GenOptions::default()).unwrap(),
+        CacheStatus::Miss
+    );
+    assert_eq!(again.outputs, recorder.outputs);
+    assert_eq!(fs::read(&path).unwrap(), bytes);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failed_writes_still_pass_the_tangent_spaces() {
+    let mut direct = Recorder::new(make_grid(4));
+    gen_tang_space_ex(&mut direct, &GenOptions::default()).unwrap();
+
+    let dir = cache_dir("unwritable");
+    let cache = TangentCache::new(&dir).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    let mut recorder = Recorder::new(make_grid(4));
+    match cache.generate(&mut recorder, &GenOptions::default()) {
+        Err(CacheError::Io(_)) => {}
+        result => panic!("{:?}", result),
+    }
+    assert_eq!(recorder.outputs, direct.outputs);
+}
+
+#[test]
+fn concurrent_writers_store_one_entry() {
+    let dir = cache_dir("concurrent");
+    let cache = TangentCache::new(&dir).unwrap();
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                let mut recorder = Recorder::new(make_grid(16));
+                cache
+                    .generate(&mut recorder, &GenOptions::default())
+                    .unwrap();
+            });
+        }
+    });
+    let entries: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
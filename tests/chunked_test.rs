@@ -0,0 +1,232 @@
+mod common;
+
+use std::cell::Cell;
+
+use common::*;
+use mikktspace_sys::*;
+
+/// Outputs and fallbacks sorted by corner.
+fn sorted(mut recorder: Recorder) -> (Vec<Output>, Vec<(usize, usize)>) {
+    recorder
+        .outputs
+        .sort_by_key(|output| (output.face, output.vert));
+    recorder.fallbacks.sort_unstable();
+    (recorder.outputs, recorder.fallbacks)
+}
+
+fn assert_matches_full_run(make_mesh: &dyn Fn() -> Mesh, options: &GenOptions) {
+    let mut full = Recorder::new(make_mesh());
+    gen_tang_space_ex(&mut full, options).unwrap();
+    let (outputs, fallbacks) = sorted(full);
+    for &max_faces in &[1, 2, 3, 7, 40, 1 << 20] {
+        let mut chunked = Recorder::new(make_mesh());
+        let chunk = ChunkOptions {
+            max_faces,
+            ..ChunkOptions::default()
+        };
+        let stats = gen_tang_space_chunked(&mut chunked, options, &chunk).unwrap();
+        assert!(stats.max_chunk_faces <= chunked.mesh.faces.len());
+        let (chunked_outputs, chunked_fallbacks) = sorted(chunked);
+        assert_eq!(chunked_outputs, outputs, "max_faces {}", max_faces);
+        assert_eq!(chunked_fallbacks, fallbacks, "max_faces {}", max_faces);
+    }
+}
+
+/// Deterministic pseudo random numbers in `[0, 1)`.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> f32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Grid with mirrored and collapsed texture coordinates, triangulated and
+/// degenerate faces, duplicated vertices and a pentagon.
+fn make_hard_mesh(seed: u64) -> Mesh {
+    let mut random = Random(seed);
+    let mut mesh = make_grid(8);
+    for tex_coord in &mut mesh.tex_coords {
+        let r = random.next();
+        if r < 0.2 {
+            // Collapsed texture coordinates chain up faces without usable
+            // derivatives.
+            *tex_coord = [0.25, 0.25];
+        } else if r < 0.3 {
+            tex_coord[0] = -tex_coord[0];
+        }
+    }
+    let faces = std::mem::take(&mut mesh.faces);
+    for face in faces {
+        let r = random.next();
+        if r < 0.4 {
+            mesh.faces.push(vec![face[0], face[1], face[2]]);
+            mesh.faces.push(vec![face[0], face[2], face[3]]);
+        } else if r < 0.45 {
+            // A copy of the first vertex with identical attributes.
+            let copy = mesh.positions.len() as u32;
+            let source = face[0] as usize;
+            mesh.positions.push(mesh.positions[source]);
+            mesh.normals.push(mesh.normals[source]);
+            mesh.tex_coords.push(mesh.tex_coords[source]);
+            mesh.faces.push(vec![copy, face[1], face[2], face[3]]);
+        } else if r < 0.5 {
+            mesh.faces.push(face.clone());
+            mesh.faces.push(vec![face[0], face[0], face[2]]);
+        } else if r < 0.52 {
+            mesh.faces
+                .push(vec![face[0], face[1], face[2], face[3], face[0]]);
+        } else {
+            mesh.faces.push(face);
+        }
+    }
+    mesh
+}
+
+/// Grid with a long, thin triangle of its own spanning it diagonally.
+fn make_grid_with_sliver(n: usize) -> Mesh {
+    let mut mesh = make_grid(n);
+    let first = mesh.positions.len() as u32;
+    for &(x, y) in &[(0.0, 0.0), (1.0, 1.0), (0.999, 1.0)] {
+        mesh.positions.push([x, y, 0.05]);
+        mesh.normals.push([0.0, 0.0, 1.0]);
+        mesh.tex_coords.push([x, y]);
+    }
+    mesh.faces.push(vec![first, first + 1, first + 2]);
+    mesh
+}
+
+/// Counts the attribute reads, which the chunked generator makes for every
+/// face it considers for an overlap.
+struct Counting {
+    mesh: Mesh,
+    reads: Cell<usize>,
+}
+
+impl MikkTSpaceInterface for Counting {
+    fn get_num_faces(&self) -> usize {
+        self.mesh.faces.len()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.mesh.faces[face].len()
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.positions[self.mesh.index(face, vert)]
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.normals[self.mesh.index(face, vert)]
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.reads.set(self.reads.get() + 1);
+        self.mesh.tex_coords[self.mesh.index(face, vert)]
+    }
+}
+
+fn count_reads(mesh: Mesh, chunk: &ChunkOptions) -> usize {
+    let mut counting = Counting {
+        mesh,
+        reads: Cell::new(0),
+    };
+    gen_tang_space_chunked(&mut counting, &GenOptions::default(), chunk).unwrap();
+    counting.reads.get()
+}
+
+#[test]
+fn matches_full_run() {
+    assert_matches_full_run(&make_cube, &GenOptions::default());
+    assert_matches_full_run(&|| make_grid(9), &GenOptions::default());
+}
+
+#[test]
+fn matches_full_run_on_hard_meshes() {
+    let options = [
+        GenOptions::default(),
+        GenOptions {
+            angular_threshold: 60.0,
+            uniform_orientation: true,
+            fallback: FallbackPolicy::NormalBasis,
+            ..GenOptions::default()
+        },
+    ];
+    for seed in 0..8 {
+        for options in &options {
+            assert_matches_full_run(&|| make_hard_mesh(seed), options);
+        }
+    }
+}
+
+#[test]
+fn chunks_own_at_most_max_faces() {
+    let mut mesh = Recorder::new(make_grid(10));
+    let chunk = ChunkOptions {
+        max_faces: 30,
+        ..ChunkOptions::default()
+    };
+    let stats = gen_tang_space_chunked(&mut mesh, &GenOptions::default(), &chunk).unwrap();
+    assert_eq!(stats.chunks, 4);
+    assert!(stats.max_chunk_faces < 100);
+    assert!(stats.peak_memory > 0);
+    assert_eq!(mesh.outputs.len(), 400);
+}
+
+#[test]
+fn long_faces_only_widen_their_own_search() {
+    assert_matches_full_run(&|| make_grid_with_sliver(9), &GenOptions::default());
+
+    let chunk = ChunkOptions {
+        max_faces: 16,
+        ..ChunkOptions::default()
+    };
+    let without = count_reads(make_grid(32), &chunk);
+    let with = count_reads(make_grid_with_sliver(32), &chunk);
+    assert!(
+        with < without * 2,
+        "{} reads, {} without the sliver",
+        with,
+        without
+    );
+}
+
+#[test]
+fn overlap_without_derivatives_is_limited_on_request() {
+    let make_mesh = || {
+        let mut mesh = make_grid(20);
+        for tex_coord in &mut mesh.tex_coords {
+            *tex_coord = [0.0, 0.0];
+        }
+        mesh
+    };
+    let mut full = Recorder::new(make_mesh());
+    gen_tang_space_ex(&mut full, &GenOptions::default()).unwrap();
+
+    // Every chunk runs on the whole mesh.
+    let mut chunk = ChunkOptions {
+        max_faces: 40,
+        ..ChunkOptions::default()
+    };
+    let mut unlimited = Recorder::new(make_mesh());
+    let stats = gen_tang_space_chunked(&mut unlimited, &GenOptions::default(), &chunk).unwrap();
+    assert_eq!(stats.chunks, 10);
+    assert_eq!(stats.max_chunk_faces, 400);
+    assert_eq!(sorted(unlimited), sorted(full));
+
+    chunk.max_overlap = Some(100);
+    let mut limited = Recorder::new(make_mesh());
+    assert_eq!(
+        gen_tang_space_chunked(&mut limited, &GenOptions::default(), &chunk),
+        Err(GenError::OverlapLimitExceeded { limit: 100 })
+    );
+    assert!(limited.outputs.is_empty());
+
+    let mut healthy = Recorder::new(make_grid(20));
+    let stats = gen_tang_space_chunked(&mut healthy, &GenOptions::default(), &chunk).unwrap();
+    assert!(stats.max_chunk_faces <= 140);
+}
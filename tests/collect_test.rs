@@ -0,0 +1,23 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+#[test]
+fn collected_tspaces_match_set_tspace() {
+    let mut recorder = Recorder::new(make_grid(8));
+    gen_tang_space_ex(&mut recorder, &GenOptions::default()).unwrap();
+    let tspaces = gen_tang_space_collect(&mut recorder, &GenOptions::default()).unwrap();
+    assert_eq!(tspaces.corners().len(), recorder.outputs.len());
+    for output in &recorder.outputs {
+        let tspace = tspaces.get(output.face, output.vert);
+        assert_eq!(tspace.tangent, output.tangent);
+        assert_eq!(tspace.bi_tangent, output.bi_tangent);
+        assert_eq!(tspace.mag_s, output.mag_s);
+        assert_eq!(tspace.mag_t, output.mag_t);
+        assert_eq!(
+            tspace.is_orientation_preserving,
+            output.is_orientation_preserving
+        );
+    }
+}
@@ -0,0 +1,209 @@
+#![allow(dead_code)]
+
+use mikktspace_sys::*;
+
+pub struct Mesh {
+    pub faces: Vec<Vec<u32>>,
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub tex_coords: Vec<[f32; 2]>,
+}
+
+impl Mesh {
+    pub fn index(&self, face: usize, vert: usize) -> usize {
+        self.faces[face][vert] as usize
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Output {
+    pub tangent: [f32; 3],
+    pub bi_tangent: [f32; 3],
+    pub mag_s: f32,
+    pub mag_t: f32,
+    pub is_orientation_preserving: bool,
+    pub face: usize,
+    pub vert: usize,
+}
+
+/// Mesh wrapper recording every `set_tspace` call in order.
+pub struct Recorder {
+    pub mesh: Mesh,
+    pub outputs: Vec<Output>,
+    pub fallbacks: Vec<(usize, usize)>,
+    /// Generated normals as `(face, vert, normal)` in call order.
+    pub normals: Vec<(usize, usize, [f32; 3])>,
+    pub progress: Vec<Progress>,
+}
+
+impl Recorder {
+    pub fn new(mesh: Mesh) -> Self {
+        Recorder {
+            mesh,
+            outputs: Vec::new(),
+            fallbacks: Vec::new(),
+            normals: Vec::new(),
+            progress: Vec::new(),
+        }
+    }
+}
+
+impl MikkTSpaceInterface for Recorder {
+    fn get_num_faces(&self) -> usize {
+        self.mesh.faces.len()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.mesh.faces[face].len()
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.positions[self.mesh.index(face, vert)]
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.normals[self.mesh.index(face, vert)]
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.mesh.tex_coords[self.mesh.index(face, vert)]
+    }
+
+    fn report_fallback(&mut self, face: usize, vert: usize) {
+        self.fallbacks.push((face, vert));
+    }
+
+    fn report_progress(&mut self, progress: Progress) {
+        self.progress.push(progress);
+    }
+
+    fn set_normal(&mut self, normal: [f32; 3], face: usize, vert: usize) {
+        self.normals.push((face, vert, normal));
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.outputs.push(Output {
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            face,
+            vert,
+        });
+    }
+}
+
+/// Cube with 4 triangles per side, same as the regression test.
+pub fn make_cube() -> Mesh {
+    // (uv, dir) per control point, five per side
+    let sides: [[([f32; 2], [f32; 3]); 5]; 6] = [
+        [
+            ([0.0, 0.0], [1.0, -1.0, 1.0]),
+            ([0.0, 1.0], [1.0, -1.0, -1.0]),
+            ([1.0, 1.0], [1.0, 1.0, -1.0]),
+            ([1.0, 0.0], [1.0, 1.0, 1.0]),
+            ([0.5, 0.5], [1.0, 0.0, 0.0]),
+        ],
+        [
+            ([1.0, 0.0], [-1.0, 1.0, 1.0]),
+            ([1.0, 1.0], [-1.0, 1.0, -1.0]),
+            ([0.0, 1.0], [-1.0, -1.0, -1.0]),
+            ([0.0, 0.0], [-1.0, -1.0, 1.0]),
+            ([0.5, 0.5], [-1.0, 0.0, 0.0]),
+        ],
+        [
+            ([0.0, 0.0], [1.0, 1.0, 1.0]),
+            ([0.0, 1.0], [1.0, 1.0, -1.0]),
+            ([0.0, 1.0], [-1.0, 1.0, -1.0]),
+            ([0.0, 0.0], [-1.0, 1.0, 1.0]),
+            ([0.0, 0.5], [0.0, 1.0, 0.0]),
+        ],
+        [
+            ([0.0, 0.0], [-1.0, -1.0, 1.0]),
+            ([0.0, 1.0], [-1.0, -1.0, -1.0]),
+            ([0.0, 1.0], [1.0, -1.0, -1.0]),
+            ([0.0, 0.0], [1.0, -1.0, 1.0]),
+            ([0.0, 0.5], [0.0, -1.0, 0.0]),
+        ],
+        [
+            ([0.0, 0.0], [-1.0, 1.0, 1.0]),
+            ([0.0, 1.0], [-1.0, -1.0, 1.0]),
+            ([1.0, 1.0], [1.0, -1.0, 1.0]),
+            ([1.0, 0.0], [1.0, 1.0, 1.0]),
+            ([0.5, 0.5], [0.0, 0.0, 1.0]),
+        ],
+        [
+            ([1.0, 0.0], [1.0, 1.0, -1.0]),
+            ([1.0, 1.0], [1.0, -1.0, -1.0]),
+            ([0.0, 1.0], [-1.0, -1.0, -1.0]),
+            ([0.0, 0.0], [-1.0, 1.0, -1.0]),
+            ([0.5, 0.5], [0.0, 0.0, -1.0]),
+        ],
+    ];
+
+    let mut mesh = Mesh {
+        faces: Vec::new(),
+        positions: Vec::new(),
+        normals: Vec::new(),
+        tex_coords: Vec::new(),
+    };
+    for side in sides.iter() {
+        let base = mesh.positions.len() as u32;
+        mesh.faces.push(vec![base, base + 1, base + 4]);
+        mesh.faces.push(vec![base + 1, base + 2, base + 4]);
+        mesh.faces.push(vec![base + 2, base + 3, base + 4]);
+        mesh.faces.push(vec![base + 3, base, base + 4]);
+        for &(uv, dir) in side.iter() {
+            let len = f32::sqrt(dir[0] * dir[0] + dir[1] * dir[1] + dir[2] * dir[2]);
+            mesh.positions
+                .push([dir[0] / 2.0, dir[1] / 2.0, dir[2] / 2.0]);
+            mesh.normals
+                .push([dir[0] / len, dir[1] / len, dir[2] / len]);
+            mesh.tex_coords.push(uv);
+        }
+    }
+    mesh
+}
+
+/// Bumpy `n` x `n` grid of quads with a mirrored UV seam down the middle.
+pub fn make_grid(n: usize) -> Mesh {
+    let mut mesh = Mesh {
+        faces: Vec::new(),
+        positions: Vec::new(),
+        normals: Vec::new(),
+        tex_coords: Vec::new(),
+    };
+    for y in 0..=n {
+        for x in 0..=n {
+            let (fx, fy) = (x as f32 / n as f32, y as f32 / n as f32);
+            let z = 0.1 * f32::sin(7.0 * fx) * f32::cos(5.0 * fy);
+            let (dx, dy) = (
+                0.7 * f32::cos(7.0 * fx) * f32::cos(5.0 * fy),
+                -0.5 * f32::sin(7.0 * fx) * f32::sin(5.0 * fy),
+            );
+            let len = f32::sqrt(dx * dx + dy * dy + 1.0);
+            mesh.positions.push([fx, fy, z]);
+            mesh.normals.push([-dx / len, -dy / len, 1.0 / len]);
+            let u = if fx > 0.5 { 1.0 - fx } else { fx };
+            mesh.tex_coords.push([u, fy]);
+        }
+    }
+    let row = n as u32 + 1;
+    for y in 0..n as u32 {
+        for x in 0..n as u32 {
+            let i = y * row + x;
+            mesh.faces.push(vec![i, i + 1, i + row + 1, i + row]);
+        }
+    }
+    mesh
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+/// Two separate quads. The first is 2 x 1 in object space and mapped onto
+/// the whole texture, the second is 1 x 1 and mapped onto a quarter of it.
+fn make_quads() -> Mesh {
+    Mesh {
+        faces: vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]],
+        positions: vec![
+            [0.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [0.0, 1.0, 1.0],
+        ],
+        normals: vec![[0.0, 0.0, 1.0]; 8],
+        tex_coords: vec![
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [1.0, 1.0],
+            [0.0, 1.0],
+            [0.0, 0.0],
+            [0.5, 0.0],
+            [0.5, 0.5],
+            [0.0, 0.5],
+        ],
+    }
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-5
+}
+
+#[test]
+fn face_metrics_follow_the_magnitudes() {
+    let mesh = Recorder::new(make_quads());
+    let report = texel_density_report(&mesh);
+    let stretched = report.faces[0].unwrap();
+    assert!(close(stretched.mag_s, 2.0) && close(stretched.mag_t, 1.0));
+    assert!(close(stretched.density, 1.0 / 2f32.sqrt()));
+    assert!(close(stretched.stretch, 2.0));
+    assert!(close(stretched.area, 2.0));
+    let uniform = report.faces[1].unwrap();
+    assert!(close(uniform.density, 0.5));
+    assert!(close(uniform.stretch, 1.0));
+}
+
+#[test]
+fn islands_and_summary() {
+    let mesh = Recorder::new(make_quads());
+    let report = texel_density_report(&mesh);
+    assert_eq!(report.islands.len(), 2);
+    assert_eq!(report.islands[0].faces, vec![0]);
+    assert!(close(report.islands[1].stretch.max, 1.0));
+    assert!(close(report.stretch.min, 1.0));
+    assert!(close(report.stretch.max, 2.0));
+    // Area weighted, the stretched quad is twice as large.
+    assert!(close(report.stretch.mean, (2.0 * 2.0 + 1.0) / 3.0));
+    assert!(report.stretch.std_dev > 0.0);
+}
+
+#[test]
+fn degenerate_texture_space_is_skipped() {
+    // Two sides of the cube have texture coordinates of zero area.
+    let mesh = Recorder::new(make_cube());
+    let report = texel_density_report(&mesh);
+    assert_eq!(report.faces.len(), 24);
+    assert!(report.faces[8..16].iter().all(Option::is_none));
+    let measured = report.faces.iter().flatten().count();
+    let in_islands: usize = report.islands.iter().map(|i| i.faces.len()).sum();
+    assert_eq!(measured, in_islands);
+    assert!(report.density.min > 0.0 && report.density.max.is_finite());
+}
+
+#[test]
+fn faces_keep_their_own_derivatives() {
+    // Two quads sharing an edge, the second is stretched twice as much along
+    // `s`. Their shared corners get the average, the faces must not.
+    let mut mesh = Recorder::new(Mesh {
+        faces: vec![vec![0, 1, 2, 3], vec![1, 4, 5, 2]],
+        positions: vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [3.0, 0.0, 0.0],
+            [3.0, 1.0, 0.0],
+        ],
+        normals: vec![[0.0, 0.0, 1.0]; 6],
+        tex_coords: vec![
+            [0.0, 0.0],
+            [0.5, 0.0],
+            [0.5, 1.0],
+            [0.0, 1.0],
+            [1.0, 0.0],
+            [1.0, 1.0],
+        ],
+    });
+    let report = texel_density_report(&mesh);
+    let first = report.faces[0].unwrap();
+    let second = report.faces[1].unwrap();
+    assert!(close(first.mag_s, 2.0) && close(first.mag_t, 1.0));
+    assert!(close(second.mag_s, 4.0) && close(second.mag_t, 1.0));
+
+    let tspaces = gen_tang_space_collect(&mut mesh, &GenOptions::default()).unwrap();
+    let shared = tspaces.get(0, 1);
+    assert!(shared.mag_s > 2.5 && shared.mag_s < 3.5);
+}
+
+#[test]
+fn quads_are_split_like_the_generator() {
+    // Non-planar, the 1-3 diagonal is shorter in object space and the 0-2
+    // diagonal in texture space.
+    let positions = vec![
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 2.0, 1.0],
+        [0.0, 1.0, 0.0],
+    ];
+    let tex_coords = vec![[0.0, 0.0], [1.0, 0.0], [0.8, 0.8], [0.0, 1.0]];
+    let quad = Recorder::new(Mesh {
+        faces: vec![vec![0, 1, 2, 3]],
+        positions: positions.clone(),
+        normals: vec![[0.0, 0.0, 1.0]; 4],
+        tex_coords: tex_coords.clone(),
+    });
+    assert_eq!(split_quad(&quad, 0), Some(QuadSplit::Diagonal13));
+    let split = Recorder::new(Mesh {
+        faces: vec![vec![0, 1, 2], vec![0, 2, 3]],
+        positions,
+        normals: vec![[0.0, 0.0, 1.0]; 4],
+        tex_coords,
+    });
+
+    let face = texel_density_report(&quad).faces[0].unwrap();
+    let halves = texel_density_report(&split).faces;
+    let (a, b) = (halves[0].unwrap(), halves[1].unwrap());
+    let weighted = |metric: fn(&FaceDensity) -> f32| {
+        (metric(&a) * a.area + metric(&b) * b.area) / (a.area + b.area)
+    };
+    assert!(close(face.area, a.area + b.area));
+    assert!(close(face.mag_s, weighted(|d| d.mag_s)));
+    assert!(close(face.mag_t, weighted(|d| d.mag_t)));
+}
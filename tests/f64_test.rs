@@ -0,0 +1,111 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+/// Grid moved far away from the origin, positions are only exact in `f64`.
+struct FarAway {
+    recorder: Recorder,
+    offset: [f64; 3],
+}
+
+impl MikkTSpaceInterface for FarAway {
+    fn get_num_faces(&self) -> usize {
+        self.recorder.get_num_faces()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.recorder.get_num_vertices_of_face(face)
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        let p = self.get_position_f64(face, vert);
+        [p[0] as f32, p[1] as f32, p[2] as f32]
+    }
+
+    fn get_position_f64(&self, face: usize, vert: usize) -> [f64; 3] {
+        let p = self.recorder.get_position(face, vert);
+        [
+            p[0] as f64 + self.offset[0],
+            p[1] as f64 + self.offset[1],
+            p[2] as f64 + self.offset[2],
+        ]
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.recorder.get_normal(face, vert)
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.recorder.get_tex_coord(face, vert)
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.recorder.set_tspace(
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            face,
+            vert,
+        );
+    }
+}
+
+fn max_difference(a: &[Output], b: &[Output]) -> f32 {
+    assert_eq!(a.len(), b.len());
+    let mut max = 0.0f32;
+    for (a, b) in a.iter().zip(b.iter()) {
+        assert_eq!((a.face, a.vert), (b.face, b.vert));
+        if a.is_orientation_preserving != b.is_orientation_preserving {
+            return f32::INFINITY;
+        }
+        for i in 0..3 {
+            max = max.max((a.tangent[i] - b.tangent[i]).abs());
+            max = max.max((a.bi_tangent[i] - b.bi_tangent[i]).abs());
+        }
+    }
+    max
+}
+
+#[test]
+fn f64_should_match_f32_on_well_scaled_meshes() {
+    for mesh in [make_cube, || make_grid(16)].iter() {
+        let mut single = Recorder::new(mesh());
+        gen_tang_space_ex(&mut single, &GenOptions::default()).unwrap();
+        let mut double = Recorder::new(mesh());
+        gen_tang_space_f64(&mut double, &GenOptions::default()).unwrap();
+        assert!(max_difference(&single.outputs, &double.outputs) < 1e-6);
+    }
+}
+
+#[test]
+fn f64_should_be_stable_far_away_from_origin() {
+    let mut reference = Recorder::new(make_grid(16));
+    gen_tang_space_f64(&mut reference, &GenOptions::default()).unwrap();
+
+    let offset = [3.0e6, -1.0e7, 2.5e6];
+    let mut single = FarAway {
+        recorder: Recorder::new(make_grid(16)),
+        offset,
+    };
+    gen_tang_space_ex(&mut single, &GenOptions::default()).unwrap();
+    assert!(max_difference(&reference.outputs, &single.recorder.outputs) > 0.1);
+
+    let mut double = FarAway {
+        recorder: Recorder::new(make_grid(16)),
+        offset,
+    };
+    gen_tang_space_f64(&mut double, &GenOptions::default()).unwrap();
+    assert!(max_difference(&reference.outputs, &double.recorder.outputs) < 1e-6);
+}
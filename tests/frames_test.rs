@@ -0,0 +1,63 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+#[test]
+fn draws_three_axes_per_corner() {
+    let mut recorder = Recorder::new(make_grid(4));
+    let tspaces = gen_tang_space_collect(&mut recorder, &GenOptions::default()).unwrap();
+    let segments = tangent_frames(&recorder, &tspaces, &FrameOptions::default());
+    assert_eq!(segments.len(), 4 * 4 * 4 * 3);
+
+    let (face, vert) = (5, 2);
+    let tspace = tspaces.get(face, vert);
+    let [tangent, bi_tangent, normal] =
+        [0, 1, 2].map(|axis| segments[(face * 4 + vert) * 3 + axis]);
+    assert_eq!(tangent.from, normal.from);
+    assert_eq!(bi_tangent.from, normal.from);
+    let direction = |segment: FrameSegment| {
+        let d = [0, 1, 2].map(|i| segment.to[i] - segment.from[i]);
+        let length = d.iter().map(|x| x * x).sum::<f32>().sqrt();
+        // A quarter of the grid spacing, scaled by 0.4.
+        assert!((length - 0.1).abs() < 0.02, "length {}", length);
+        d.map(|x| x / length)
+    };
+    for (axis, expected) in [(tangent, tspace.tangent), (bi_tangent, tspace.bi_tangent)] {
+        let d = direction(axis);
+        for i in 0..3 {
+            assert!((d[i] - expected[i]).abs() < 1e-4);
+        }
+    }
+    direction(normal);
+    // The origin lies between the corner and the face center.
+    let corner = recorder.get_position(face, vert);
+    assert_ne!(tangent.from, corner);
+}
+
+#[test]
+fn writes_ply_and_obj() {
+    let mut recorder = Recorder::new(make_cube());
+    let tspaces = gen_tang_space_collect(&mut recorder, &GenOptions::default()).unwrap();
+    let segments = tangent_frames(&recorder, &tspaces, &FrameOptions::default());
+
+    let mut ply = Vec::new();
+    write_frames_ply(&mut ply, &segments).unwrap();
+    let ply = String::from_utf8(ply).unwrap();
+    assert!(ply.contains(&format!("element vertex {}\n", segments.len() * 2)));
+    assert!(ply.contains(&format!("element edge {}\n", segments.len())));
+    let body = ply.split("end_header\n").nth(1).unwrap();
+    assert_eq!(body.lines().count(), segments.len() * 3);
+
+    let mut obj = Vec::new();
+    write_frames_obj(&mut obj, &segments).unwrap();
+    let obj = String::from_utf8(obj).unwrap();
+    assert_eq!(
+        obj.lines().filter(|l| l.starts_with("v ")).count(),
+        segments.len() * 2
+    );
+    assert_eq!(
+        obj.lines().filter(|l| l.starts_with("l ")).count(),
+        segments.len()
+    );
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts the allocations of the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+#[test]
+fn matches_one_shot_generation() {
+    let options = GenOptions {
+        angular_threshold: 1.0,
+        ..GenOptions::default()
+    };
+    let mut generator = TangentGenerator::new(options);
+    for mesh in [make_grid(8), make_cube(), make_grid(3), make_grid(12)] {
+        let mut expected = Recorder::new(mesh);
+        gen_tang_space_ex(&mut expected, &options).unwrap();
+
+        let mut recorder = Recorder::new(expected.mesh);
+        generator.generate(&mut recorder).unwrap();
+        assert_eq!(recorder.outputs, expected.outputs);
+    }
+}
+
+#[test]
+fn reuses_scratch_buffers() {
+    let mut generator = TangentGenerator::default();
+    let mut recorder = Recorder::new(make_grid(8));
+    recorder.outputs.reserve(8 * 8 * 4);
+    recorder.progress.reserve(1024);
+
+    generator.generate(&mut recorder).unwrap();
+    let capacity = generator.scratch_capacity();
+    assert!(capacity > 0);
+
+    for _ in 0..4 {
+        recorder.outputs.clear();
+        recorder.progress.clear();
+        let before = allocations();
+        generator.generate(&mut recorder).unwrap();
+        assert_eq!(allocations(), before);
+        assert_eq!(generator.scratch_capacity(), capacity);
+    }
+
+    // Smaller meshes fit into the existing buffers as well.
+    let mut small = Recorder::new(make_grid(4));
+    small.outputs.reserve(4 * 4 * 4);
+    small.progress.reserve(1024);
+    let before = allocations();
+    generator.generate(&mut small).unwrap();
+    assert_eq!(allocations(), before);
+}
+
+#[test]
+fn peak_memory_counts_scratch_capacity() {
+    let mut generator = TangentGenerator::default();
+    let mut recorder = Recorder::new(make_grid(8));
+    let stats = generator.generate(&mut recorder).unwrap();
+    let capacity = generator.scratch_capacity();
+    assert_eq!(stats.peak_memory, capacity);
+
+    // Buffers kept from earlier calls count as well.
+    let mut small = Recorder::new(make_grid(4));
+    assert_eq!(
+        generator.generate(&mut small).unwrap().peak_memory,
+        capacity
+    );
+
+    let mut exact = Recorder::new(make_grid(8));
+    let one_shot = gen_tang_space_ex(&mut exact, &GenOptions::default()).unwrap();
+    assert!(one_shot.peak_memory < capacity);
+}
+
+#[test]
+fn memory_limit_bounds_scratch_capacity() {
+    let mut unlimited = Recorder::new(make_grid(8));
+    let peak = gen_tang_space_ex(&mut unlimited, &GenOptions::default())
+        .unwrap()
+        .peak_memory;
+    for &limit in &[peak / 2, peak, peak + peak / 2, 2 * peak] {
+        let mut generator = TangentGenerator::default();
+        // Fill the pool with buffers of another shape first.
+        generator
+            .generate(&mut Recorder::new(make_grid(3)))
+            .unwrap();
+        generator.set_options(GenOptions {
+            memory_limit: Some(limit),
+            ..GenOptions::default()
+        });
+        let mut recorder = Recorder::new(make_grid(8));
+        match generator.generate(&mut recorder) {
+            Ok(stats) => {
+                assert!(stats.peak_memory <= limit);
+                assert_eq!(recorder.outputs, unlimited.outputs);
+            }
+            Err(GenError::MemoryLimitExceeded { .. }) => assert!(limit < 2 * peak),
+            Err(error) => panic!("unexpected error {}", error),
+        }
+        assert!(generator.scratch_capacity() <= limit);
+    }
+}
@@ -0,0 +1,112 @@
+//! Golden hashes of the generated tangent space bits.
+//!
+//! The inputs only use correctly rounded arithmetic, so the hashes pin the
+//! output of the C build alone. A mismatch means its floating point semantics
+//! changed.
+
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+/// FNV-1a over the raw bits of every output in call order.
+fn hash(outputs: &[Output]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let mut mix = |word: u32| {
+        for byte in word.to_le_bytes().iter() {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+    for output in outputs {
+        for x in output.tangent.iter().chain(output.bi_tangent.iter()) {
+            mix(x.to_bits());
+        }
+        mix(output.mag_s.to_bits());
+        mix(output.mag_t.to_bits());
+        mix(output.is_orientation_preserving as u32);
+        mix(output.face as u32);
+        mix(output.vert as u32);
+    }
+    hash
+}
+
+/// `n` x `n` curved grid with mirrored texture coordinates like `make_grid`,
+/// but built from polynomials instead of `sin` and `cos`, whose results
+/// differ between math libraries.
+fn make_exact_grid(n: usize) -> Mesh {
+    let mut mesh = Mesh {
+        faces: Vec::new(),
+        positions: Vec::new(),
+        normals: Vec::new(),
+        tex_coords: Vec::new(),
+    };
+    for y in 0..=n {
+        for x in 0..=n {
+            let (fx, fy) = (x as f32 / n as f32, y as f32 / n as f32);
+            let z = 2.0 * fx * (1.0 - fx) * (fy - 0.5);
+            let (dx, dy) = (2.0 * (1.0 - 2.0 * fx) * (fy - 0.5), 2.0 * fx * (1.0 - fx));
+            let len = f32::sqrt(dx * dx + dy * dy + 1.0);
+            mesh.positions.push([fx, fy, z]);
+            mesh.normals.push([-dx / len, -dy / len, 1.0 / len]);
+            let u = if fx > 0.5 { 1.0 - fx } else { fx };
+            mesh.tex_coords.push([u, fy]);
+        }
+    }
+    let row = n as u32 + 1;
+    for y in 0..n as u32 {
+        for x in 0..n as u32 {
+            let i = y * row + x;
+            mesh.faces.push(vec![i, i + 1, i + row + 1, i + row]);
+        }
+    }
+    mesh
+}
+
+fn generate(mesh: Mesh, options: &GenOptions) -> u64 {
+    let mut recorder = Recorder::new(mesh);
+    gen_tang_space_ex(&mut recorder, options).unwrap();
+    hash(&recorder.outputs)
+}
+
+fn generate_f64(mesh: Mesh, options: &GenOptions) -> u64 {
+    let mut recorder = Recorder::new(mesh);
+    gen_tang_space_f64(&mut recorder, options).unwrap();
+    hash(&recorder.outputs)
+}
+
+#[test]
+fn golden_cube() {
+    assert_eq!(
+        generate(make_cube(), &GenOptions::default()),
+        0x6233_1455_0408_e7b5
+    );
+}
+
+#[test]
+fn golden_grid() {
+    assert_eq!(
+        generate(make_exact_grid(16), &GenOptions::default()),
+        0x9b7f_a2e4_ee77_0625
+    );
+}
+
+#[test]
+fn golden_grid_angular_threshold() {
+    let options = GenOptions {
+        angular_threshold: 1.0,
+        ..GenOptions::default()
+    };
+    assert_eq!(
+        generate(make_exact_grid(16), &options),
+        0x3e0b_477e_64f1_a462
+    );
+}
+
+#[test]
+fn golden_grid_f64() {
+    assert_eq!(
+        generate_f64(make_exact_grid(16), &GenOptions::default()),
+        0x4202_affe_2c16_b405
+    );
+}
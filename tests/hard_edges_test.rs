@@ -0,0 +1,103 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+const N: usize = 8;
+
+struct HardEdges {
+    recorder: Recorder,
+    sharp_column: Option<usize>,
+    split_smoothing_groups: bool,
+}
+
+impl MikkTSpaceInterface for HardEdges {
+    fn get_num_faces(&self) -> usize {
+        self.recorder.get_num_faces()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.recorder.get_num_vertices_of_face(face)
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.recorder.get_position(face, vert)
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.recorder.get_normal(face, vert)
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.recorder.get_tex_coord(face, vert)
+    }
+
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        if self.split_smoothing_groups && face % N >= 3 {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn is_edge_sharp(&self, face: usize, vert: usize) -> bool {
+        // vertex 1 to 2 is the right hand edge of every grid quad
+        Some(face % N) == self.sharp_column && vert == 1
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.recorder.set_tspace(
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            face,
+            vert,
+        );
+    }
+}
+
+fn tangents_across_column_two(sharp_column: Option<usize>, split_smoothing_groups: bool) -> bool {
+    let mut mesh = HardEdges {
+        recorder: Recorder::new(make_grid(N)),
+        sharp_column,
+        split_smoothing_groups,
+    };
+    assert!(gen_tang_space_default(&mut mesh));
+
+    // top right corner of quad (2, 3) welds with the top left corner of quad (3, 3)
+    let left = 3 * N + 2;
+    let right = 3 * N + 3;
+    let outputs = &mesh.recorder.outputs;
+    let a = outputs
+        .iter()
+        .find(|o| o.face == left && o.vert == 2)
+        .unwrap();
+    let b = outputs
+        .iter()
+        .find(|o| o.face == right && o.vert == 3)
+        .unwrap();
+    a.tangent == b.tangent && a.bi_tangent == b.bi_tangent
+}
+
+#[test]
+fn sharp_edges_should_split_tangent_spaces() {
+    assert!(tangents_across_column_two(None, false));
+    assert!(tangents_across_column_two(Some(5), false));
+    assert!(!tangents_across_column_two(Some(2), false));
+}
+
+#[test]
+fn disjoint_smoothing_groups_should_split_tangent_spaces() {
+    assert!(!tangents_across_column_two(None, true));
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+const N: usize = 8;
+
+fn inspect(mesh: Mesh, options: &GenOptions) -> (TSpaceInspection, Recorder) {
+    let mut recorder = Recorder::new(mesh);
+    let inspection = gen_tang_space_inspect(&mut recorder, options).unwrap();
+    (inspection, recorder)
+}
+
+/// Corner of grid quad `(x, y)` at its lower left or lower right vertex.
+fn grid_corner(x: usize, y: usize, right: bool) -> (usize, usize) {
+    (y * N + x, right as usize)
+}
+
+#[test]
+fn output_is_unchanged() {
+    let (_, recorder) = inspect(make_grid(N), &GenOptions::default());
+    let mut expected = Recorder::new(make_grid(N));
+    assert!(gen_tang_space_default(&mut expected));
+    assert_eq!(recorder.outputs, expected.outputs);
+}
+
+#[test]
+fn explains_grid_corners() {
+    let (inspection, recorder) = inspect(make_grid(N), &GenOptions::default());
+    let mesh = &recorder;
+
+    let interior = inspection.explain(mesh, grid_corner(1, 2, true), grid_corner(2, 2, false));
+    assert_eq!(interior, TSpaceDifference::Shared);
+    let assignments = inspection.corner(1 + 2 * N, 1);
+    assert_eq!(assignments.len(), 1);
+    assert_eq!(
+        assignments[0].faces,
+        vec![1 + N, 2 + N, 1 + 2 * N, 2 + 2 * N]
+    );
+
+    let seam = inspection.explain(mesh, grid_corner(3, 2, true), grid_corner(4, 2, false));
+    assert_eq!(seam, TSpaceDifference::OppositeOrientation);
+
+    let apart = inspection.explain(mesh, grid_corner(1, 2, false), grid_corner(1, 2, true));
+    assert_eq!(
+        apart,
+        TSpaceDifference::NotWelded {
+            position: true,
+            normal: true,
+            tex_coord: true
+        }
+    );
+}
+
+#[test]
+fn explains_angular_threshold() {
+    let options = GenOptions {
+        angular_threshold: 1.0,
+        ..GenOptions::default()
+    };
+    let (inspection, recorder) = inspect(make_grid(N), &options);
+    let split = (1..N).any(|y| {
+        (1..N / 2).any(|x| {
+            inspection.explain(
+                &recorder,
+                grid_corner(x - 1, y, true),
+                grid_corner(x, y, false),
+            ) == TSpaceDifference::AngularThreshold
+        })
+    });
+    assert!(split);
+}
+
+#[test]
+fn explains_disconnected_and_ungrouped_corners() {
+    // Two triangles touching in a single vertex.
+    let bow_tie = Mesh {
+        faces: vec![vec![0, 1, 2], vec![0, 3, 4]],
+        positions: vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [-1.0, -1.0, 0.0],
+        ],
+        normals: vec![[0.0, 0.0, 1.0]; 5],
+        tex_coords: vec![[0.5, 0.5], [1.0, 0.5], [1.0, 1.0], [0.0, 0.5], [0.0, 0.0]],
+    };
+    let (inspection, recorder) = inspect(bow_tie, &GenOptions::default());
+    assert_eq!(
+        inspection.explain(&recorder, (0, 0), (1, 0)),
+        TSpaceDifference::Disconnected
+    );
+
+    // Two sides of the cube have texture coordinates without area.
+    let (inspection, recorder) = inspect(make_cube(), &GenOptions::default());
+    assert!(inspection.corner(8, 2).is_empty());
+    assert_eq!(
+        inspection.explain(&recorder, (8, 2), (9, 2)),
+        TSpaceDifference::Ungrouped {
+            first: true,
+            second: true
+        }
+    );
+}
+
+#[test]
+fn lists_only_contributing_faces() {
+    // Row 2 of the grid gets texture coordinates without area, its triangles
+    // join the groups of row 1 without adding to their tangent spaces.
+    let mut mesh = make_grid(N);
+    for x in 0..=N {
+        mesh.tex_coords[3 * (N + 1) + x][1] = 2.0 / N as f32;
+    }
+    let (inspection, _) = inspect(mesh, &GenOptions::default());
+    let below = inspection.corner(1 + N, 2);
+    let above = inspection.corner(1 + 2 * N, 1);
+    assert!(!below.is_empty() && !above.is_empty());
+    for assignment in below.iter().chain(above) {
+        assert_eq!(assignment.subgroup, below[0].subgroup);
+        assert_eq!(assignment.faces, vec![1 + N, 2 + N]);
+    }
+}
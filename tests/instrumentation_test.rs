@@ -0,0 +1,35 @@
+#![cfg(feature = "instrumentation")]
+
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+#[test]
+fn phase_counts() {
+    let mut recorder = Recorder::new(make_cube());
+    let stats = gen_tang_space_ex(&mut recorder, &GenOptions::default()).unwrap();
+    assert_eq!(stats.phase(Phase::Weld).count, 24);
+    assert_eq!(stats.phase(Phase::Degenerates).count, 0);
+    assert_eq!(stats.phase(Phase::TriInfo).count, 24);
+    assert_eq!(stats.phase(Phase::DegenEpilogue).count, 0);
+    // One group per vertex of the four sides with usable texture
+    // coordinates, the other two sides group with anything.
+    assert_eq!(stats.phase(Phase::Groups).count, 20);
+    assert_eq!(stats.phase(Phase::TSpaces).count, 20);
+}
+
+#[test]
+fn degenerate_triangles_are_counted() {
+    let mut mesh = make_grid(4);
+    let last = mesh.faces.len() - 1;
+    let first = mesh.faces[last][0];
+    mesh.faces[last] = vec![first, first, first];
+
+    let mut recorder = Recorder::new(mesh);
+    let stats = TangentGenerator::default().generate(&mut recorder).unwrap();
+    assert_eq!(stats.phase(Phase::Weld).count, 4 * 4 * 2 - 1);
+    assert_eq!(stats.phase(Phase::Degenerates).count, 1);
+    assert_eq!(stats.phase(Phase::TriInfo).count, 4 * 4 * 2 - 2);
+    assert_eq!(stats.phase(Phase::DegenEpilogue).count, 1);
+}
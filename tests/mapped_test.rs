@@ -0,0 +1,91 @@
+#![cfg(feature = "mmap")]
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use common::*;
+use mikktspace_sys::*;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("mikktspace-mapped-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// `make_grid` split into triangles.
+fn make_triangles() -> Mesh {
+    let mut mesh = make_grid(12);
+    let faces = std::mem::take(&mut mesh.faces);
+    for face in faces {
+        mesh.faces.push(vec![face[0], face[1], face[2]]);
+        mesh.faces.push(vec![face[0], face[2], face[3]]);
+    }
+    mesh
+}
+
+fn write_files(mesh: &Mesh, dir: &Path) -> (PathBuf, PathBuf) {
+    let mut vertices = Vec::new();
+    for i in 0..mesh.positions.len() {
+        let values = mesh.positions[i]
+            .iter()
+            .chain(&mesh.normals[i])
+            .chain(&mesh.tex_coords[i]);
+        for value in values {
+            vertices.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    let mut indices = Vec::new();
+    for index in mesh.faces.iter().flatten() {
+        indices.extend_from_slice(&index.to_le_bytes());
+    }
+    let paths = (dir.join("vertices.bin"), dir.join("indices.bin"));
+    fs::write(&paths.0, vertices).unwrap();
+    fs::write(&paths.1, indices).unwrap();
+    paths
+}
+
+#[test]
+fn chunked_generation_writes_tangent_file() {
+    let mut expected = Recorder::new(make_triangles());
+    gen_tang_space_ex(&mut expected, &GenOptions::default()).unwrap();
+
+    let dir = temp_dir("generate");
+    let (vertices, indices) = write_files(&expected.mesh, &dir);
+    let tangents = dir.join("tangents.bin");
+    let mut mesh = unsafe { MappedMesh::open(&vertices, &indices, &tangents) }.unwrap();
+    assert_eq!(mesh.num_triangles(), expected.mesh.faces.len());
+    let chunk = ChunkOptions {
+        max_faces: 50,
+        ..ChunkOptions::default()
+    };
+    let stats = gen_tang_space_chunked(&mut mesh, &GenOptions::default(), &chunk).unwrap();
+    assert_eq!(stats.chunks, 6);
+    mesh.flush().unwrap();
+    drop(mesh);
+
+    let bytes = fs::read(&tangents).unwrap();
+    assert_eq!(bytes.len(), expected.outputs.len() * 16);
+    let value = |i: usize| f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+    for output in &expected.outputs {
+        let offset = (output.face * 3 + output.vert) * 16;
+        let tangent = [value(offset), value(offset + 4), value(offset + 8)];
+        assert_eq!(tangent, output.tangent);
+        assert_eq!(value(offset + 12) > 0.0, output.is_orientation_preserving);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rejects_out_of_bounds_indices() {
+    let mut mesh = make_triangles();
+    mesh.faces[7][2] = mesh.positions.len() as u32;
+    let dir = temp_dir("bounds");
+    let (vertices, indices) = write_files(&mesh, &dir);
+    let error = unsafe { MappedMesh::open(&vertices, &indices, dir.join("tangents.bin")) };
+    assert_eq!(error.err().unwrap().kind(), std::io::ErrorKind::InvalidData);
+    fs::remove_dir_all(&dir).unwrap();
+}
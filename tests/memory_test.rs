@@ -0,0 +1,100 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+fn reference(mesh: Mesh) -> Vec<Output> {
+    let mut recorder = Recorder::new(mesh);
+    assert!(gen_tang_space_default(&mut recorder));
+    recorder.outputs
+}
+
+fn with_limit(mesh: Mesh, memory_limit: Option<usize>) -> (Result<GenStats, GenError>, Recorder) {
+    with_options(
+        mesh,
+        &GenOptions {
+            memory_limit,
+            ..GenOptions::default()
+        },
+    )
+}
+
+fn with_options(mesh: Mesh, options: &GenOptions) -> (Result<GenStats, GenError>, Recorder) {
+    let mut recorder = Recorder::new(mesh);
+    (gen_tang_space_ex(&mut recorder, options), recorder)
+}
+
+#[test]
+fn peak_memory_is_reported() {
+    let (small, _) = with_limit(make_grid(4), None);
+    let (large, recorder) = with_limit(make_grid(32), None);
+    assert!(small.unwrap().peak_memory > 0);
+    assert!(large.unwrap().peak_memory > small.unwrap().peak_memory);
+    assert_eq!(recorder.outputs, reference(make_grid(32)));
+}
+
+#[test]
+fn limit_at_peak_succeeds() {
+    let (stats, _) = with_limit(make_grid(16), None);
+    let peak = stats.unwrap().peak_memory;
+    let (result, recorder) = with_limit(make_grid(16), Some(peak));
+    assert_eq!(result.unwrap().peak_memory, peak);
+    assert_eq!(recorder.outputs, reference(make_grid(16)));
+}
+
+#[test]
+fn exceeding_the_limit_fails_cleanly() {
+    let (result, recorder) = with_limit(make_grid(16), Some(1024));
+    match result {
+        Err(GenError::MemoryLimitExceeded { limit, requested }) => {
+            assert_eq!(limit, 1024);
+            assert!(requested > 0);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(recorder.outputs.is_empty());
+}
+
+#[test]
+fn limits_below_the_peak_fail() {
+    let (stats, _) = with_limit(make_grid(8), None);
+    let peak = stats.unwrap().peak_memory;
+    for step in 1..64 {
+        let limit = peak - peak * step / 64;
+        match with_limit(make_grid(8), Some(limit)) {
+            (Err(GenError::MemoryLimitExceeded { .. }), recorder) => {
+                assert!(recorder.outputs.is_empty());
+            }
+            (result, _) => panic!("unexpected result {:?} for limit {}", result, limit),
+        }
+    }
+}
+
+#[test]
+fn tight_limits_fall_back_or_match_the_reference() {
+    let expected = reference(make_grid(8));
+    let (stats, _) = with_limit(make_grid(8), None);
+    let peak = stats.unwrap().peak_memory;
+    let mut below_peak = 0;
+    for step in 1..64 {
+        let limit = peak - peak * step / 64;
+        let options = GenOptions {
+            memory_limit: Some(limit),
+            quadratic_fallback: true,
+            ..GenOptions::default()
+        };
+        match with_options(make_grid(8), &options) {
+            (Ok(stats), recorder) => {
+                assert!(stats.peak_memory <= limit);
+                assert_eq!(recorder.outputs, expected);
+                below_peak += 1;
+            }
+            (Err(GenError::MemoryLimitExceeded { .. }), recorder) => {
+                assert!(recorder.outputs.is_empty());
+            }
+            (Err(error), _) => panic!("unexpected error {}", error),
+        }
+    }
+    // Some limits are only met by the allocation free welding paths.
+    assert!(below_peak > 0);
+}
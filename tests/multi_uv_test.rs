@@ -0,0 +1,155 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+struct TwoSets {
+    mesh: Mesh,
+    detail_tex_coords: Vec<[f32; 2]>,
+    outputs: [Vec<Output>; 2],
+    fallbacks: [Vec<(usize, usize)>; 2],
+    progress: [Vec<Progress>; 2],
+}
+
+impl MikkTSpaceMultiInterface for TwoSets {
+    fn get_num_faces(&self) -> usize {
+        self.mesh.faces.len()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.mesh.faces[face].len()
+    }
+
+    fn get_num_tex_coord_sets(&self) -> usize {
+        2
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.positions[self.mesh.index(face, vert)]
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.normals[self.mesh.index(face, vert)]
+    }
+
+    fn get_tex_coord(&self, set: usize, face: usize, vert: usize) -> [f32; 2] {
+        match set {
+            0 => self.mesh.tex_coords[self.mesh.index(face, vert)],
+            _ => self.detail_tex_coords[self.mesh.index(face, vert)],
+        }
+    }
+
+    fn report_progress(&mut self, set: usize, progress: Progress) {
+        self.progress[set].push(progress);
+    }
+
+    fn report_fallback(&mut self, set: usize, face: usize, vert: usize) {
+        self.fallbacks[set].push((face, vert));
+    }
+
+    fn set_tspace(
+        &mut self,
+        set: usize,
+        tangent: [f32; 3],
+        bi_tangent: [f32; 3],
+        mag_s: f32,
+        mag_t: f32,
+        is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.outputs[set].push(Output {
+            tangent,
+            bi_tangent,
+            mag_s,
+            mag_t,
+            is_orientation_preserving,
+            face,
+            vert,
+        });
+    }
+}
+
+fn detail_tex_coords(mesh: &Mesh) -> Vec<[f32; 2]> {
+    mesh.positions
+        .iter()
+        .map(|p| [4.0 * p[1] - p[2], 3.0 * p[0]])
+        .collect()
+}
+
+#[test]
+fn each_set_should_match_single_set_generation() {
+    let mut multi = TwoSets {
+        mesh: make_grid(8),
+        detail_tex_coords: detail_tex_coords(&make_grid(8)),
+        outputs: [Vec::new(), Vec::new()],
+        fallbacks: [Vec::new(), Vec::new()],
+        progress: [Vec::new(), Vec::new()],
+    };
+    assert!(gen_tang_space_multi(&mut multi, 60.0));
+
+    let mut base = Recorder::new(make_grid(8));
+    assert!(gen_tang_space(&mut base, 60.0));
+
+    let mut detail_mesh = make_grid(8);
+    detail_mesh.tex_coords = detail_tex_coords(&detail_mesh);
+    let mut detail = Recorder::new(detail_mesh);
+    assert!(gen_tang_space(&mut detail, 60.0));
+
+    assert_eq!(multi.outputs[0], base.outputs);
+    assert_eq!(multi.outputs[1], detail.outputs);
+    assert_ne!(multi.outputs[0], multi.outputs[1]);
+    assert_eq!(multi.progress[0], base.progress);
+    assert_eq!(multi.progress[1], detail.progress);
+}
+
+#[test]
+fn options_apply_to_every_set() {
+    let options = GenOptions {
+        angular_threshold: 60.0,
+        uniform_orientation: true,
+        fallback: FallbackPolicy::NormalBasis,
+        ..GenOptions::default()
+    };
+    // The cube's +y and -y sides have no usable texture coordinates.
+    let mut multi = TwoSets {
+        mesh: make_cube(),
+        detail_tex_coords: detail_tex_coords(&make_cube()),
+        outputs: [Vec::new(), Vec::new()],
+        fallbacks: [Vec::new(), Vec::new()],
+        progress: [Vec::new(), Vec::new()],
+    };
+    let stats = gen_tang_space_multi_ex(&mut multi, &options).unwrap();
+    assert_eq!(stats.len(), 2);
+
+    let mut base = Recorder::new(make_cube());
+    gen_tang_space_ex(&mut base, &options).unwrap();
+    let mut detail_mesh = make_cube();
+    detail_mesh.tex_coords = detail_tex_coords(&detail_mesh);
+    let mut detail = Recorder::new(detail_mesh);
+    gen_tang_space_ex(&mut detail, &options).unwrap();
+
+    assert!(!base.fallbacks.is_empty());
+    assert_eq!(multi.outputs[0], base.outputs);
+    assert_eq!(multi.fallbacks[0], base.fallbacks);
+    assert_eq!(multi.outputs[1], detail.outputs);
+    assert_eq!(multi.fallbacks[1], detail.fallbacks);
+}
+
+#[test]
+fn memory_limit_fails_with_error() {
+    let mut multi = TwoSets {
+        mesh: make_grid(8),
+        detail_tex_coords: detail_tex_coords(&make_grid(8)),
+        outputs: [Vec::new(), Vec::new()],
+        fallbacks: [Vec::new(), Vec::new()],
+        progress: [Vec::new(), Vec::new()],
+    };
+    let options = GenOptions {
+        memory_limit: Some(64),
+        ..GenOptions::default()
+    };
+    let result = gen_tang_space_multi_ex(&mut multi, &options);
+    assert!(matches!(result, Err(GenError::MemoryLimitExceeded { .. })));
+    assert!(multi.outputs[0].is_empty());
+}
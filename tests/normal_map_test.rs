@@ -0,0 +1,64 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+const SIZE: usize = 64;
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    [v[0] / len, v[1] / len, v[2] / len]
+}
+
+fn max_difference(a: &NormalMap, b: &NormalMap) -> f32 {
+    a.texels
+        .iter()
+        .zip(b.texels.iter())
+        .flat_map(|(x, y)| (0..3).map(move |i| (x[i] - y[i]).abs()))
+        .fold(0.0, f32::max)
+}
+
+/// Some bumps in tangent space, covering both halves of the mirrored grid.
+fn bumpy_map() -> NormalMap {
+    let mut map = NormalMap::new(SIZE, SIZE, [0.0, 0.0, 1.0]);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let (u, v) = (x as f32 / SIZE as f32, y as f32 / SIZE as f32);
+            let n = [0.4 * (9.0 * u).sin(), 0.3 * (7.0 * v).cos(), 1.0];
+            map.set(x, y, normalize(n));
+        }
+    }
+    map
+}
+
+#[test]
+fn round_trip_is_exact() {
+    let mut mesh = Recorder::new(make_grid(8));
+    let options = GenOptions::default();
+    let tangent = bumpy_map();
+    let object = tangent_to_object_space(&mut mesh, &options, &tangent).unwrap();
+    assert!(max_difference(&object, &tangent) > 0.1);
+    let back = object_to_tangent_space(&mut mesh, &options, &object).unwrap();
+    assert!(max_difference(&back, &tangent) < 1e-5);
+}
+
+#[test]
+fn flat_map_follows_interpolated_normals() {
+    let mut mesh = Recorder::new(make_grid(8));
+    let flat = NormalMap::new(SIZE, SIZE, [0.0, 0.0, 1.0]);
+    let object = tangent_to_object_space(&mut mesh, &GenOptions::default(), &flat).unwrap();
+    // The grid spans texture space [0, 0.5] twice, texels above u = 0.5 stay
+    // untouched.
+    assert_eq!(object.get(SIZE - 1, 0), [0.0, 0.0, 1.0]);
+    let n = object.get(5, 20);
+    assert!(n[2] > 0.5 && n[2] < 1.0);
+    assert!((n[0] * n[0] + n[1] * n[1] + n[2] * n[2] - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn rgb8_encoding() {
+    let map = NormalMap::from_rgb8(1, 2, &[128, 128, 255, 255, 128, 128]);
+    assert!((map.get(0, 0)[2] - 1.0).abs() < 1e-2);
+    assert!((map.get(0, 1)[0] - 1.0).abs() < 1e-2);
+    assert_eq!(map.to_rgb8(), vec![128, 128, 255, 255, 128, 128]);
+}
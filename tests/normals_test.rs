@@ -0,0 +1,230 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+fn without_normals(mut mesh: Mesh) -> Recorder {
+    for normal in mesh.normals.iter_mut() {
+        *normal = [0.0; 3];
+    }
+    Recorder::new(mesh)
+}
+
+fn generate(mesh: Mesh, options: &NormalOptions) -> Recorder {
+    let mut recorder = without_normals(mesh);
+    gen_normals(&mut recorder, options);
+    recorder
+}
+
+fn close(a: [f32; 3], b: [f32; 3], tolerance: f32) -> bool {
+    (0..3).all(|i| (a[i] - b[i]).abs() <= tolerance)
+}
+
+#[test]
+fn cube_corners_are_smoothed() {
+    let recorder = generate(make_cube(), &NormalOptions::default());
+    assert_eq!(recorder.normals.len(), 6 * 4 * 3);
+    for &(face, vert, normal) in &recorder.normals {
+        let p = recorder.get_position(face, vert);
+        let expected = if p.iter().all(|x| x.abs() == 0.5) {
+            let s = 1.0 / 3f32.sqrt();
+            [p[0].signum() * s, p[1].signum() * s, p[2].signum() * s]
+        } else {
+            // Side centers
+            [p[0] * 2.0, p[1] * 2.0, p[2] * 2.0]
+        };
+        assert!(close(normal, expected, 1e-6), "{:?} at {:?}", normal, p);
+    }
+}
+
+#[test]
+fn smoothing_angle_keeps_hard_edges() {
+    let options = NormalOptions {
+        smoothing_angle: 60.0,
+        ..NormalOptions::default()
+    };
+    let recorder = generate(make_cube(), &options);
+    for &(face, vert, normal) in &recorder.normals {
+        let center = recorder.get_position(face, 2);
+        let side = [center[0] * 2.0, center[1] * 2.0, center[2] * 2.0];
+        assert!(
+            close(normal, side, 0.0),
+            "{:?} of face {} vert {}",
+            normal,
+            face,
+            vert
+        );
+    }
+}
+
+#[test]
+fn grid_normals_approximate_the_surface() {
+    for &weighting in [NormalWeighting::Area, NormalWeighting::Angle].iter() {
+        let options = NormalOptions {
+            weighting,
+            ..NormalOptions::default()
+        };
+        let recorder = generate(make_grid(32), &options);
+        let reference = make_grid(32);
+        for &(face, vert, normal) in &recorder.normals {
+            let p = recorder.get_position(face, vert);
+            if p[0] == 0.0 || p[0] == 1.0 || p[1] == 0.0 || p[1] == 1.0 {
+                // One sided at the border.
+                continue;
+            }
+            let expected = reference.normals[reference.index(face, vert)];
+            assert!(close(normal, expected, 0.02), "{:?} {:?}", normal, expected);
+        }
+    }
+}
+
+#[test]
+fn tangents_are_built_on_generated_normals() {
+    let mut recorder = without_normals(make_grid(8));
+    gen_tang_space_with_normals(
+        &mut recorder,
+        &NormalOptions::default(),
+        &GenOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(recorder.normals.len(), recorder.outputs.len());
+    for output in &recorder.outputs {
+        let &(_, _, n) = recorder
+            .normals
+            .iter()
+            .find(|&&(face, vert, _)| (face, vert) == (output.face, output.vert))
+            .unwrap();
+        let t = output.tangent;
+        assert!((t[0] * n[0] + t[1] * n[1] + t[2] * n[2]).abs() < 1e-5);
+    }
+}
+
+/// Positions and texture coordinates only.
+struct Unlit {
+    mesh: Mesh,
+    tangents: Vec<(usize, usize, [f32; 3])>,
+}
+
+impl NormalFreeInterface for Unlit {
+    fn get_num_faces(&self) -> usize {
+        self.mesh.faces.len()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.mesh.faces[face].len()
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.positions[self.mesh.faces[face][vert] as usize]
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.mesh.tex_coords[self.mesh.faces[face][vert] as usize]
+    }
+
+    fn set_tspace_basic(&mut self, tangent: [f32; 3], _sign: f32, face: usize, vert: usize) {
+        self.tangents.push((face, vert, tangent));
+    }
+}
+
+#[test]
+fn meshes_without_normals_generate_through_the_adapter() {
+    let mut unlit = Unlit {
+        mesh: make_grid(8),
+        tangents: Vec::new(),
+    };
+    gen_tang_space_normal_free(
+        &mut unlit,
+        &NormalOptions::default(),
+        &GenOptions::default(),
+    )
+    .unwrap();
+    let mut expected = without_normals(make_grid(8));
+    gen_tang_space_with_normals(
+        &mut expected,
+        &NormalOptions::default(),
+        &GenOptions::default(),
+    )
+    .unwrap();
+    let expected: Vec<_> = expected
+        .outputs
+        .iter()
+        .map(|output| (output.face, output.vert, output.tangent))
+        .collect();
+    assert_eq!(unlit.tangents, expected);
+}
+
+/// A mesh split into smoothing groups or with sharp edges.
+struct Creased {
+    mesh: Mesh,
+    groups: fn(usize) -> u32,
+    sharp: bool,
+    normals: Vec<(usize, usize, [f32; 3])>,
+}
+
+impl MikkTSpaceInterface for Creased {
+    fn get_num_faces(&self) -> usize {
+        self.mesh.faces.len()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.mesh.faces[face].len()
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.positions[self.mesh.faces[face][vert] as usize]
+    }
+
+    fn get_normal(&self, _face: usize, _vert: usize) -> [f32; 3] {
+        [0.0; 3]
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.mesh.tex_coords[self.mesh.faces[face][vert] as usize]
+    }
+
+    fn get_smoothing_group(&self, face: usize) -> u32 {
+        (self.groups)(face)
+    }
+
+    fn is_edge_sharp(&self, _face: usize, _vert: usize) -> bool {
+        self.sharp
+    }
+
+    fn set_tspace_basic(&mut self, _tangent: [f32; 3], _sign: f32, _face: usize, _vert: usize) {}
+
+    fn set_normal(&mut self, normal: [f32; 3], face: usize, vert: usize) {
+        self.normals.push((face, vert, normal));
+    }
+}
+
+fn creased_cube(groups: fn(usize) -> u32, sharp: bool) -> Vec<(usize, usize, [f32; 3])> {
+    let mut creased = Creased {
+        mesh: make_cube(),
+        groups,
+        sharp,
+        normals: Vec::new(),
+    };
+    gen_normals(&mut creased, &NormalOptions::default());
+    creased.normals
+}
+
+#[test]
+fn smoothing_groups_and_sharp_edges_keep_hard_edges() {
+    let hard = generate(
+        make_cube(),
+        &NormalOptions {
+            smoothing_angle: 60.0,
+            ..NormalOptions::default()
+        },
+    );
+    // Every side is four triangles, one group per side.
+    assert_eq!(creased_cube(|face| 1 << (face / 4), false), hard.normals);
+    assert_eq!(creased_cube(|_| 1, true), hard.normals);
+
+    let smooth = generate(make_cube(), &NormalOptions::default());
+    assert_eq!(
+        creased_cube(|face| 1 | 1 << (face / 4), false),
+        smooth.normals
+    );
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+/// A healthy triangle next to one whose texture coordinates have zero area.
+fn make_bad_uv_pair() -> Mesh {
+    Mesh {
+        faces: vec![vec![0, 1, 2], vec![1, 3, 2]],
+        positions: vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+        ],
+        normals: vec![[0.0, 0.0, 1.0]; 4],
+        tex_coords: vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [0.5, 0.5]],
+    }
+}
+
+fn orientations(recorder: &Recorder, face: usize) -> Vec<bool> {
+    recorder
+        .outputs
+        .iter()
+        .filter(|o| o.face == face)
+        .map(|o| o.is_orientation_preserving)
+        .collect()
+}
+
+#[test]
+fn default_options_should_match_gen_tang_space() {
+    let mut reference = Recorder::new(make_grid(6));
+    assert!(gen_tang_space(&mut reference, 45.0));
+
+    let mut ex = Recorder::new(make_grid(6));
+    let options = GenOptions {
+        angular_threshold: 45.0,
+        ..GenOptions::default()
+    };
+    gen_tang_space_ex(&mut ex, &options).unwrap();
+    assert_eq!(reference.outputs, ex.outputs);
+}
+
+#[test]
+fn uniform_orientation_should_apply_to_whole_face() {
+    let mut reference = Recorder::new(make_bad_uv_pair());
+    assert!(gen_tang_space_default(&mut reference));
+    assert_eq!(orientations(&reference, 0), vec![true, true, true]);
+    assert_eq!(orientations(&reference, 1), vec![true, false, true]);
+
+    let mut uniform = Recorder::new(make_bad_uv_pair());
+    let options = GenOptions {
+        uniform_orientation: true,
+        ..GenOptions::default()
+    };
+    gen_tang_space_ex(&mut uniform, &options).unwrap();
+    assert_eq!(orientations(&uniform, 0), vec![true, true, true]);
+    assert_eq!(orientations(&uniform, 1), vec![true, true, true]);
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[test]
+fn normal_basis_fallback_should_be_orthonormal() {
+    let mut mesh = make_bad_uv_pair();
+    let n = 1.0 / f32::sqrt(3.0);
+    mesh.normals = vec![[n, n, n]; 4];
+
+    let mut reference = Recorder::new(mesh);
+    assert!(gen_tang_space_default(&mut reference));
+    assert!(reference.fallbacks.is_empty());
+    assert_eq!(reference.outputs[4].tangent, [1.0, 0.0, 0.0]);
+    assert_eq!(reference.outputs[4].bi_tangent, [0.0, 1.0, 0.0]);
+
+    let mut fallback = Recorder::new(reference.mesh);
+    let options = GenOptions {
+        fallback: FallbackPolicy::NormalBasis,
+        ..GenOptions::default()
+    };
+    gen_tang_space_ex(&mut fallback, &options).unwrap();
+    assert_eq!(fallback.fallbacks, vec![(1, 1)]);
+
+    let output = fallback.outputs[4];
+    assert_eq!((output.face, output.vert), (1, 1));
+    assert!(output.is_orientation_preserving);
+    assert!(dot(output.tangent, [n, n, n]).abs() < 1e-6);
+    assert!(dot(output.bi_tangent, [n, n, n]).abs() < 1e-6);
+    assert!(dot(output.tangent, output.bi_tangent).abs() < 1e-6);
+    assert!((dot(output.tangent, output.tangent) - 1.0).abs() < 1e-6);
+    assert!((dot(output.bi_tangent, output.bi_tangent) - 1.0).abs() < 1e-6);
+    assert_eq!(&fallback.outputs[..4], &reference.outputs[..4]);
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+fn to_ply(mesh: Mesh) -> PlyMesh {
+    PlyMesh {
+        positions: mesh.positions,
+        normals: mesh.normals,
+        tex_coords: mesh.tex_coords,
+        tangents: Vec::new(),
+        faces: mesh.faces,
+    }
+}
+
+fn round_trip(mesh: &PlyMesh, format: PlyFormat) -> PlyMesh {
+    let mut bytes = Vec::new();
+    write_ply(&mut bytes, mesh, format).unwrap();
+    read_ply(&bytes[..]).unwrap()
+}
+
+#[test]
+fn round_trips_all_formats() {
+    let mut mesh = to_ply(make_grid(5));
+    for &format in &[
+        PlyFormat::Ascii,
+        PlyFormat::BinaryLittleEndian,
+        PlyFormat::BinaryBigEndian,
+    ] {
+        assert_eq!(round_trip(&mesh, format), mesh);
+    }
+    mesh.generate_tangents(&GenOptions::default()).unwrap();
+    for &format in &[
+        PlyFormat::Ascii,
+        PlyFormat::BinaryLittleEndian,
+        PlyFormat::BinaryBigEndian,
+    ] {
+        assert_eq!(round_trip(&mesh, format), mesh);
+    }
+}
+
+#[test]
+fn reads_other_layouts() {
+    let ply = "ply\r\n\
+        format ascii 1.0\r\n\
+        comment from a scanner\r\n\
+        element vertex 4\r\n\
+        property double x\r\n\
+        property double y\r\n\
+        property double z\r\n\
+        property uchar red\r\n\
+        property float nx\r\n\
+        property float ny\r\n\
+        property float nz\r\n\
+        property float u\r\n\
+        property float v\r\n\
+        element face 2\r\n\
+        property list uchar int vertex_index\r\n\
+        property int flags\r\n\
+        element edge 1\r\n\
+        property int vertex1\r\n\
+        property int vertex2\r\n\
+        end_header\r\n\
+        0 0 0 255 0 0 1 0 0\r\n\
+        1 0 0 255 0 0 1 1 0\r\n\
+        1 1 0 255 0 0 1 1 1\r\n\
+        0 1 0 255 0 0 1 0.1 1\r\n\
+        3 0 1 2 7\r\n\
+        3 0 2 3 7\r\n\
+        0 2\r\n";
+    let mesh = read_ply(ply.as_bytes()).unwrap();
+    assert_eq!(mesh.positions[2], [1.0, 1.0, 0.0]);
+    assert_eq!(mesh.normals, vec![[0.0, 0.0, 1.0]; 4]);
+    assert_eq!(mesh.tex_coords[3], [0.1, 1.0]);
+    assert!(mesh.tangents.is_empty());
+    assert_eq!(mesh.faces, [[0, 1, 2], [0, 2, 3]]);
+}
+
+#[test]
+fn tangents_match_reference() {
+    let mut mesh = to_ply(make_grid(6));
+    let num_vertices = mesh.positions.len();
+    let mut expected = Recorder::new(make_grid(6));
+    gen_tang_space_ex(&mut expected, &GenOptions::default()).unwrap();
+
+    mesh.generate_tangents(&GenOptions::default()).unwrap();
+    // The mirrored seam down the middle splits its vertices.
+    assert!(mesh.positions.len() > num_vertices);
+    assert_eq!(mesh.tangents.len(), mesh.positions.len());
+    for output in &expected.outputs {
+        let vertex = mesh.faces[output.face][output.vert] as usize;
+        let tangent = mesh.tangents[vertex];
+        assert_eq!([tangent[0], tangent[1], tangent[2]], output.tangent);
+        assert_eq!(tangent[3] > 0.0, output.is_orientation_preserving);
+        let original = expected.mesh.index(output.face, output.vert);
+        assert_eq!(mesh.positions[vertex], expected.mesh.positions[original]);
+        assert_eq!(mesh.tex_coords[vertex], expected.mesh.tex_coords[original]);
+    }
+}
+
+#[test]
+fn exports_tangent_properties() {
+    let mut mesh = to_ply(make_cube());
+    mesh.generate_tangents(&GenOptions::default()).unwrap();
+    let mut bytes = Vec::new();
+    write_ply(&mut bytes, &mesh, PlyFormat::Ascii).unwrap();
+    let text = String::from_utf8(bytes).unwrap();
+    assert!(text.contains("property float s\nproperty float t\nproperty float tx\n"));
+    assert!(text.contains("property float tw\nelement face"));
+}
+
+#[test]
+fn reports_errors() {
+    let header = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\n\
+        property float y\nproperty float z\nproperty float nx\nproperty float ny\n\
+        property float nz\nelement face 0\nproperty list uchar uint vertex_indices\n\
+        end_header\n";
+    assert!(matches!(
+        read_ply(header.as_bytes()),
+        Err(PlyError::MissingProperty("s"))
+    ));
+
+    let mut bytes = Vec::new();
+    let mut mesh = to_ply(make_cube());
+    mesh.faces[3][1] = mesh.positions.len() as u32;
+    write_ply(&mut bytes, &mesh, PlyFormat::BinaryLittleEndian).unwrap();
+    assert!(matches!(read_ply(&bytes[..]), Err(PlyError::Invalid(_))));
+
+    bytes.clear();
+    write_ply(&mut bytes, &to_ply(make_cube()), PlyFormat::BinaryBigEndian).unwrap();
+    bytes.pop();
+    assert!(matches!(read_ply(&bytes[..]), Err(PlyError::Io(_))));
+}
+
+#[test]
+fn huge_list_counts_fail_without_allocating() {
+    let ply = "ply\nformat binary_little_endian 1.0\nelement vertex 0\n\
+        property float x\nproperty float y\nproperty float z\nproperty float s\n\
+        property float t\nelement face 1\nproperty list uint uint vertex_indices\n\
+        end_header\n";
+    let mut bytes = ply.as_bytes().to_vec();
+    bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    assert!(matches!(read_ply(&bytes[..]), Err(PlyError::Io(_))));
+}
+
+#[test]
+fn invalid_meshes_are_rejected_before_generation() {
+    let mut mesh = to_ply(make_cube());
+    mesh.tex_coords.pop();
+    let original = mesh.clone();
+    assert!(matches!(
+        mesh.generate_tangents(&GenOptions::default()),
+        Err(PlyError::Invalid(_))
+    ));
+    assert_eq!(mesh, original);
+
+    let mut mesh = to_ply(make_cube());
+    mesh.faces[2][1] = mesh.positions.len() as u32;
+    let original = mesh.clone();
+    assert!(matches!(
+        mesh.generate_tangents(&GenOptions::default()),
+        Err(PlyError::Invalid(_))
+    ));
+    assert_eq!(mesh, original);
+}
+
+#[test]
+fn normals_are_generated_when_missing() {
+    let mut mesh = to_ply(make_cube());
+    mesh.normals.clear();
+    let read = round_trip(&mesh, PlyFormat::Ascii);
+    assert_eq!(read, mesh);
+
+    let mut expected = Recorder::new(make_cube());
+    gen_tang_space_with_normals(
+        &mut expected,
+        &NormalOptions::default(),
+        &GenOptions::default(),
+    )
+    .unwrap();
+    mesh.generate_tangents(&GenOptions::default()).unwrap();
+    assert_eq!(mesh.normals.len(), mesh.positions.len());
+    for &(face, vert, normal) in &expected.normals {
+        assert_eq!(mesh.normals[mesh.faces[face][vert] as usize], normal);
+    }
+    for output in &expected.outputs {
+        let tangent = mesh.tangents[mesh.faces[output.face][output.vert] as usize];
+        assert_eq!([tangent[0], tangent[1], tangent[2]], output.tangent);
+    }
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+#[test]
+fn every_phase_reports_until_complete() {
+    let mut recorder = Recorder::new(make_grid(128));
+    gen_tang_space_ex(&mut recorder, &GenOptions::default()).unwrap();
+    let progress = &recorder.progress;
+
+    for pair in progress.windows(2) {
+        assert!(pair[0].overall() <= pair[1].overall(), "{:?}", pair);
+    }
+    for &phase in &Phase::ALL {
+        let reports: Vec<&Progress> = progress.iter().filter(|p| p.phase == phase).collect();
+        let last = reports.last().expect("phase without progress");
+        assert_eq!(last.done, last.total, "{:?}", phase);
+        assert!(reports.iter().all(|p| p.done <= p.total));
+        // Reports are throttled.
+        assert!(reports.len() <= 300, "{:?}: {}", phase, reports.len());
+    }
+    // The grid is large enough for intermediate reports.
+    let tri_info = progress
+        .iter()
+        .filter(|p| p.phase == Phase::TriInfo && p.done < p.total)
+        .count();
+    assert!(tri_info > 1);
+    assert_eq!(progress.last().unwrap().overall(), 1.0);
+}
+
+#[test]
+fn f64_generation_reports_progress() {
+    let mut recorder = Recorder::new(make_cube());
+    gen_tang_space_f64(&mut recorder, &GenOptions::default()).unwrap();
+    assert_eq!(
+        recorder.progress.last().unwrap().phase,
+        Phase::DegenEpilogue
+    );
+    assert_eq!(recorder.progress.last().unwrap().overall(), 1.0);
+}
+
+#[test]
+fn collecting_reports_progress_to_the_mesh() {
+    let mut recorder = Recorder::new(make_grid(16));
+    gen_tang_space_ex(&mut recorder, &GenOptions::default()).unwrap();
+    let mut collected = Recorder::new(make_grid(16));
+    gen_tang_space_collect(&mut collected, &GenOptions::default()).unwrap();
+    assert_eq!(collected.progress, recorder.progress);
+
+    let mut converted = Recorder::new(make_grid(16));
+    let map = NormalMap::new(8, 8, [0.0, 0.0, 1.0]);
+    tangent_to_object_space(&mut converted, &GenOptions::default(), &map).unwrap();
+    assert_eq!(converted.progress, recorder.progress);
+}
@@ -0,0 +1,22 @@
+use mikktspace_sys::*;
+
+#[test]
+fn equal_keys_share_vertices() {
+    let indices = [0, 1, 2, 2, 1, 3];
+    let keys = [7, 7, 7, 7, 7, 7];
+    let reindexed = reindex(&indices, 4, &keys);
+    assert_eq!(reindexed.indices, indices);
+    assert_eq!(reindexed.sources, [0, 1, 2, 3]);
+    assert!(reindexed.splits(4).is_empty());
+}
+
+#[test]
+fn different_keys_split_vertices() {
+    let indices = [0, 1, 2, 2, 1, 3, 1, 0, 3];
+    let keys = ['a', 'a', 'a', 'b', 'b', 'a', 'b', 'c', 'a'];
+    let reindexed = reindex(&indices, 5, &keys);
+    // Vertex 4 is unused and keeps its place.
+    assert_eq!(reindexed.sources, [0, 1, 2, 3, 4, 2, 1, 0]);
+    assert_eq!(reindexed.indices, [0, 1, 2, 5, 6, 3, 6, 7, 3]);
+    assert_eq!(reindexed.splits(5), [2, 1, 0]);
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+/// Two quads sharing an edge, the second one with its texture mirrored and
+/// moved to its own island.
+fn make_mirrored_pair() -> Mesh {
+    Mesh {
+        faces: vec![vec![0, 1, 4, 3], vec![5, 2, 6, 7]],
+        positions: vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [2.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+        ],
+        normals: vec![[0.0, 0.0, 1.0]; 8],
+        tex_coords: vec![
+            [0.0, 0.0],
+            [0.4, 0.0],
+            [0.6, 0.0],
+            [0.0, 0.4],
+            [0.4, 0.4],
+            [1.0, 0.0],
+            [0.6, 0.4],
+            [1.0, 0.4],
+        ],
+    }
+}
+
+#[test]
+fn mirrored_island_is_reported() {
+    let mut mesh = Recorder::new(make_mirrored_pair());
+    let report = uv_seam_report(&mut mesh, &GenOptions::default()).unwrap();
+    assert_eq!(report.preserving_corners, 4);
+    assert_eq!(report.mirrored_corners, 4);
+    assert_eq!(report.islands.len(), 2);
+    assert!(!report.islands[0].is_mirrored());
+    assert_eq!(
+        report.mirrored_islands().collect::<Vec<_>>(),
+        vec![&UvIsland {
+            faces: vec![1],
+            preserving_corners: 0,
+            mirrored_corners: 4,
+        }]
+    );
+    assert_eq!(
+        report.seams,
+        vec![SeamEdge {
+            first: (0, 1),
+            second: (1, 3),
+            kind: SeamKind::Mirror,
+        }]
+    );
+}
+
+#[test]
+fn grid_mirror_line_splits_tangents_inside_one_island() {
+    let n = 8;
+    let mut mesh = Recorder::new(make_grid(n));
+    let report = uv_seam_report(&mut mesh, &GenOptions::default()).unwrap();
+    // The texture coordinates are continuous across the mirror line.
+    assert_eq!(report.islands.len(), 1);
+    assert_eq!(report.preserving_corners, n * n * 2);
+    assert_eq!(report.mirrored_corners, n * n * 2);
+    assert_eq!(report.seams.len(), n);
+    for seam in &report.seams {
+        assert_eq!(seam.kind, SeamKind::Mirror);
+        assert_eq!(seam.first.0 % n, n / 2 - 1);
+        assert_eq!(seam.second.0, seam.first.0 + 1);
+    }
+}
+
+#[test]
+fn islands_partition_the_cube() {
+    let mut mesh = Recorder::new(make_cube());
+    let report = uv_seam_report(&mut mesh, &GenOptions::default()).unwrap();
+    let mut faces: Vec<usize> = report
+        .islands
+        .iter()
+        .flat_map(|island| island.faces.iter().copied())
+        .collect();
+    faces.sort_unstable();
+    assert_eq!(faces, (0..24).collect::<Vec<_>>());
+    let corners: usize = report
+        .islands
+        .iter()
+        .map(|island| island.preserving_corners + island.mirrored_corners)
+        .sum();
+    assert_eq!(corners, 6 * 4 * 3);
+    assert_eq!(report.preserving_corners + report.mirrored_corners, corners);
+    assert!(report.islands.len() > 1);
+    assert!(report.seams.iter().any(|seam| seam.kind == SeamKind::Uv));
+    assert!(report
+        .seams
+        .iter()
+        .any(|seam| seam.kind == SeamKind::Mirror));
+}
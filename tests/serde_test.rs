@@ -0,0 +1,81 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+fn round_trip<T>(value: &T) -> T
+where
+    T: Serialize + DeserializeOwned,
+{
+    serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+}
+
+#[test]
+fn options_round_trip() {
+    let options = GenOptions {
+        angular_threshold: 45.0,
+        uniform_orientation: true,
+        fallback: FallbackPolicy::NormalBasis,
+        memory_limit: Some(1 << 20),
+        quadratic_fallback: true,
+    };
+    assert_eq!(round_trip(&options), options);
+    let normal_options = NormalOptions {
+        weighting: NormalWeighting::Area,
+        smoothing_angle: 60.0,
+    };
+    assert_eq!(round_trip(&normal_options), normal_options);
+    let frame_options = FrameOptions {
+        scale: 0.25,
+        inset: 0.1,
+    };
+    assert_eq!(round_trip(&frame_options), frame_options);
+}
+
+#[test]
+fn results_round_trip_bit_exact() {
+    let mut mesh = Recorder::new(make_grid(8));
+    let tspaces = gen_tang_space_collect(&mut mesh, &GenOptions::default()).unwrap();
+    let restored = round_trip(&tspaces);
+    for (a, b) in tspaces.corners().iter().zip(restored.corners()) {
+        assert_eq!(a.tangent.map(f32::to_bits), b.tangent.map(f32::to_bits));
+        assert_eq!(
+            a.bi_tangent.map(f32::to_bits),
+            b.bi_tangent.map(f32::to_bits)
+        );
+    }
+    assert_eq!(restored, tspaces);
+    assert_eq!(restored.get(3, 2), tspaces.get(3, 2));
+}
+
+#[test]
+fn reports_round_trip() {
+    let mut mesh = Recorder::new(make_cube());
+    let stats = gen_tang_space_ex(&mut mesh, &GenOptions::default()).unwrap();
+    assert_eq!(round_trip(&stats), stats);
+    let seams = uv_seam_report(&mut mesh, &GenOptions::default()).unwrap();
+    assert_eq!(round_trip(&seams), seams);
+    let density = texel_density_report(&mesh);
+    assert_eq!(round_trip(&density), density);
+    let report = gen_tang_space_validate(&mut mesh, &GenOptions::default(), 1e-4).unwrap();
+    assert_eq!(round_trip(&report), report);
+    let inspection = gen_tang_space_inspect(&mut mesh, &GenOptions::default()).unwrap();
+    assert_eq!(round_trip(&inspection), inspection);
+    let tspaces = gen_tang_space_collect(&mut mesh, &GenOptions::default()).unwrap();
+    let frames = tangent_frames(&mesh, &tspaces, &FrameOptions::default());
+    assert_eq!(round_trip(&frames), frames);
+    let error = GenError::MemoryLimitExceeded {
+        limit: 1024,
+        requested: 4096,
+    };
+    assert_eq!(round_trip(&error), error);
+    let error = BakeError::CageVertexCount {
+        face: 2,
+        expected: 4,
+        found: 3,
+    };
+    assert_eq!(round_trip(&error), error);
+}
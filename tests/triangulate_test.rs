@@ -0,0 +1,104 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+/// A single quad with the given corner positions and texture coordinates.
+fn quad(positions: [[f32; 3]; 4], tex_coords: [[f32; 2]; 4]) -> Recorder {
+    Recorder::new(Mesh {
+        faces: vec![vec![0, 1, 2, 3]],
+        positions: positions.to_vec(),
+        normals: vec![[0.0, 0.0, 1.0]; 4],
+        tex_coords: tex_coords.to_vec(),
+    })
+}
+
+const SQUARE_UV: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+#[test]
+fn shorter_diagonal_wins() {
+    let kite = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.5, 1.5, 0.0],
+        [0.0, 1.0, 0.0],
+    ];
+    assert_eq!(QuadSplit::shortest(kite, SQUARE_UV), QuadSplit::Diagonal13);
+    let kite = [
+        [0.0, 0.0, 0.0],
+        [1.0, -0.5, 0.0],
+        [1.0, 1.0, 0.0],
+        [-0.5, 1.0, 0.0],
+    ];
+    assert_eq!(QuadSplit::shortest(kite, SQUARE_UV), QuadSplit::Diagonal02);
+}
+
+#[test]
+fn ties_compare_texture_coordinates() {
+    let square = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+    ];
+    let uv = [[0.0, 0.0], [1.0, 0.0], [0.8, 0.8], [0.0, 1.0]];
+    assert_eq!(QuadSplit::shortest(square, uv), QuadSplit::Diagonal02);
+    let uv = [[0.2, 0.2], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+    assert_eq!(QuadSplit::shortest(square, uv), QuadSplit::Diagonal02);
+    let uv = [[0.0, 0.0], [0.8, 0.2], [1.0, 1.0], [0.0, 1.0]];
+    assert_eq!(QuadSplit::shortest(square, uv), QuadSplit::Diagonal13);
+}
+
+#[test]
+fn split_does_not_depend_on_the_first_corner() {
+    let positions = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.2],
+        [1.3, 1.1, 0.0],
+        [0.0, 1.0, 0.1],
+    ];
+    let mesh = quad(positions, SQUARE_UV);
+    let diagonal = |split: QuadSplit, shift: usize| match split {
+        QuadSplit::Diagonal02 => [shift % 4, (shift + 2) % 4],
+        QuadSplit::Diagonal13 => [(shift + 1) % 4, (shift + 3) % 4],
+    };
+    let mut expected = diagonal(split_quad(&mesh, 0).unwrap(), 0);
+    expected.sort_unstable();
+    for shift in 1..4 {
+        let rotate = |i: usize| (i + shift) % 4;
+        let rotated = quad(
+            [0, 1, 2, 3].map(|i| positions[rotate(i)]),
+            [0, 1, 2, 3].map(|i| SQUARE_UV[rotate(i)]),
+        );
+        let mut found = diagonal(split_quad(&rotated, 0).unwrap(), shift);
+        found.sort_unstable();
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn triangulate_keeps_triangles_and_splits_quads() {
+    let mesh = Recorder::new(make_cube());
+    assert_eq!(triangulate(&mesh).len(), mesh.mesh.faces.len());
+    let grid = Recorder::new(make_grid(4));
+    let triangles = triangulate(&grid);
+    assert_eq!(triangles.len(), 32);
+    for (i, tri) in triangles.iter().enumerate() {
+        let face = i / 2;
+        let split = split_quad(&grid, face).unwrap().triangles()[i % 2];
+        assert_eq!(*tri, [(face, split[0]), (face, split[1]), (face, split[2])]);
+    }
+}
+
+#[test]
+fn checker_flags_disagreeing_quads() {
+    let grid = Recorder::new(make_grid(4));
+    assert!(check_quad_splits(&grid, |face| split_quad(&grid, face).unwrap()).is_empty());
+    let mismatches = check_quad_splits(&grid, |_| QuadSplit::Diagonal02);
+    assert!(!mismatches.is_empty());
+    for mismatch in mismatches {
+        assert_eq!(mismatch.expected, QuadSplit::Diagonal13);
+        assert_eq!(mismatch.found, QuadSplit::Diagonal02);
+        assert_eq!(split_quad(&grid, mismatch.face), Some(mismatch.expected));
+    }
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+/// `Mesh` returning its attributes as `V3` and `V2`.
+struct TypedMesh<V3, V2> {
+    mesh: Mesh,
+    outputs: Vec<(V3, V3, usize, usize)>,
+    signs: Vec<(V3, f32)>,
+    _tex_coord: std::marker::PhantomData<V2>,
+}
+
+impl<V3: Vector3, V2: Vector2> TypedInterface for TypedMesh<V3, V2> {
+    type Vec3 = V3;
+    type Vec2 = V2;
+
+    fn get_num_faces(&self) -> usize {
+        self.mesh.faces.len()
+    }
+
+    fn get_num_vertices_of_face(&self, face: usize) -> usize {
+        self.mesh.faces[face].len()
+    }
+
+    fn get_position(&self, face: usize, vert: usize) -> V3 {
+        V3::from_array(self.mesh.positions[self.mesh.index(face, vert)])
+    }
+
+    fn get_normal(&self, face: usize, vert: usize) -> V3 {
+        V3::from_array(self.mesh.normals[self.mesh.index(face, vert)])
+    }
+
+    fn get_tex_coord(&self, face: usize, vert: usize) -> V2 {
+        V2::from_array(self.mesh.tex_coords[self.mesh.index(face, vert)])
+    }
+
+    fn set_tspace_basic(&mut self, tangent: V3, sign: f32, _face: usize, _vert: usize) {
+        self.signs.push((tangent, sign));
+    }
+
+    fn set_tspace(
+        &mut self,
+        tangent: V3,
+        bi_tangent: V3,
+        _mag_s: f32,
+        _mag_t: f32,
+        _is_orientation_preserving: bool,
+        face: usize,
+        vert: usize,
+    ) {
+        self.outputs.push((tangent, bi_tangent, face, vert));
+    }
+}
+
+fn matches_arrays<V3: Vector3, V2: Vector2>() {
+    let mut expected = Recorder::new(make_cube());
+    gen_tang_space_ex(&mut expected, &GenOptions::default()).unwrap();
+
+    let mut mesh = TypedMesh::<V3, V2> {
+        mesh: make_cube(),
+        outputs: Vec::new(),
+        signs: Vec::new(),
+        _tex_coord: std::marker::PhantomData,
+    };
+    gen_tang_space_typed(&mut mesh, &GenOptions::default()).unwrap();
+    assert_eq!(mesh.outputs.len(), expected.outputs.len());
+    for (&(tangent, bi_tangent, face, vert), output) in mesh.outputs.iter().zip(&expected.outputs) {
+        assert_eq!((face, vert), (output.face, output.vert));
+        assert_eq!(tangent.to_array(), output.tangent);
+        assert_eq!(bi_tangent.to_array(), output.bi_tangent);
+    }
+    for (&(tangent, sign), output) in mesh.signs.iter().zip(&expected.outputs) {
+        assert_eq!(tangent.to_array(), output.tangent);
+        assert_eq!(sign > 0.0, output.is_orientation_preserving);
+    }
+}
+
+#[test]
+fn arrays() {
+    matches_arrays::<[f32; 3], [f32; 2]>();
+}
+
+#[test]
+fn typed_mesh_works_with_other_functions() {
+    let mut mesh = TypedMesh::<[f32; 3], [f32; 2]> {
+        mesh: make_grid(4),
+        outputs: Vec::new(),
+        signs: Vec::new(),
+        _tex_coord: std::marker::PhantomData,
+    };
+    let report = gen_tang_space_validate(&mut Typed(&mut mesh), &GenOptions::default(), 1e-4);
+    assert!(report.unwrap().is_valid());
+    assert_eq!(mesh.outputs.len(), 4 * 4 * 4);
+}
+
+#[cfg(feature = "glam")]
+#[test]
+fn glam() {
+    matches_arrays::<glam::Vec3, glam::Vec2>();
+    matches_arrays::<glam::Vec3A, glam::Vec2>();
+}
+
+#[cfg(feature = "nalgebra")]
+#[test]
+fn nalgebra() {
+    matches_arrays::<nalgebra::Vector3<f32>, nalgebra::Vector2<f32>>();
+    matches_arrays::<nalgebra::Point3<f32>, nalgebra::Point2<f32>>();
+}
+
+#[cfg(feature = "mint")]
+#[test]
+fn mint() {
+    matches_arrays::<mint::Vector3<f32>, mint::Vector2<f32>>();
+    matches_arrays::<mint::Point3<f32>, mint::Point2<f32>>();
+}
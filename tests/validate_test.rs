@@ -0,0 +1,105 @@
+mod common;
+
+use common::*;
+use mikktspace_sys::*;
+
+const TOLERANCE: f32 = 1e-4;
+
+fn validate(mesh: Mesh, options: &GenOptions) -> (ValidationReport, Recorder) {
+    let mut recorder = Recorder::new(mesh);
+    let report = gen_tang_space_validate(&mut recorder, options, TOLERANCE).unwrap();
+    (report, recorder)
+}
+
+/// A triangle in the xz plane whose texture coordinates have zero area.
+fn make_unmapped_triangle() -> Mesh {
+    Mesh {
+        faces: vec![vec![0, 1, 2]],
+        positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+        normals: vec![[0.0, 1.0, 0.0]; 3],
+        tex_coords: vec![[0.5, 0.5]; 3],
+    }
+}
+
+#[test]
+fn healthy_mesh_is_valid() {
+    let (report, recorder) = validate(make_grid(12), &GenOptions::default());
+    assert_eq!(report.issues, vec![]);
+    assert_eq!(report.corners, 12 * 12 * 4);
+    assert_eq!(recorder.outputs.len(), 12 * 12 * 4);
+}
+
+#[test]
+fn cube_needs_the_normal_basis_fallback() {
+    // Two sides of the cube have texture coordinates of zero area.
+    let (report, _) = validate(make_cube(), &GenOptions::default());
+    assert!(!report.is_valid());
+    for issue in report.issues {
+        match issue {
+            Issue::NotPerpendicular { face: 8..=15, .. } | Issue::MixedSigns { face: 8..=15 } => {}
+            issue => panic!("unexpected issue {:?}", issue),
+        }
+    }
+
+    let options = GenOptions {
+        fallback: FallbackPolicy::NormalBasis,
+        uniform_orientation: true,
+        ..GenOptions::default()
+    };
+    let (report, _) = validate(make_cube(), &options);
+    assert_eq!(report.issues, vec![]);
+}
+
+#[test]
+fn reference_fallback_is_not_perpendicular() {
+    let (report, _) = validate(make_unmapped_triangle(), &GenOptions::default());
+    assert_eq!(report.corners, 3);
+    for vert in 0..3 {
+        assert!(report.issues.contains(&Issue::NotPerpendicular {
+            face: 0,
+            vert,
+            vector: Vector::BiTangent,
+            cosine: 1.0,
+        }));
+    }
+
+    let options = GenOptions {
+        fallback: FallbackPolicy::NormalBasis,
+        ..GenOptions::default()
+    };
+    let (report, _) = validate(make_unmapped_triangle(), &options);
+    assert!(report.is_valid(), "{:?}", report);
+}
+
+#[test]
+fn unsupported_faces_are_skipped() {
+    let mut mesh = make_grid(2);
+    mesh.faces.push(vec![0, 1, 4, 3, 2]);
+    let (report, _) = validate(mesh, &GenOptions::default());
+    assert!(report.is_valid());
+    assert_eq!(report.corners, 16);
+}
+
+#[test]
+fn stored_results_get_the_same_report() {
+    for options in [
+        GenOptions::default(),
+        GenOptions {
+            fallback: FallbackPolicy::NormalBasis,
+            ..GenOptions::default()
+        },
+    ] {
+        let (expected, mut recorder) = validate(make_cube(), &options);
+        let tspaces = gen_tang_space_collect(&mut recorder, &options).unwrap();
+        let report = mikktspace_sys::validate(&recorder, &tspaces, TOLERANCE);
+        assert_eq!(report, expected);
+    }
+}
+
+#[test]
+#[should_panic]
+fn stored_results_must_match_the_mesh() {
+    let mut small = Recorder::new(make_grid(4));
+    let tspaces = gen_tang_space_collect(&mut small, &GenOptions::default()).unwrap();
+    mikktspace_sys::validate(&Recorder::new(make_grid(8)), &tspaces, TOLERANCE);
+}